@@ -0,0 +1,763 @@
+// src/archive.rs
+use crate::db::{find_inventory_id_by_name, find_recipe_id_by_name, get_all_inventory,
+    get_all_recipe_ingredients, get_recipe_collection, insert_recipe_steps, read_transactions,
+    recipe_steps_from_instructions, upsert_inventory_item, upsert_recipe, upsert_recipe_ingredient};
+use crate::models::{InventoryItem, RecipeCollection, RecipeIngredient, Transaction};
+use chrono::Local;
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{params, params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Identifies a bakery_manager archive
+pub const ARCHIVE_FORMAT: &str = "bakery_manager";
+// Bump when the document layout changes in a way older readers can't handle
+pub const ARCHIVE_SCHEMA_VERSION: u32 = 1;
+
+// Tables carried as typed model sections; everything else goes in `tables`
+const MODEL_TABLES: [&str; 4] = ["inventory", "recipes", "recipe_ingredients", "transactions"];
+
+// The whole database as one JSON document
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatabaseArchive {
+    pub format: String,
+    pub schema_version: u32,
+    pub exported_at: String,
+    pub inventory: Vec<InventoryItem>,
+    pub recipes: Vec<RecipeCollection>,
+    pub recipe_ingredients: Vec<RecipeIngredient>,
+    pub transactions: Vec<Transaction>,
+    // Every other table, row by row as column -> value
+    #[serde(default)]
+    pub tables: BTreeMap<String, Vec<Map<String, Value>>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportMode {
+    // Match rows by natural key (names, transaction details) and add or update
+    Merge,
+    // Delete everything and load the archive exactly, ids included
+    ReplaceAll,
+}
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Db(rusqlite::Error),
+    Format(String),
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArchiveError::Io(e) => write!(f, "file error: {}", e),
+            ArchiveError::Json(e) => write!(f, "invalid JSON: {}", e),
+            ArchiveError::Db(e) => write!(f, "database error: {}", e),
+            ArchiveError::Format(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<io::Error> for ArchiveError {
+    fn from(e: io::Error) -> Self {
+        ArchiveError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ArchiveError {
+    fn from(e: serde_json::Error) -> Self {
+        ArchiveError::Json(e)
+    }
+}
+
+impl From<rusqlite::Error> for ArchiveError {
+    fn from(e: rusqlite::Error) -> Self {
+        ArchiveError::Db(e)
+    }
+}
+
+// What an import did, section by section
+#[derive(Debug, Default)]
+pub struct ArchiveSummary {
+    pub sections: Vec<(String, usize)>,
+    pub notes: Vec<String>,
+}
+
+// User tables in the database, in creation order
+fn table_names(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY rowid"
+    )?;
+    let rows = stmt.query_map([], |row| row.get(0))?;
+
+    let mut names = Vec::new();
+    for row in rows {
+        names.push(row?);
+    }
+
+    Ok(names)
+}
+
+fn column_names(conn: &Connection, table: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info(\"{}\")", table))?;
+    let rows = stmt.query_map([], |row| row.get(1))?;
+
+    let mut names = Vec::new();
+    for row in rows {
+        names.push(row?);
+    }
+
+    Ok(names)
+}
+
+// Primary-key columns of a table, in key order
+fn primary_key(conn: &Connection, table: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info(\"{}\")", table))?;
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, i32>(5)?, row.get::<_, String>(1)?)))?;
+
+    let mut keys = Vec::new();
+    for row in rows {
+        let (position, name) = row?;
+        if position > 0 {
+            keys.push((position, name));
+        }
+    }
+    keys.sort();
+
+    Ok(keys.into_iter().map(|(_, name)| name).collect())
+}
+
+// Columns that hold another table's id, as (column, parent table)
+fn references(conn: &Connection, table: &str) -> rusqlite::Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA foreign_key_list(\"{}\")", table))?;
+    let rows = stmt.query_map([], |row| Ok((row.get(3)?, row.get(2)?)))?;
+
+    let mut refs = Vec::new();
+    for row in rows {
+        refs.push(row?);
+    }
+    // Snapshot lines keep the inventory id they were taken from without a foreign key
+    if table == "inventory_snapshot_items" {
+        refs.push(("inventory_id".to_string(), "inventory".to_string()));
+    }
+
+    Ok(refs)
+}
+
+// Single-column unique keys (besides the primary key), e.g. pans.name
+fn unique_columns(conn: &Connection, table: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA index_list(\"{}\")", table))?;
+    let indexes = stmt.query_map([], |row| Ok((row.get::<_, String>(1)?, row.get::<_, bool>(2)?)))?;
+
+    let mut columns = Vec::new();
+    for index in indexes {
+        let (name, unique) = index?;
+        if !unique {
+            continue;
+        }
+        let mut info = conn.prepare(&format!("PRAGMA index_info(\"{}\")", name))?;
+        let indexed: Vec<String> = info.query_map([], |row| row.get(2))?.collect::<rusqlite::Result<_>>()?;
+        if indexed.len() == 1 {
+            columns.push(indexed[0].clone());
+        }
+    }
+
+    Ok(columns)
+}
+
+fn to_json(value: ValueRef) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => Value::from(i),
+        ValueRef::Real(f) => Value::from(f),
+        ValueRef::Text(text) => Value::from(String::from_utf8_lossy(text).into_owned()),
+        ValueRef::Blob(bytes) => Value::from(bytes.to_vec()),
+    }
+}
+
+fn to_sql(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or(0.0)),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        Value::Array(items) if items.iter().all(|v| v.as_u64().is_some_and(|b| b <= 255)) => {
+            SqlValue::Blob(items.iter().filter_map(|v| v.as_u64()).map(|b| b as u8).collect())
+        }
+        other => SqlValue::Text(other.to_string()),
+    }
+}
+
+// Dump a table without a model section as column -> value rows
+fn dump_table(conn: &Connection, table: &str) -> rusqlite::Result<Vec<Map<String, Value>>> {
+    let columns = column_names(conn, table)?;
+    let mut stmt = conn.prepare(&format!("SELECT * FROM \"{}\"", table))?;
+    let mut rows = stmt.query([])?;
+
+    let mut dumped = Vec::new();
+    while let Some(row) = rows.next()? {
+        let mut record = Map::new();
+        for (i, column) in columns.iter().enumerate() {
+            record.insert(column.clone(), to_json(row.get_ref(i)?));
+        }
+        dumped.push(record);
+    }
+
+    Ok(dumped)
+}
+
+// Load dumped rows back into a table, keeping only columns the table still has
+fn load_table(conn: &Connection, table: &str, rows: &[Map<String, Value>]) -> rusqlite::Result<usize> {
+    let columns = column_names(conn, table)?;
+    for row in rows {
+        let present: Vec<&String> = columns.iter().filter(|c| row.contains_key(*c)).collect();
+        if present.is_empty() {
+            continue;
+        }
+        let sql = format!(
+            "INSERT INTO \"{}\" ({}) VALUES ({})",
+            table,
+            present.iter().map(|c| format!("\"{}\"", c)).collect::<Vec<_>>().join(", "),
+            vec!["?"; present.len()].join(", ")
+        );
+        conn.execute(&sql, params_from_iter(present.iter().map(|c| to_sql(&row[*c]))))?;
+    }
+
+    Ok(rows.len())
+}
+
+// Read the whole database into an archive document
+pub fn build_archive(conn: &Connection) -> rusqlite::Result<DatabaseArchive> {
+    let mut tables = BTreeMap::new();
+    for table in table_names(conn)? {
+        if !MODEL_TABLES.contains(&table.as_str()) {
+            let rows = dump_table(conn, &table)?;
+            tables.insert(table, rows);
+        }
+    }
+
+    Ok(DatabaseArchive {
+        format: ARCHIVE_FORMAT.to_string(),
+        schema_version: ARCHIVE_SCHEMA_VERSION,
+        exported_at: Local::now().format("%Y-%m-%dT%H:%M:%S").to_string(),
+        inventory: get_all_inventory(conn)?,
+        recipes: get_recipe_collection(conn)?,
+        recipe_ingredients: get_all_recipe_ingredients(conn)?,
+        transactions: read_transactions(conn)?,
+        tables,
+    })
+}
+
+// Write the database as pretty-printed JSON
+pub fn export_json(conn: &Connection, path: &Path) -> Result<(), ArchiveError> {
+    let archive = build_archive(conn)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&archive)?)?;
+
+    Ok(())
+}
+
+// Read an archive and check it is one this version understands
+pub fn read_archive(path: &Path) -> Result<DatabaseArchive, ArchiveError> {
+    let text = fs::read_to_string(path)?;
+
+    // Check the header before the full parse so a wrong or newer file gets a clear message
+    let header: Value = serde_json::from_str(&text)?;
+    if header.get("format").and_then(Value::as_str) != Some(ARCHIVE_FORMAT) {
+        return Err(ArchiveError::Format(format!("{} is not a {} archive", path.display(), ARCHIVE_FORMAT)));
+    }
+    match header.get("schema_version").and_then(Value::as_u64) {
+        Some(version) if version >= 1 && version <= ARCHIVE_SCHEMA_VERSION as u64 => {}
+        Some(version) => {
+            return Err(ArchiveError::Format(format!(
+                "archive schema version {} is not supported (this build reads up to version {})",
+                version, ARCHIVE_SCHEMA_VERSION
+            )))
+        }
+        None => return Err(ArchiveError::Format("archive has no schema_version".to_string())),
+    }
+
+    Ok(serde_json::from_str(&text)?)
+}
+
+// Import an archive in one transaction
+pub fn import_json(conn: &Connection, path: &Path, mode: ImportMode) -> Result<ArchiveSummary, ArchiveError> {
+    let archive = read_archive(path)?;
+
+    let db_tx = conn.unchecked_transaction()?;
+    let summary = match mode {
+        ImportMode::ReplaceAll => replace_all(&db_tx, &archive)?,
+        ImportMode::Merge => merge(&db_tx, &archive)?,
+    };
+    db_tx.commit()?;
+
+    Ok(summary)
+}
+
+fn replace_all(conn: &Connection, archive: &DatabaseArchive) -> Result<ArchiveSummary, ArchiveError> {
+    let existing = table_names(conn)?;
+    for table in &existing {
+        conn.execute(&format!("DELETE FROM \"{}\"", table), [])?;
+    }
+
+    for item in &archive.inventory {
+        conn.execute(
+            "INSERT INTO inventory (id, name, unit, quantity, cost_per_unit, category, role)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![item.id, item.name, item.unit, item.quantity, item.cost_per_unit, item.category, item.role],
+        )?;
+    }
+    for recipe in &archive.recipes {
+        conn.execute(
+            "INSERT INTO recipes (id, name, instructions, yield_quantity, category, prep_time, bake_time, total_time, msrp_per_unit,
+                                  target_ddt_c, friction_factor_c, pan_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                recipe.id, recipe.name, recipe.instructions, recipe.yield_quantity, recipe.category,
+                recipe.prep_time, recipe.bake_time, recipe.total_time, recipe.msrp_per_unit,
+                recipe.target_ddt_c, recipe.friction_factor_c, recipe.pan_id
+            ],
+        )?;
+    }
+    for line in &archive.recipe_ingredients {
+        conn.execute(
+            "INSERT INTO recipe_ingredients (recipe_id, ingredient_id, quantity_required) VALUES (?1, ?2, ?3)",
+            params![line.recipe_id, line.ingredient_id, line.quantity_required],
+        )?;
+    }
+    for tx in &archive.transactions {
+        conn.execute(
+            "INSERT INTO transactions (id, date, transaction_type, amount, description, account, cleared, locked,
+                                       customer_id, invoice_id, category, reconciliation_id, recurring_id, order_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![
+                tx.id, tx.date, tx.transaction_type, tx.amount, tx.description, tx.account, tx.cleared, tx.locked,
+                tx.customer_id, tx.invoice_id, tx.category, tx.reconciliation_id, tx.recurring_id, tx.order_id
+            ],
+        )?;
+    }
+
+    let mut summary = ArchiveSummary::default();
+    summary.sections.push(("inventory".to_string(), archive.inventory.len()));
+    summary.sections.push(("recipes".to_string(), archive.recipes.len()));
+    summary.sections.push(("recipe_ingredients".to_string(), archive.recipe_ingredients.len()));
+    summary.sections.push(("transactions".to_string(), archive.transactions.len()));
+
+    for (table, rows) in &archive.tables {
+        if existing.contains(table) {
+            summary.sections.push((table.clone(), load_table(conn, table, rows)?));
+        } else {
+            summary.notes.push(format!("Skipped table '{}': it does not exist in this database", table));
+        }
+    }
+    // Archives from before structured steps: rebuild steps from the instructions text
+    if !archive.tables.contains_key("recipe_steps") {
+        for recipe in &archive.recipes {
+            insert_recipe_steps(conn, recipe.id, &recipe_steps_from_instructions(recipe))?;
+        }
+    }
+
+    Ok(summary)
+}
+
+// Merge by natural key: inventory and recipes by name, ingredient lines by recipe + ingredient,
+// transactions by date, type, amount, description and account. Other tables follow with their
+// ids remapped to the merged rows (see merge_table).
+fn merge(conn: &Connection, archive: &DatabaseArchive) -> Result<ArchiveSummary, ArchiveError> {
+    let mut summary = ArchiveSummary::default();
+
+    // Archive ids -> local ids
+    let mut inventory_ids = HashMap::new();
+    for item in &archive.inventory {
+        upsert_inventory_item(conn, item)?;
+        if let Some(local_id) = find_inventory_id_by_name(conn, &item.name)? {
+            inventory_ids.insert(item.id, local_id);
+        }
+    }
+    summary.sections.push(("inventory".to_string(), archive.inventory.len()));
+
+    // The archive's pan ids mean nothing here; pans are matched by name further down
+    let mut links = Vec::new();
+    let mut recipe_ids = HashMap::new();
+    for recipe in &archive.recipes {
+        upsert_recipe(conn, &RecipeCollection { pan_id: None, ..recipe.clone() })?;
+        if let Some(local_id) = find_recipe_id_by_name(conn, &recipe.name)? {
+            recipe_ids.insert(recipe.id, local_id);
+            if let Some(pan_id) = recipe.pan_id {
+                links.push(Link { table: "recipes", local_id: local_id as i64, column: "pan_id", parent: "pans".to_string(), archive_id: pan_id as i64 });
+            }
+        }
+    }
+    summary.sections.push(("recipes".to_string(), archive.recipes.len()));
+
+    let mut merged_lines = 0;
+    for line in &archive.recipe_ingredients {
+        match (recipe_ids.get(&line.recipe_id), inventory_ids.get(&line.ingredient_id)) {
+            (Some(recipe_id), Some(ingredient_id)) => {
+                upsert_recipe_ingredient(conn, *recipe_id, *ingredient_id, line.quantity_required)?;
+                merged_lines += 1;
+            }
+            _ => summary.notes.push(format!(
+                "Skipped ingredient line recipe {} / ingredient {}: not in the archive's recipes or inventory",
+                line.recipe_id, line.ingredient_id
+            )),
+        }
+    }
+    summary.sections.push(("recipe_ingredients".to_string(), merged_lines));
+
+    // Links to customers, invoices, orders, reconciliations and templates are restored once
+    // those tables are merged below
+    let mut ids: HashMap<String, HashMap<i64, i64>> = HashMap::new();
+    ids.insert("inventory".to_string(), inventory_ids.iter().map(|(k, v)| (*k as i64, *v as i64)).collect());
+    ids.insert("recipes".to_string(), recipe_ids.iter().map(|(k, v)| (*k as i64, *v as i64)).collect());
+    let mut transaction_ids = HashMap::new();
+    let mut added_transactions = 0;
+    for tx in &archive.transactions {
+        let existing: Option<i64> = match conn.query_row(
+            "SELECT id FROM transactions
+             WHERE date = ?1 AND transaction_type = ?2 AND ABS(amount - ?3) < 0.005
+               AND COALESCE(description, '') = ?4 AND account = ?5",
+            params![tx.date, tx.transaction_type, tx.amount, tx.description, tx.account],
+            |row| row.get(0),
+        ) {
+            Ok(id) => Some(id),
+            Err(rusqlite::Error::QueryReturnedNoRows) => None,
+            Err(e) => return Err(e.into()),
+        };
+        if let Some(id) = existing {
+            transaction_ids.insert(tx.id as i64, id);
+            continue;
+        }
+        conn.execute(
+            "INSERT INTO transactions (date, transaction_type, amount, description, account, cleared, locked, category)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![tx.date, tx.transaction_type, tx.amount, tx.description, tx.account, tx.cleared, tx.locked, tx.category],
+        )?;
+        let local_id = conn.last_insert_rowid();
+        transaction_ids.insert(tx.id as i64, local_id);
+        for (column, parent, archive_id) in [
+            ("customer_id", "customers", tx.customer_id),
+            ("invoice_id", "invoices", tx.invoice_id),
+            ("order_id", "orders", tx.order_id),
+            ("reconciliation_id", "reconciliations", tx.reconciliation_id),
+            ("recurring_id", "recurring_transactions", tx.recurring_id),
+        ] {
+            if let Some(archive_id) = archive_id {
+                links.push(Link { table: "transactions", local_id, column, parent: parent.to_string(), archive_id: archive_id as i64 });
+            }
+        }
+        added_transactions += 1;
+    }
+    ids.insert("transactions".to_string(), transaction_ids);
+    summary.sections.push(("transactions (new)".to_string(), added_transactions));
+
+    // Every other table, parents before children where the references allow it
+    let existing = table_names(conn)?;
+    let mut pending: Vec<&String> = archive.tables.keys().filter(|table| existing.contains(table)).collect();
+    pending.sort_by_key(|table| existing.iter().position(|name| name == *table));
+    for table in archive.tables.keys().filter(|table| !existing.contains(table)) {
+        summary.notes.push(format!("Skipped table '{}': it does not exist in this database", table));
+    }
+
+    let mut cleared_steps = HashSet::new();
+    while !pending.is_empty() {
+        let mut next = 0;
+        for (index, table) in pending.iter().enumerate() {
+            let refs = references(conn, table)?;
+            if refs.iter().all(|(_, parent)| parent == *table || !pending.contains(&parent)) {
+                next = index;
+                break;
+            }
+        }
+        let table = pending.remove(next);
+        let unresolved: Vec<String> = pending.iter().map(|t| t.to_string()).collect();
+        let (added, skipped) = merge_table(conn, table, &archive.tables[table], &unresolved, &mut ids, &mut links, &mut cleared_steps)?;
+        summary.sections.push((format!("{} (new)", table), added));
+        if skipped > 0 {
+            summary.notes.push(format!(
+                "Skipped {} row(s) in '{}': they refer to rows missing from the archive or clash with a local row",
+                skipped, table
+            ));
+        }
+    }
+
+    // Fill in references to tables that were merged after the row that holds them
+    for link in &links {
+        if let Some(local_parent) = ids.get(&link.parent).and_then(|map| map.get(&link.archive_id)) {
+            conn.execute(
+                &format!("UPDATE \"{}\" SET \"{}\" = ?1 WHERE id = ?2", link.table, link.column),
+                params![local_parent, link.local_id],
+            )?;
+        }
+    }
+
+    Ok(summary)
+}
+
+// A reference left empty on a merged row until its parent table has been merged
+struct Link<'a> {
+    table: &'a str,
+    local_id: i64,
+    column: &'a str,
+    parent: String,
+    archive_id: i64,
+}
+
+// Merge one dumped table into the database, remapping archive ids to local ids.
+// Rows with an `id` key match an identical local row (ignoring the id) before being added,
+// or a local row with the same unique name; keyless rows are added unless their key exists.
+// Archive recipe steps replace the merged recipe's steps. Returns (rows added, rows skipped).
+fn merge_table<'a>(
+    conn: &Connection,
+    table: &'a str,
+    rows: &'a [Map<String, Value>],
+    unresolved: &[String],
+    ids: &mut HashMap<String, HashMap<i64, i64>>,
+    links: &mut Vec<Link<'a>>,
+    cleared_steps: &mut HashSet<i64>,
+) -> rusqlite::Result<(usize, usize)> {
+    let columns = column_names(conn, table)?;
+    let has_id = primary_key(conn, table)? == ["id"];
+    let refs = references(conn, table)?;
+    // Only a unique name identifies the same thing across databases; a clashing invoice number doesn't
+    let unique_name = has_id && unique_columns(conn, table)?.iter().any(|c| c == "name");
+
+    let mut added = 0;
+    let mut skipped = 0;
+    'rows: for row in rows {
+        let mut values: Vec<(&'a str, SqlValue)> = Vec::new();
+        let mut deferred = Vec::new();
+        for column in columns.iter().filter(|c| row.contains_key(*c) && !(has_id && *c == "id")) {
+            let (key, _) = row.get_key_value(column).expect("column is present");
+            let mut value = to_sql(&row[column]);
+            if let (Some((_, parent)), SqlValue::Integer(archive_id)) = (refs.iter().find(|(c, _)| c == column), &value) {
+                if unresolved.contains(parent) && has_id {
+                    deferred.push((key.as_str(), parent.clone(), *archive_id));
+                    value = SqlValue::Null;
+                } else {
+                    match ids.get(parent).and_then(|map| map.get(archive_id)) {
+                        Some(local_id) => value = SqlValue::Integer(*local_id),
+                        None => {
+                            skipped += 1;
+                            continue 'rows;
+                        }
+                    }
+                }
+            }
+            values.push((key.as_str(), value));
+        }
+        if values.is_empty() {
+            continue;
+        }
+
+        if table == "recipe_steps" {
+            if let Some((_, SqlValue::Integer(recipe_id))) = values.iter().find(|(c, _)| *c == "recipe_id") {
+                if cleared_steps.insert(*recipe_id) {
+                    conn.execute("DELETE FROM recipe_steps WHERE recipe_id = ?1", [recipe_id])?;
+                }
+            }
+        }
+
+        let archive_id = row.get("id").and_then(Value::as_i64);
+        if has_id {
+            let compared: Vec<&(&str, SqlValue)> =
+                values.iter().filter(|(c, _)| !deferred.iter().any(|(d, _, _)| d == c)).collect();
+            let sql = format!(
+                "SELECT id FROM \"{}\" WHERE {}",
+                table,
+                compared.iter().map(|(c, _)| format!("\"{}\" IS ?", c)).collect::<Vec<_>>().join(" AND ")
+            );
+            let matched = if compared.is_empty() {
+                None
+            } else {
+                find_id(conn, &sql, compared.iter().map(|(_, v)| v.clone()).collect())?
+            };
+            if let (Some(local_id), Some(archive_id)) = (matched, archive_id) {
+                ids.entry(table.to_string()).or_default().insert(archive_id, local_id);
+                continue;
+            }
+        }
+
+        let sql = format!(
+            "INSERT OR IGNORE INTO \"{}\" ({}) VALUES ({})",
+            table,
+            values.iter().map(|(c, _)| format!("\"{}\"", c)).collect::<Vec<_>>().join(", "),
+            vec!["?"; values.len()].join(", ")
+        );
+        if conn.execute(&sql, params_from_iter(values.iter().map(|(_, v)| v.clone())))? == 0 {
+            // A unique name already in use is the same thing under local edits, e.g. a standard pan
+            let local_id = match values.iter().find(|(c, _)| *c == "name") {
+                Some((_, name)) if unique_name => {
+                    find_id(conn, &format!("SELECT id FROM \"{}\" WHERE name IS ?", table), vec![name.clone()])?
+                }
+                _ => None,
+            };
+            match (local_id, archive_id) {
+                (Some(local_id), Some(archive_id)) => {
+                    ids.entry(table.to_string()).or_default().insert(archive_id, local_id);
+                }
+                _ => skipped += 1,
+            }
+            continue;
+        }
+
+        added += 1;
+        if has_id {
+            let local_id = conn.last_insert_rowid();
+            if let Some(archive_id) = archive_id {
+                ids.entry(table.to_string()).or_default().insert(archive_id, local_id);
+            }
+            for (column, parent, archive_id) in deferred {
+                links.push(Link { table, local_id, column, parent, archive_id });
+            }
+        }
+    }
+
+    Ok((added, skipped))
+}
+
+fn find_id(conn: &Connection, sql: &str, values: Vec<SqlValue>) -> rusqlite::Result<Option<i64>> {
+    match conn.query_row(sql, params_from_iter(values), |row| row.get(0)) {
+        Ok(id) => Ok(Some(id)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{add_customer, add_invoice, add_order, add_order_deposit, add_production_plan, complete_reconciliation,
+        fulfil_order, init_db, record_invoice_payment, seed_inventory, seed_pans, seed_recipe_ingredients, seed_recipes,
+        seed_resources, start_reconciliation};
+    use crate::models::{Customer, Invoice, Order};
+
+    fn customer(name: &str) -> Customer {
+        Customer {
+            id: 0,
+            name: name.to_string(),
+            email: None,
+            phone: None,
+            address: None,
+            customer_type: "wholesale".to_string(),
+            price_tier_id: None,
+            tax_exempt: false,
+            notes: None,
+        }
+    }
+
+    fn count(conn: &Connection, sql: &str) -> i64 {
+        conn.query_row(sql, [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn merge_remaps_ids_in_every_table_and_is_repeatable() {
+        let source = Connection::open_in_memory().unwrap();
+        init_db(&source).unwrap();
+        seed_inventory(&source).unwrap();
+        seed_recipes(&source).unwrap();
+        seed_recipe_ingredients(&source).unwrap();
+        seed_pans(&source).unwrap();
+        seed_resources(&source).unwrap();
+
+        let recipe_id = find_recipe_id_by_name(&source, "Babka").unwrap().unwrap();
+        let customer_id = add_customer(&source, &customer("Corner Cafe")).unwrap();
+        let order = Order {
+            id: 0,
+            customer_name: "Corner Cafe".to_string(),
+            customer_contact: None,
+            due_date: "2026-03-02".to_string(),
+            due_time: None,
+            fulfilment: "pickup".to_string(),
+            deposit_paid: 0.0,
+            status: "confirmed".to_string(),
+            notes: None,
+            created_date: "2026-03-01".to_string(),
+            sale_transaction_id: None,
+            customer_id: Some(customer_id),
+        };
+        let order_id = add_order(&source, &order, &[(recipe_id, 10, 3.0)]).unwrap();
+        add_order_deposit(&source, order_id, 10.0, "2026-03-01", "Operating", Some("Sales")).unwrap();
+        fulfil_order(&source, order_id, "2026-03-02", "Operating", Some("Sales")).unwrap().unwrap();
+        let invoice = Invoice {
+            id: 0,
+            number: 1,
+            customer_id,
+            order_id: Some(order_id),
+            sale_transaction_id: None,
+            issue_date: "2026-03-02".to_string(),
+            due_date: "2026-04-01".to_string(),
+            terms: "Net 30".to_string(),
+            tax_rate: 0.0,
+            deposit_applied: 10.0,
+            notes: None,
+            voided: false,
+        };
+        let invoice_id = add_invoice(&source, &invoice, &[(Some(recipe_id), "Babka".to_string(), 10, 3.0)]).unwrap();
+        record_invoice_payment(&source, invoice_id, "2026-03-10", 20.0, "Operating").unwrap();
+        let reconciliation_id = start_reconciliation(&source, "Operating", "2026-03-01", "2026-03-31", 0.0, 20.0).unwrap();
+        source.execute("UPDATE transactions SET cleared = 1 WHERE transaction_type = 'receipt'", []).unwrap();
+        complete_reconciliation(&source, reconciliation_id).unwrap();
+        add_production_plan(&source, "2026-03-02", None, &[(recipe_id, 2.0)]).unwrap();
+        source.execute("UPDATE recipes SET pan_id = (SELECT MAX(id) FROM pans) WHERE id = ?1", [recipe_id]).unwrap();
+
+        // The target already has rows, so archive ids don't line up with local ones
+        let target = Connection::open_in_memory().unwrap();
+        init_db(&target).unwrap();
+        target.execute("INSERT INTO pans (name, shape, volume_cups) VALUES ('Local Pan', 'bundt', 10)", []).unwrap();
+        seed_pans(&target).unwrap();
+        seed_resources(&target).unwrap();
+        add_customer(&target, &customer("Someone Else")).unwrap();
+        target.execute("INSERT INTO inventory (name, unit, quantity, cost_per_unit) VALUES ('Local Salt', 'g', 1, 1)", []).unwrap();
+
+        let path = std::env::temp_dir().join(format!("bakery_merge_test_{}.json", std::process::id()));
+        export_json(&source, &path).unwrap();
+        import_json(&target, &path, ImportMode::Merge).unwrap();
+
+        let local_recipe = find_recipe_id_by_name(&target, "Babka").unwrap().unwrap();
+        let (local_customer, local_order): (i64, i64) = target
+            .query_row("SELECT customer_id, id FROM orders", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!(count(&target, "SELECT id FROM customers WHERE name = 'Corner Cafe'"), local_customer);
+        assert_eq!(count(&target, "SELECT recipe_id FROM order_items"), local_recipe as i64);
+        assert_eq!(count(&target, "SELECT customer_id FROM invoices"), local_customer);
+        assert_eq!(count(&target, "SELECT order_id FROM invoices"), local_order);
+        assert_eq!(count(&target, "SELECT COUNT(*) FROM transactions t JOIN invoices i ON t.invoice_id = i.id WHERE t.transaction_type = 'receipt'"), 1);
+        assert_eq!(count(&target, &format!("SELECT COUNT(*) FROM transactions WHERE order_id = {}", local_order)), 2);
+        assert_eq!(
+            count(&target, "SELECT COUNT(*) FROM transactions t JOIN reconciliations r ON t.reconciliation_id = r.id
+                            WHERE t.transaction_type = 'receipt' AND t.cleared = 1 AND t.locked = 1"),
+            1
+        );
+        assert_eq!(
+            count(&target, "SELECT sale_transaction_id FROM orders"),
+            count(&target, "SELECT id FROM transactions WHERE description LIKE 'Order #%'")
+        );
+        assert_eq!(count(&target, "SELECT recipe_id FROM production_plan_items"), local_recipe as i64);
+        assert_eq!(count(&target, "SELECT COUNT(*) FROM pans"), count(&source, "SELECT COUNT(*) FROM pans") + 1);
+        let pan_name = |conn: &Connection, recipe: i32| -> String {
+            conn.query_row("SELECT p.name FROM recipes r JOIN pans p ON r.pan_id = p.id WHERE r.id = ?1", [recipe], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(pan_name(&target, local_recipe), pan_name(&source, recipe_id));
+
+        // A second merge of the same archive finds everything already there
+        let tables = table_names(&target).unwrap();
+        let before: Vec<i64> = tables.iter().map(|t| count(&target, &format!("SELECT COUNT(*) FROM \"{}\"", t))).collect();
+        import_json(&target, &path, ImportMode::Merge).unwrap();
+        let after: Vec<i64> = tables.iter().map(|t| count(&target, &format!("SELECT COUNT(*) FROM \"{}\"", t))).collect();
+        fs::remove_file(&path).ok();
+        assert_eq!(before, after);
+    }
+}
@@ -0,0 +1,1169 @@
+// src/bakers_math.rs
+// Baker's percentages: every ingredient expressed as a percentage of total flour weight.
+// Pure calculations only; callers load ingredients and print the result.
+
+// What an ingredient does in a dough, stored as a tag on inventory items
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IngredientRole {
+    Flour,
+    Liquid,
+    Salt,
+    Leavener,
+    Fat,
+    Sugar,
+    Preferment,
+}
+
+impl IngredientRole {
+    pub const ALL: [IngredientRole; 7] = [
+        IngredientRole::Flour,
+        IngredientRole::Liquid,
+        IngredientRole::Salt,
+        IngredientRole::Leavener,
+        IngredientRole::Fat,
+        IngredientRole::Sugar,
+        IngredientRole::Preferment,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IngredientRole::Flour => "flour",
+            IngredientRole::Liquid => "liquid",
+            IngredientRole::Salt => "salt",
+            IngredientRole::Leavener => "leavener",
+            IngredientRole::Fat => "fat",
+            IngredientRole::Sugar => "sugar",
+            IngredientRole::Preferment => "preferment",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<IngredientRole> {
+        let text = text.trim().to_lowercase();
+        IngredientRole::ALL.into_iter().find(|role| role.as_str() == text)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefermentKind {
+    Levain,
+    Poolish,
+    Biga,
+    Sponge,
+}
+
+impl PrefermentKind {
+    pub const ALL: [PrefermentKind; 4] = [
+        PrefermentKind::Levain,
+        PrefermentKind::Poolish,
+        PrefermentKind::Biga,
+        PrefermentKind::Sponge,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PrefermentKind::Levain => "levain",
+            PrefermentKind::Poolish => "poolish",
+            PrefermentKind::Biga => "biga",
+            PrefermentKind::Sponge => "sponge",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<PrefermentKind> {
+        let text = text.trim().to_lowercase();
+        PrefermentKind::ALL.into_iter().find(|kind| kind.as_str() == text)
+    }
+
+    // Levains are seeded with ripe starter; the others with commercial yeast
+    pub fn seeded_with_starter(&self) -> bool {
+        matches!(self, PrefermentKind::Levain)
+    }
+}
+
+// How a preferment is built: water as % of its flour, and seed as % of its fresh flour
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Preferment {
+    pub kind: PrefermentKind,
+    pub hydration: f32,
+    pub inoculation: f32,
+}
+
+impl Preferment {
+    // Assumed when a preferment item has no details recorded
+    pub const DEFAULT: Preferment = Preferment { kind: PrefermentKind::Levain, hydration: 100.0, inoculation: 20.0 };
+    // Upper bounds for recorded details; stiff bigas sit near 50% and liquid levains rarely pass 200%
+    pub const MAX_HYDRATION: f32 = 200.0;
+    pub const MAX_INOCULATION: f32 = 100.0;
+
+    // Split a weight of preferment into (flour, water, yeast) grams.
+    // A starter seed is taken to be at the same hydration as the levain it builds.
+    pub fn composition(&self, grams: f32) -> (f32, f32, f32) {
+        let hydration = self.hydration / 100.0;
+        if self.kind.seeded_with_starter() {
+            let flour = grams / (1.0 + hydration);
+            (flour, grams - flour, 0.0)
+        } else {
+            let inoculation = self.inoculation / 100.0;
+            let flour = grams / (1.0 + hydration + inoculation);
+            (flour, flour * hydration, flour * inoculation)
+        }
+    }
+
+    // Seed (starter or yeast) needed to build this weight of preferment
+    pub fn seed_grams(&self, grams: f32) -> f32 {
+        let hydration = self.hydration / 100.0;
+        let inoculation = self.inoculation / 100.0;
+        grams * inoculation / (1.0 + hydration + inoculation)
+    }
+}
+
+// One recipe line as input to the calculation
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormulaIngredient {
+    pub name: String,
+    pub quantity: f32,
+    pub unit: String,
+    pub role: Option<IngredientRole>,
+    // Build details for items tagged as preferments
+    pub preferment: Option<Preferment>,
+}
+
+// Convert a weight to grams; None for volume, count or unknown units
+pub fn to_grams(quantity: f32, unit: &str) -> Option<f32> {
+    match unit.trim().to_lowercase().as_str() {
+        "g" | "gram" | "grams" => Some(quantity),
+        "kg" | "kilogram" | "kilograms" => Some(quantity * 1000.0),
+        "oz" | "ounce" | "ounces" => Some(quantity * 28.349_523),
+        "lb" | "lbs" | "pound" | "pounds" => Some(quantity * 453.592_37),
+        _ => None,
+    }
+}
+
+// Convert grams back into a weight unit; None for volume, count or unknown units
+pub fn from_grams(grams: f32, unit: &str) -> Option<f32> {
+    to_grams(1.0, unit).map(|per_unit| grams / per_unit)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IngredientPercentage {
+    pub name: String,
+    pub role: Option<IngredientRole>,
+    pub grams: f32,
+    pub percent: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaveningType {
+    // Preferment (levain, starter) only
+    Preferment,
+    // Added leavener (commercial yeast or chemical) only
+    Leavener,
+    Hybrid,
+    None,
+}
+
+impl LeaveningType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LeaveningType::Preferment => "Sourdough / preferment",
+            LeaveningType::Leavener => "Commercial yeast / added leavener",
+            LeaveningType::Hybrid => "Hybrid (added leavener + preferment)",
+            LeaveningType::None => "Unleavened or unknown (no leavener or preferment tagged)",
+        }
+    }
+}
+
+// What a preferment in a recipe contributes to the overall formula
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrefermentBreakdown {
+    pub name: String,
+    pub preferment: Preferment,
+    // True when no details were recorded and Preferment::DEFAULT was used
+    pub assumed: bool,
+    pub grams: f32,
+    pub flour_grams: f32,
+    pub water_grams: f32,
+    pub yeast_grams: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BakersPercentages {
+    // Added flour plus the flour inside preferments
+    pub total_flour_grams: f32,
+    // Flour components (including preferment flour) as a share of total flour; these sum to 100%
+    pub flour: Vec<IngredientPercentage>,
+    // Every other weighed ingredient as a percentage of total flour
+    pub others: Vec<IngredientPercentage>,
+    // Ingredients measured by volume or count, left out of the percentages
+    pub unweighed: Vec<FormulaIngredient>,
+    // Weighed ingredients with no role tag (counted, but not in any summary figure)
+    pub untagged: Vec<String>,
+    pub hydration: f32,
+    pub salt: f32,
+    pub sugar: f32,
+    pub fat: f32,
+    pub leavener: f32,
+    // Preferment weight as a percentage of total flour
+    pub preferment: f32,
+    // Share of total flour that was prefermented
+    pub prefermented_flour: f32,
+    pub preferments: Vec<PrefermentBreakdown>,
+    pub leavening: LeaveningType,
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BakersMathError {
+    // No weighed ingredient is tagged as flour, so there is nothing to divide by
+    NoFlour,
+    // Flour percentages in a formula must add up to 100
+    FlourNotOneHundred(f32),
+    // Target dough weight must be positive
+    InvalidTarget,
+    // Loss allowance must be at least 0% and below 100%
+    InvalidLoss(f32),
+    // The leavening line to substitute isn't measured by weight
+    NotWeighed(String),
+    // A starter swap needs liquid lines to take water from or give it back to
+    NoLiquid,
+    // The starter would bring in more flour or water than the recipe has
+    StarterTooLarge,
+    // A poolish, biga or sponge is built with commercial yeast, so it can't be swapped as a starter
+    YeastedPreferment(String, PrefermentKind),
+}
+
+impl std::fmt::Display for BakersMathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BakersMathError::NoFlour => write!(f, "no weighed ingredient is tagged as flour"),
+            BakersMathError::FlourNotOneHundred(total) => {
+                write!(f, "flour percentages add up to {:.1}%, not 100%", total)
+            }
+            BakersMathError::InvalidTarget => write!(f, "target dough weight must be greater than zero"),
+            BakersMathError::InvalidLoss(loss) => {
+                write!(f, "loss allowance of {:.1}% is out of range (0 to under 100%)", loss)
+            }
+            BakersMathError::NotWeighed(name) => write!(f, "{} is not measured by weight", name),
+            BakersMathError::NoLiquid => write!(f, "no weighed ingredient is tagged as liquid"),
+            BakersMathError::StarterTooLarge => {
+                write!(f, "the starter would need more flour or water than the recipe contains")
+            }
+            BakersMathError::YeastedPreferment(name, kind) => write!(
+                f,
+                "{} is a {} built with commercial yeast, not a starter; adjust the yeast in it instead",
+                name,
+                kind.as_str()
+            ),
+        }
+    }
+}
+
+// Hydration commentary, by range
+fn hydration_note(hydration: f32) -> &'static str {
+    if hydration >= 80.0 {
+        "Very high hydration - may be challenging to handle, consider stretch & folds"
+    } else if hydration >= 70.0 {
+        "High hydration - suitable for ciabatta, focaccia, or rustic breads"
+    } else if hydration >= 65.0 {
+        "Standard hydration level for most bread types"
+    } else if hydration >= 60.0 {
+        "Lower hydration - produces denser bread, easier to handle"
+    } else {
+        "Low hydration - typical for bagels, pretzels, or sandwich bread"
+    }
+}
+
+// Salt commentary against the usual 1.8-2.2% range
+fn salt_note(salt: f32) -> &'static str {
+    if salt < 1.8 {
+        "Salt is below standard range (1.8-2.2%)"
+    } else if salt > 2.2 {
+        "Salt is above standard range (1.8-2.2%)"
+    } else {
+        "Salt is within standard range (1.8-2.2%)"
+    }
+}
+
+// Overall formula: preferment flour counts as flour and preferment water as liquid,
+// so hydration and flour percentages describe the whole dough
+pub fn calculate(ingredients: &[FormulaIngredient]) -> Result<BakersPercentages, BakersMathError> {
+    let mut weighed = Vec::new();
+    let mut unweighed = Vec::new();
+    for ingredient in ingredients {
+        match to_grams(ingredient.quantity, &ingredient.unit) {
+            Some(grams) => weighed.push((ingredient, grams)),
+            None => unweighed.push(ingredient.clone()),
+        }
+    }
+
+    let preferments: Vec<PrefermentBreakdown> = weighed
+        .iter()
+        .filter(|(i, _)| i.role == Some(IngredientRole::Preferment))
+        .map(|(i, grams)| {
+            let preferment = i.preferment.unwrap_or(Preferment::DEFAULT);
+            let (flour_grams, water_grams, yeast_grams) = preferment.composition(*grams);
+            PrefermentBreakdown {
+                name: i.name.clone(),
+                preferment,
+                assumed: i.preferment.is_none(),
+                grams: *grams,
+                flour_grams,
+                water_grams,
+                yeast_grams,
+            }
+        })
+        .collect();
+
+    let added_flour_grams = weighed
+        .iter()
+        .filter(|(i, _)| i.role == Some(IngredientRole::Flour))
+        .fold(0.0, |sum, (_, grams)| sum + grams);
+    if added_flour_grams <= 0.0 {
+        return Err(BakersMathError::NoFlour);
+    }
+    let prefermented_flour_grams = preferments.iter().fold(0.0, |sum, p| sum + p.flour_grams);
+    let total_flour_grams = added_flour_grams + prefermented_flour_grams;
+    let percent_of_flour = |grams: f32| grams / total_flour_grams * 100.0;
+
+    let mut result = BakersPercentages {
+        total_flour_grams,
+        flour: Vec::new(),
+        others: Vec::new(),
+        unweighed,
+        untagged: Vec::new(),
+        hydration: 0.0,
+        salt: 0.0,
+        sugar: 0.0,
+        fat: 0.0,
+        leavener: 0.0,
+        preferment: 0.0,
+        prefermented_flour: percent_of_flour(prefermented_flour_grams),
+        preferments: Vec::new(),
+        leavening: LeaveningType::None,
+        notes: Vec::new(),
+    };
+
+    for (ingredient, grams) in weighed {
+        let line = IngredientPercentage {
+            name: ingredient.name.clone(),
+            role: ingredient.role,
+            grams,
+            percent: percent_of_flour(grams),
+        };
+        match ingredient.role {
+            Some(IngredientRole::Flour) => {
+                result.flour.push(line);
+                continue;
+            }
+            Some(IngredientRole::Liquid) => result.hydration += line.percent,
+            Some(IngredientRole::Salt) => result.salt += line.percent,
+            Some(IngredientRole::Sugar) => result.sugar += line.percent,
+            Some(IngredientRole::Fat) => result.fat += line.percent,
+            Some(IngredientRole::Leavener) => result.leavener += line.percent,
+            Some(IngredientRole::Preferment) => result.preferment += line.percent,
+            None => result.untagged.push(ingredient.name.clone()),
+        }
+        result.others.push(line);
+    }
+
+    // Leavening type reflects what is added to the final dough, so yeast inside a poolish doesn't make it a hybrid
+    result.leavening = match (result.leavener > 0.0, result.preferment > 0.0) {
+        (true, true) => LeaveningType::Hybrid,
+        (false, true) => LeaveningType::Preferment,
+        (true, false) => LeaveningType::Leavener,
+        (false, false) => LeaveningType::None,
+    };
+
+    for breakdown in &preferments {
+        result.flour.push(IngredientPercentage {
+            name: format!("{} (prefermented flour)", breakdown.name),
+            role: Some(IngredientRole::Flour),
+            grams: breakdown.flour_grams,
+            percent: percent_of_flour(breakdown.flour_grams),
+        });
+        result.hydration += percent_of_flour(breakdown.water_grams);
+        result.leavener += percent_of_flour(breakdown.yeast_grams);
+    }
+
+    if result.hydration > 0.0 {
+        result.notes.push(hydration_note(result.hydration).to_string());
+    }
+    if result.salt > 0.0 {
+        result.notes.push(salt_note(result.salt).to_string());
+    }
+    if result.prefermented_flour > 0.0 {
+        result.notes.push(format!(
+            "{:.1}% of the flour is prefermented; hydration includes preferment water",
+            result.prefermented_flour
+        ));
+    }
+    for breakdown in preferments.iter().filter(|p| p.assumed) {
+        result.notes.push(format!(
+            "No preferment details for {}; assumed a {:.0}% hydration {}",
+            breakdown.name,
+            breakdown.preferment.hydration,
+            breakdown.preferment.kind.as_str()
+        ));
+    }
+    if !result.untagged.is_empty() {
+        result.notes.push(format!(
+            "No role tag on: {} (tag them in Inventory to include them in the summary)",
+            result.untagged.join(", ")
+        ));
+    }
+    result.preferments = preferments;
+
+    Ok(result)
+}
+
+// One line of a formula written as baker's percentages
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormulaPercentage {
+    pub name: String,
+    pub role: Option<IngredientRole>,
+    pub percent: f32,
+}
+
+// How much dough a formula should make
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DoughTarget {
+    TotalWeight(f32),
+    Pieces { count: u32, grams_each: f32 },
+}
+
+impl DoughTarget {
+    pub fn grams(&self) -> f32 {
+        match self {
+            DoughTarget::TotalWeight(grams) => *grams,
+            DoughTarget::Pieces { count, grams_each } => *count as f32 * grams_each,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaledIngredient {
+    pub name: String,
+    pub role: Option<IngredientRole>,
+    pub percent: f32,
+    pub grams: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScaledFormula {
+    // Dough wanted after mixing losses
+    pub target_grams: f32,
+    pub loss_percent: f32,
+    // Sum of all baker's percentages (flour = 100)
+    pub total_percent: f32,
+    pub total_flour_grams: f32,
+    // Sum of the rounded ingredient weights, in the same order as the input
+    pub total_dough_grams: f32,
+    pub lines: Vec<ScaledIngredient>,
+}
+
+// Round to the nearest multiple of `step`; a step of zero or less leaves the value alone
+fn round_to(value: f32, step: f32) -> f32 {
+    if step > 0.0 { (value / step).round() * step } else { value }
+}
+
+// Turn a percentage formula into gram weights that yield `target` after `loss_percent`
+// of the mixed dough is lost, rounding each weight to the nearest `rounding` grams
+pub fn scale_formula(
+    formula: &[FormulaPercentage],
+    target: DoughTarget,
+    loss_percent: f32,
+    rounding: f32,
+) -> Result<ScaledFormula, BakersMathError> {
+    let flour_percent = formula
+        .iter()
+        .filter(|line| line.role == Some(IngredientRole::Flour))
+        .fold(0.0, |sum, line| sum + line.percent);
+    if flour_percent <= 0.0 {
+        return Err(BakersMathError::NoFlour);
+    }
+    if (flour_percent - 100.0).abs() > 0.05 {
+        return Err(BakersMathError::FlourNotOneHundred(flour_percent));
+    }
+    let target_grams = target.grams();
+    if target_grams <= 0.0 {
+        return Err(BakersMathError::InvalidTarget);
+    }
+    if !(0.0..100.0).contains(&loss_percent) {
+        return Err(BakersMathError::InvalidLoss(loss_percent));
+    }
+
+    let total_percent = formula.iter().fold(0.0, |sum, line| sum + line.percent);
+    let mixed_grams = target_grams / (1.0 - loss_percent / 100.0);
+    let total_flour_grams = mixed_grams / total_percent * 100.0;
+
+    let lines: Vec<ScaledIngredient> = formula
+        .iter()
+        .map(|line| ScaledIngredient {
+            name: line.name.clone(),
+            role: line.role,
+            percent: line.percent,
+            grams: round_to(total_flour_grams * line.percent / 100.0, rounding),
+        })
+        .collect();
+    let total_dough_grams = lines.iter().fold(0.0, |sum, line| sum + line.grams);
+
+    Ok(ScaledFormula {
+        target_grams,
+        loss_percent,
+        total_percent,
+        total_flour_grams,
+        total_dough_grams,
+        lines,
+    })
+}
+
+// Leavening forms a recipe's yeast line can be converted between
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YeastType {
+    Instant,
+    ActiveDry,
+    Fresh,
+    Starter,
+}
+
+impl YeastType {
+    pub const ALL: [YeastType; 4] = [YeastType::Instant, YeastType::ActiveDry, YeastType::Fresh, YeastType::Starter];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            YeastType::Instant => "instant",
+            YeastType::ActiveDry => "active dry",
+            YeastType::Fresh => "fresh",
+            YeastType::Starter => "starter",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<YeastType> {
+        match text.trim().to_lowercase().replace(['-', '_'], " ").as_str() {
+            "instant" | "instant dry" => Some(YeastType::Instant),
+            "active dry" | "active" | "ad" => Some(YeastType::ActiveDry),
+            "fresh" | "cake" | "compressed" => Some(YeastType::Fresh),
+            "starter" | "sourdough" | "levain" => Some(YeastType::Starter),
+            _ => None,
+        }
+    }
+
+    // Default inventory name when a recipe variant needs a new item
+    pub fn item_name(&self) -> &'static str {
+        match self {
+            YeastType::Instant => "Yeast, Instant",
+            YeastType::ActiveDry => "Yeast, Active Dry",
+            YeastType::Fresh => "Yeast, Fresh",
+            YeastType::Starter => "Sourdough Starter",
+        }
+    }
+
+    // Grams of this yeast equivalent to 1 g of instant yeast; None for starter
+    pub fn instant_ratio(&self) -> Option<f32> {
+        match self {
+            YeastType::Instant => Some(1.0),
+            YeastType::ActiveDry => Some(1.25),
+            YeastType::Fresh => Some(3.0),
+            YeastType::Starter => None,
+        }
+    }
+}
+
+// Settings used when swapping to or from a starter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StarterSwap {
+    // Starter hydration (water per 100 flour)
+    pub hydration: f32,
+    // Starter to use, as % of total flour, when replacing yeast
+    pub starter_percent: f32,
+    // Instant yeast to use, as % of total flour, when replacing a starter
+    pub instant_percent: f32,
+}
+
+impl StarterSwap {
+    pub const DEFAULT: StarterSwap = StarterSwap { hydration: 100.0, starter_percent: 20.0, instant_percent: 1.0 };
+}
+
+// A recipe line before and after a substitution, in grams (None when not measured by weight)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubstitutedLine {
+    pub name: String,
+    pub role: Option<IngredientRole>,
+    pub before_grams: Option<f32>,
+    pub after_grams: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeaveningSubstitution {
+    pub from: YeastType,
+    pub to: YeastType,
+    // One entry per input ingredient, in order; the replaced line ends at 0 g
+    pub lines: Vec<SubstitutedLine>,
+    pub new_leavener_grams: f32,
+    pub notes: Vec<String>,
+}
+
+// Spread `delta` grams across the given lines in proportion to their weight
+fn spread(lines: &mut [SubstitutedLine], role: IngredientRole, delta: f32) -> Result<(), BakersMathError> {
+    let total = lines
+        .iter()
+        .filter(|l| l.role == Some(role))
+        .fold(0.0, |sum, l| sum + l.after_grams.unwrap_or(0.0));
+    if total <= 0.0 {
+        return Err(if role == IngredientRole::Flour { BakersMathError::NoFlour } else { BakersMathError::NoLiquid });
+    }
+    if total + delta < 0.0 {
+        return Err(BakersMathError::StarterTooLarge);
+    }
+    for line in lines.iter_mut().filter(|l| l.role == Some(role)) {
+        if let Some(grams) = line.after_grams.as_mut() {
+            *grams += delta * *grams / total;
+        }
+    }
+    Ok(())
+}
+
+// Replace the leavening at `index` with another yeast type. Yeast-to-yeast swaps use the
+// standard weight ratios; swapping to a starter takes its flour and water out of the
+// flour and liquid lines, and swapping away from one puts them back.
+pub fn substitute_leavening(
+    ingredients: &[FormulaIngredient],
+    index: usize,
+    from: YeastType,
+    to: YeastType,
+    swap: StarterSwap,
+) -> Result<LeaveningSubstitution, BakersMathError> {
+    let source = &ingredients[index];
+    if let Some(p) = source.preferment.filter(|p| !p.kind.seeded_with_starter()) {
+        return Err(BakersMathError::YeastedPreferment(source.name.clone(), p.kind));
+    }
+    let source_grams = to_grams(source.quantity, &source.unit)
+        .ok_or_else(|| BakersMathError::NotWeighed(source.name.clone()))?;
+
+    let mut lines: Vec<SubstitutedLine> = ingredients
+        .iter()
+        .map(|i| {
+            let grams = to_grams(i.quantity, &i.unit);
+            SubstitutedLine { name: i.name.clone(), role: i.role, before_grams: grams, after_grams: grams }
+        })
+        .collect();
+    lines[index].after_grams = Some(0.0);
+    let mut notes = Vec::new();
+
+    let new_leavener_grams = match (from.instant_ratio(), to.instant_ratio()) {
+        (Some(from_ratio), Some(to_ratio)) => source_grams / from_ratio * to_ratio,
+        (Some(_), None) => {
+            let flour: f32 = lines
+                .iter()
+                .filter(|l| l.role == Some(IngredientRole::Flour))
+                .fold(0.0, |sum, l| sum + l.after_grams.unwrap_or(0.0));
+            if flour <= 0.0 {
+                return Err(BakersMathError::NoFlour);
+            }
+            // Starter is sized against the same total flour, so part of the flour moves into it
+            let starter = flour * swap.starter_percent / 100.0;
+            let starter_flour = starter / (1.0 + swap.hydration / 100.0);
+            spread(&mut lines, IngredientRole::Flour, -starter_flour)?;
+            spread(&mut lines, IngredientRole::Liquid, -(starter - starter_flour))?;
+            notes.push(format!(
+                "Starter at {:.0}% hydration replaces {:.1} g flour and {:.1} g liquid; allow a longer, slower rise",
+                swap.hydration, starter_flour, starter - starter_flour
+            ));
+            starter
+        }
+        (None, Some(to_ratio)) => {
+            let starter_flour = source_grams / (1.0 + swap.hydration / 100.0);
+            spread(&mut lines, IngredientRole::Flour, starter_flour)?;
+            spread(&mut lines, IngredientRole::Liquid, source_grams - starter_flour)?;
+            let flour: f32 = lines
+                .iter()
+                .filter(|l| l.role == Some(IngredientRole::Flour))
+                .fold(0.0, |sum, l| sum + l.after_grams.unwrap_or(0.0));
+            notes.push(format!(
+                "Starter flour ({:.1} g) and water ({:.1} g) are added back to the flour and liquid lines",
+                starter_flour, source_grams - starter_flour
+            ));
+            flour * swap.instant_percent / 100.0 * to_ratio
+        }
+        (None, None) => source_grams,
+    };
+
+    if to == YeastType::ActiveDry && from != YeastType::ActiveDry {
+        notes.push("Bloom active dry yeast in some of the recipe's warm water before mixing".to_string());
+    }
+    if to == YeastType::Fresh && from != YeastType::Fresh {
+        notes.push("Crumble fresh yeast into the flour or dissolve it in the water".to_string());
+    }
+
+    Ok(LeaveningSubstitution { from, to, lines, new_leavener_grams, notes })
+}
+
+// Temperature scale for dough temperature calculations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TempUnit {
+    Fahrenheit,
+    Celsius,
+}
+
+impl TempUnit {
+    pub fn parse(text: &str) -> Option<TempUnit> {
+        match text.trim().to_lowercase().as_str() {
+            "f" | "fahrenheit" => Some(TempUnit::Fahrenheit),
+            "c" | "celsius" => Some(TempUnit::Celsius),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            TempUnit::Fahrenheit => "°F",
+            TempUnit::Celsius => "°C",
+        }
+    }
+
+    pub fn to_celsius(self, temp: f32) -> f32 {
+        match self {
+            TempUnit::Fahrenheit => (temp - 32.0) * 5.0 / 9.0,
+            TempUnit::Celsius => temp,
+        }
+    }
+
+    pub fn in_unit(self, celsius: f32) -> f32 {
+        match self {
+            TempUnit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+            TempUnit::Celsius => celsius,
+        }
+    }
+
+    // Temperature differences (like friction factor) scale without the 32° offset
+    pub fn delta_to_celsius(self, delta: f32) -> f32 {
+        match self {
+            TempUnit::Fahrenheit => delta * 5.0 / 9.0,
+            TempUnit::Celsius => delta,
+        }
+    }
+
+    pub fn delta_in_unit(self, delta: f32) -> f32 {
+        match self {
+            TempUnit::Fahrenheit => delta * 9.0 / 5.0,
+            TempUnit::Celsius => delta,
+        }
+    }
+
+    // Water this cold or colder needs ice
+    pub fn freezing(self) -> f32 {
+        self.in_unit(0.0)
+    }
+}
+
+// Inputs for the desired dough temperature (DDT) method, all in the same unit.
+// With a preferment temperature the 4-factor method is used, otherwise the 3-factor method.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DoughTemperature {
+    pub target: f32,
+    pub room: f32,
+    pub flour: f32,
+    pub preferment: Option<f32>,
+    pub friction: f32,
+}
+
+impl DoughTemperature {
+    pub fn factors(&self) -> u8 {
+        if self.preferment.is_some() { 4 } else { 3 }
+    }
+
+    // Water temperature that brings the mixed dough to the target
+    pub fn water_temperature(&self) -> f32 {
+        let known = self.room + self.flour + self.friction + self.preferment.unwrap_or(0.0);
+        self.target * self.factors() as f32 - known
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ingredient(name: &str, quantity: f32, unit: &str, role: Option<IngredientRole>) -> FormulaIngredient {
+        FormulaIngredient { name: name.to_string(), quantity, unit: unit.to_string(), role, preferment: None }
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.01
+    }
+
+    fn preferment(name: &str, quantity: f32, details: Option<Preferment>) -> FormulaIngredient {
+        FormulaIngredient { preferment: details, ..ingredient(name, quantity, "g", Some(IngredientRole::Preferment)) }
+    }
+
+    fn country_loaf() -> Vec<FormulaIngredient> {
+        vec![
+            ingredient("Flour, Bread", 0.8, "kg", Some(IngredientRole::Flour)),
+            ingredient("Flour, Whole Wheat", 200.0, "grams", Some(IngredientRole::Flour)),
+            ingredient("Water", 720.0, "g", Some(IngredientRole::Liquid)),
+            ingredient("Salt, Kosher", 20.0, "g", Some(IngredientRole::Salt)),
+        ]
+    }
+
+    #[test]
+    fn converts_weights_to_grams() {
+        assert_eq!(to_grams(1.5, "kg"), Some(1500.0));
+        assert_eq!(to_grams(250.0, "Grams"), Some(250.0));
+        assert!(close(to_grams(1.0, "lb").unwrap(), 453.59));
+        assert!(close(to_grams(2.0, "oz").unwrap(), 56.70));
+        assert_eq!(to_grams(3.0, "each"), None);
+        assert_eq!(to_grams(5.0, "ml"), None);
+    }
+
+    #[test]
+    fn parses_roles_case_insensitively() {
+        assert_eq!(IngredientRole::parse(" Flour "), Some(IngredientRole::Flour));
+        assert_eq!(IngredientRole::parse("preferment"), Some(IngredientRole::Preferment));
+        assert_eq!(IngredientRole::parse("starch"), None);
+        for role in IngredientRole::ALL {
+            assert_eq!(IngredientRole::parse(role.as_str()), Some(role));
+        }
+    }
+
+    #[test]
+    fn flour_components_total_one_hundred_percent() {
+        let result = calculate(&country_loaf()).unwrap();
+        assert!(close(result.total_flour_grams, 1000.0));
+        assert_eq!(result.flour.len(), 2);
+        assert!(close(result.flour[0].percent, 80.0));
+        assert!(close(result.flour[1].percent, 20.0));
+        assert!(close(result.flour.iter().map(|f| f.percent).sum::<f32>(), 100.0));
+    }
+
+    #[test]
+    fn summarises_roles_against_total_flour() {
+        let result = calculate(&country_loaf()).unwrap();
+        assert!(close(result.hydration, 72.0));
+        assert!(close(result.salt, 2.0));
+        assert_eq!(result.others.len(), 2);
+        assert_eq!(result.leavening, LeaveningType::None);
+        assert_eq!(result.prefermented_flour, 0.0);
+    }
+
+    #[test]
+    fn splits_preferments_by_their_own_hydration() {
+        let levain = Preferment { kind: PrefermentKind::Levain, hydration: 100.0, inoculation: 20.0 };
+        assert_eq!(levain.composition(200.0), (100.0, 100.0, 0.0));
+        // 100 g fresh flour + 100 g water + 20 g starter
+        assert!(close(levain.seed_grams(220.0), 20.0));
+
+        let stiff = Preferment { kind: PrefermentKind::Levain, hydration: 60.0, inoculation: 50.0 };
+        let (flour, water, yeast) = stiff.composition(160.0);
+        assert!(close(flour, 100.0) && close(water, 60.0) && yeast == 0.0);
+
+        // Poolish: 100 flour, 100 water, 0.1 yeast
+        let poolish = Preferment { kind: PrefermentKind::Poolish, hydration: 100.0, inoculation: 0.1 };
+        let (flour, water, yeast) = poolish.composition(200.1);
+        assert!(close(flour, 100.0) && close(water, 100.0) && close(yeast, 0.1));
+
+        let mut dough = country_loaf();
+        dough.push(preferment("Poolish", 200.1, Some(poolish)));
+        let result = calculate(&dough).unwrap();
+        assert_eq!(result.leavening, LeaveningType::Preferment);
+        assert!(result.leavener > 0.0);
+    }
+
+    #[test]
+    fn preferment_flour_and_water_count_in_the_overall_formula() {
+        let biga = Preferment { kind: PrefermentKind::Biga, hydration: 60.0, inoculation: 0.0 };
+        let mut dough = country_loaf();
+        dough[2].quantity = 580.0;
+        dough.push(preferment("Biga", 400.0, Some(biga)));
+        let result = calculate(&dough).unwrap();
+
+        // 1000 g added flour + 250 g biga flour; 580 g water + 150 g biga water
+        assert!(close(result.total_flour_grams, 1250.0));
+        assert!(close(result.prefermented_flour, 20.0));
+        assert!(close(result.hydration, 730.0 / 1250.0 * 100.0));
+        assert!(close(result.preferment, 32.0));
+        assert_eq!(result.flour.len(), 3);
+        assert!(close(result.flour.iter().map(|f| f.percent).sum::<f32>(), 100.0));
+        assert_eq!(result.leavening, LeaveningType::Preferment);
+        assert!(!result.preferments[0].assumed);
+    }
+
+    #[test]
+    fn assumes_a_default_levain_without_details() {
+        let mut dough = country_loaf();
+        dough.push(preferment("Sourdough Starter", 200.0, None));
+        let result = calculate(&dough).unwrap();
+        assert!(close(result.total_flour_grams, 1100.0));
+        assert!(close(result.hydration, 820.0 / 1100.0 * 100.0));
+        assert!(result.preferments[0].assumed);
+        assert!(result.notes.iter().any(|n| n.starts_with("No preferment details for Sourdough Starter")));
+    }
+
+    #[test]
+    fn parses_preferment_kinds() {
+        assert_eq!(PrefermentKind::parse("Poolish"), Some(PrefermentKind::Poolish));
+        assert_eq!(PrefermentKind::parse("pate fermentee"), None);
+        assert!(PrefermentKind::Levain.seeded_with_starter());
+        assert!(!PrefermentKind::Biga.seeded_with_starter());
+    }
+
+    #[test]
+    fn uses_role_tags_not_ingredient_names() {
+        // A liquid whose name doesn't say "water", and a "flour" that isn't tagged as flour
+        let result = calculate(&[
+            ingredient("Bread flour", 500.0, "g", Some(IngredientRole::Flour)),
+            ingredient("Whole milk", 300.0, "g", Some(IngredientRole::Liquid)),
+            ingredient("Rice flour (for dusting)", 20.0, "g", None),
+        ])
+        .unwrap();
+        assert!(close(result.total_flour_grams, 500.0));
+        assert!(close(result.hydration, 60.0));
+        assert_eq!(result.untagged, vec!["Rice flour (for dusting)".to_string()]);
+    }
+
+    #[test]
+    fn detects_leavening_type() {
+        let yeast = ingredient("Yeast, Instant", 7.0, "g", Some(IngredientRole::Leavener));
+        let starter = preferment("Levain", 100.0, Some(Preferment::DEFAULT));
+        let flour = ingredient("Flour", 500.0, "g", Some(IngredientRole::Flour));
+
+        let leavening = |items: Vec<FormulaIngredient>| calculate(&items).unwrap().leavening;
+        assert_eq!(leavening(vec![flour.clone(), yeast.clone()]), LeaveningType::Leavener);
+        assert_eq!(leavening(vec![flour.clone(), starter.clone()]), LeaveningType::Preferment);
+        assert_eq!(leavening(vec![flour.clone(), yeast, starter]), LeaveningType::Hybrid);
+        assert_eq!(leavening(vec![flour]), LeaveningType::None);
+    }
+
+    #[test]
+    fn leaves_unweighed_ingredients_out() {
+        let result = calculate(&[
+            ingredient("Flour", 1.0, "kg", Some(IngredientRole::Flour)),
+            ingredient("Eggs", 2.0, "each", Some(IngredientRole::Liquid)),
+            ingredient("Vanilla", 5.0, "ml", None),
+        ])
+        .unwrap();
+        assert_eq!(result.unweighed.len(), 2);
+        assert!(result.others.is_empty());
+        assert_eq!(result.hydration, 0.0);
+    }
+
+    #[test]
+    fn fails_without_flour() {
+        let result = calculate(&[ingredient("Water", 100.0, "g", Some(IngredientRole::Liquid))]);
+        assert_eq!(result, Err(BakersMathError::NoFlour));
+        let untagged_flour = calculate(&[ingredient("Flour", 100.0, "g", None)]);
+        assert_eq!(untagged_flour, Err(BakersMathError::NoFlour));
+    }
+
+    #[test]
+    fn analysis_notes_cover_hydration_and_salt() {
+        let result = calculate(&country_loaf()).unwrap();
+        assert!(result.notes.iter().any(|n| n.starts_with("High hydration")));
+        assert!(result.notes.iter().any(|n| n == "Salt is within standard range (1.8-2.2%)"));
+
+        assert_eq!(hydration_note(55.0), "Low hydration - typical for bagels, pretzels, or sandwich bread");
+        assert_eq!(hydration_note(67.0), "Standard hydration level for most bread types");
+        assert_eq!(hydration_note(85.0), "Very high hydration - may be challenging to handle, consider stretch & folds");
+        assert_eq!(salt_note(1.5), "Salt is below standard range (1.8-2.2%)");
+        assert_eq!(salt_note(2.5), "Salt is above standard range (1.8-2.2%)");
+    }
+
+    fn line(name: &str, role: Option<IngredientRole>, percent: f32) -> FormulaPercentage {
+        FormulaPercentage { name: name.to_string(), role, percent }
+    }
+
+    fn lean_dough() -> Vec<FormulaPercentage> {
+        vec![
+            line("Flour, Bread", Some(IngredientRole::Flour), 90.0),
+            line("Flour, Whole Wheat", Some(IngredientRole::Flour), 10.0),
+            line("Water", Some(IngredientRole::Liquid), 70.0),
+            line("Salt, Kosher", Some(IngredientRole::Salt), 2.0),
+            line("Yeast, Instant", Some(IngredientRole::Leavener), 1.0),
+        ]
+    }
+
+    #[test]
+    fn converts_grams_back_to_units() {
+        assert_eq!(from_grams(1500.0, "kg"), Some(1.5));
+        assert_eq!(from_grams(250.0, "grams"), Some(250.0));
+        assert!(close(from_grams(453.592_37, "lb").unwrap(), 1.0));
+        assert_eq!(from_grams(10.0, "each"), None);
+    }
+
+    #[test]
+    fn scales_formula_to_total_dough_weight() {
+        let scaled = scale_formula(&lean_dough(), DoughTarget::TotalWeight(1730.0), 0.0, 0.0).unwrap();
+        assert!(close(scaled.total_percent, 173.0));
+        assert!(close(scaled.total_flour_grams, 1000.0));
+        assert!(close(scaled.lines[0].grams, 900.0));
+        assert!(close(scaled.lines[2].grams, 700.0));
+        assert!(close(scaled.lines[4].grams, 10.0));
+        assert!(close(scaled.total_dough_grams, 1730.0));
+    }
+
+    #[test]
+    fn scales_formula_to_pieces() {
+        let target = DoughTarget::Pieces { count: 10, grams_each: 173.0 };
+        assert!(close(target.grams(), 1730.0));
+        let scaled = scale_formula(&lean_dough(), target, 0.0, 1.0).unwrap();
+        assert!(close(scaled.total_flour_grams, 1000.0));
+    }
+
+    #[test]
+    fn loss_allowance_increases_every_weight() {
+        let scaled = scale_formula(&lean_dough(), DoughTarget::TotalWeight(1730.0), 2.0, 0.0).unwrap();
+        let expected_flour = 1730.0 / 0.98 / 1.73;
+        assert!(close(scaled.total_flour_grams, expected_flour));
+        assert!(scaled.total_dough_grams > scaled.target_grams);
+    }
+
+    #[test]
+    fn rounds_each_weight_to_the_step() {
+        let scaled = scale_formula(&lean_dough(), DoughTarget::TotalWeight(1000.0), 0.0, 5.0).unwrap();
+        for scaled_line in &scaled.lines {
+            assert!(close(scaled_line.grams % 5.0, 0.0));
+        }
+        // 1000 / 1.73 = 578.03 g flour; 1% yeast = 5.78 g, nearer 5 than 10, rounds down to 5
+        assert!(close(scaled.lines[4].grams, 5.0));
+    }
+
+    #[test]
+    fn rejects_invalid_formulas() {
+        let target = DoughTarget::TotalWeight(1000.0);
+        let no_flour = vec![line("Water", Some(IngredientRole::Liquid), 70.0)];
+        assert_eq!(scale_formula(&no_flour, target, 0.0, 1.0), Err(BakersMathError::NoFlour));
+
+        let short = vec![line("Flour", Some(IngredientRole::Flour), 90.0)];
+        assert_eq!(scale_formula(&short, target, 0.0, 1.0), Err(BakersMathError::FlourNotOneHundred(90.0)));
+
+        let zero = DoughTarget::Pieces { count: 0, grams_each: 100.0 };
+        assert_eq!(scale_formula(&lean_dough(), zero, 0.0, 1.0), Err(BakersMathError::InvalidTarget));
+        assert_eq!(scale_formula(&lean_dough(), target, 100.0, 1.0), Err(BakersMathError::InvalidLoss(100.0)));
+    }
+
+    #[test]
+    fn three_factor_water_temperature() {
+        // 76°F target: 76 x 3 = 228 - (72 room + 70 flour + 26 friction) = 60
+        let ddt = DoughTemperature { target: 76.0, room: 72.0, flour: 70.0, preferment: None, friction: 26.0 };
+        assert_eq!(ddt.factors(), 3);
+        assert!(close(ddt.water_temperature(), 60.0));
+    }
+
+    #[test]
+    fn four_factor_water_temperature() {
+        // 24°C target: 24 x 4 = 96 - (22 room + 21 flour + 25 levain + 13 friction) = 15
+        let ddt = DoughTemperature { target: 24.0, room: 22.0, flour: 21.0, preferment: Some(25.0), friction: 13.0 };
+        assert_eq!(ddt.factors(), 4);
+        assert!(close(ddt.water_temperature(), 15.0));
+    }
+
+    #[test]
+    fn converts_between_temperature_units() {
+        assert_eq!(TempUnit::parse("F"), Some(TempUnit::Fahrenheit));
+        assert_eq!(TempUnit::parse("celsius"), Some(TempUnit::Celsius));
+        assert_eq!(TempUnit::parse("k"), None);
+        assert!(close(TempUnit::Fahrenheit.to_celsius(76.0), 24.444));
+        assert!(close(TempUnit::Fahrenheit.in_unit(24.0), 75.2));
+        assert!(close(TempUnit::Fahrenheit.delta_to_celsius(27.0), 15.0));
+        assert!(close(TempUnit::Fahrenheit.delta_in_unit(15.0), 27.0));
+        assert_eq!(TempUnit::Celsius.to_celsius(21.0), 21.0);
+        assert_eq!(TempUnit::Fahrenheit.freezing(), 32.0);
+    }
+
+    #[test]
+    fn same_answer_in_either_unit() {
+        let celsius = DoughTemperature { target: 25.0, room: 20.0, flour: 20.0, preferment: Some(26.0), friction: 12.0 };
+        let f = TempUnit::Fahrenheit;
+        let fahrenheit = DoughTemperature {
+            target: f.in_unit(celsius.target),
+            room: f.in_unit(celsius.room),
+            flour: f.in_unit(celsius.flour),
+            preferment: celsius.preferment.map(|t| f.in_unit(t)),
+            friction: f.delta_in_unit(celsius.friction),
+        };
+        assert!(close(f.to_celsius(fahrenheit.water_temperature()), celsius.water_temperature()));
+    }
+
+    fn yeasted_dough() -> Vec<FormulaIngredient> {
+        vec![
+            ingredient("Flour, Bread", 1.0, "kg", Some(IngredientRole::Flour)),
+            ingredient("Water", 700.0, "g", Some(IngredientRole::Liquid)),
+            ingredient("Salt, Kosher", 20.0, "g", Some(IngredientRole::Salt)),
+            ingredient("Yeast, Instant", 10.0, "g", Some(IngredientRole::Leavener)),
+            ingredient("Eggs", 2.0, "each", None),
+        ]
+    }
+
+    #[test]
+    fn parses_yeast_types() {
+        assert_eq!(YeastType::parse("Active-Dry"), Some(YeastType::ActiveDry));
+        assert_eq!(YeastType::parse("cake"), Some(YeastType::Fresh));
+        assert_eq!(YeastType::parse("levain"), Some(YeastType::Starter));
+        assert_eq!(YeastType::parse("osmotolerant"), None);
+    }
+
+    #[test]
+    fn converts_between_commercial_yeasts() {
+        let dough = yeasted_dough();
+        let swap = StarterSwap::DEFAULT;
+        let active = substitute_leavening(&dough, 3, YeastType::Instant, YeastType::ActiveDry, swap).unwrap();
+        assert!(close(active.new_leavener_grams, 12.5));
+        let fresh = substitute_leavening(&dough, 3, YeastType::Instant, YeastType::Fresh, swap).unwrap();
+        assert!(close(fresh.new_leavener_grams, 30.0));
+        assert_eq!(fresh.lines[3].after_grams, Some(0.0));
+        // Flour and water are untouched by yeast-to-yeast swaps
+        assert_eq!(fresh.lines[0].after_grams, Some(1000.0));
+        assert_eq!(fresh.lines[4].after_grams, None);
+    }
+
+    #[test]
+    fn swapping_to_starter_moves_flour_and_water_into_it() {
+        let result = substitute_leavening(&yeasted_dough(), 3, YeastType::Instant, YeastType::Starter, StarterSwap::DEFAULT)
+            .unwrap();
+        // 20% of 1000 g flour = 200 g starter = 100 g flour + 100 g water
+        assert!(close(result.new_leavener_grams, 200.0));
+        assert!(close(result.lines[0].after_grams.unwrap(), 900.0));
+        assert!(close(result.lines[1].after_grams.unwrap(), 600.0));
+        assert!(close(result.lines[2].after_grams.unwrap(), 20.0));
+    }
+
+    #[test]
+    fn swapping_from_starter_adds_flour_and_water_back() {
+        let mut dough = country_loaf();
+        dough.push(preferment("Levain", 160.0, Some(Preferment { kind: PrefermentKind::Levain, hydration: 60.0, inoculation: 20.0 })));
+        let swap = StarterSwap { hydration: 60.0, ..StarterSwap::DEFAULT };
+        let result = substitute_leavening(&dough, 4, YeastType::Starter, YeastType::Instant, swap).unwrap();
+        // At 60% hydration the 160 g levain gives back 100 g flour and 60 g water
+        let flour = result.lines[0].after_grams.unwrap() + result.lines[1].after_grams.unwrap();
+        assert!(close(flour, 1100.0));
+        assert!(close(result.lines[2].after_grams.unwrap(), 780.0));
+        assert!(close(result.new_leavener_grams, 11.0));
+    }
+
+    #[test]
+    fn rejects_substitutions_that_cannot_work() {
+        let mut dough = yeasted_dough();
+        dough[3].unit = "tsp".to_string();
+        let swap = StarterSwap::DEFAULT;
+        assert_eq!(
+            substitute_leavening(&dough, 3, YeastType::Instant, YeastType::Fresh, swap),
+            Err(BakersMathError::NotWeighed("Yeast, Instant".to_string()))
+        );
+
+        let no_water = vec![
+            ingredient("Flour", 500.0, "g", Some(IngredientRole::Flour)),
+            ingredient("Yeast", 5.0, "g", Some(IngredientRole::Leavener)),
+        ];
+        assert_eq!(substitute_leavening(&no_water, 1, YeastType::Instant, YeastType::Starter, swap), Err(BakersMathError::NoLiquid));
+
+        let huge = StarterSwap { starter_percent: 500.0, ..swap };
+        assert_eq!(
+            substitute_leavening(&yeasted_dough(), 3, YeastType::Instant, YeastType::Starter, huge),
+            Err(BakersMathError::StarterTooLarge)
+        );
+
+        let mut poolish_dough = country_loaf();
+        poolish_dough.push(preferment("Poolish", 400.0, Some(Preferment { kind: PrefermentKind::Poolish, hydration: 100.0, inoculation: 0.1 })));
+        assert_eq!(
+            substitute_leavening(&poolish_dough, 4, YeastType::Starter, YeastType::Instant, swap),
+            Err(BakersMathError::YeastedPreferment("Poolish".to_string(), PrefermentKind::Poolish))
+        );
+    }
+}
@@ -1,14 +1,51 @@
 // src/cli.rs
 use crate::db::{add_inventory_item, add_transaction, calculate_recipe_cost, deduct_recipe_from_inventory, filter_by_date, 
-    get_all_inventory, get_ingredients_for_recipe, get_recipe_collection, read_transactions, transaction_filter, 
+    get_all_inventory, get_ingredients_for_recipe, get_all_recipe_ingredients, get_recipe_collection, read_transactions, transaction_filter, 
     update_inventory_cost, update_inventory_quantity, update_msrp_for_recipe, write_csv_transaction_report, reset_database,
-    run_integrity_check, vacuum_database
+    run_integrity_check, vacuum_database, get_transaction, update_transaction, unlock_transaction,
+    start_reconciliation, get_open_reconciliation, get_reconciliation, get_reconciliations,
+    get_reconciliation_transactions, set_transaction_cleared, last_reconciled_balance, reconciliation_cleared_balance, complete_reconciliation,
+    add_itemized_sale, get_sale_items, get_product_sales, record_production_run, get_finished_goods,
+    add_order, get_orders, get_order, get_order_items, get_order_sale_tax, update_order_status, add_order_deposit, fulfil_order,
+    add_customer, update_customer, get_customers, get_customer, get_orders_for_customer, get_transactions_for_customer,
+    add_price_tier, get_price_tiers, set_tier_price, get_tier_prices, resolve_unit_price,
+    add_invoice, find_invoice_for_source, get_invoice_details, get_all_invoice_details, record_invoice_payment,
+    void_invoice, next_invoice_number, add_tax_jurisdiction, update_tax_jurisdiction, get_tax_jurisdictions,
+    combined_tax_rate, set_category_taxable, get_category_tax_rules, compute_sale_tax, get_sale_tax_total, get_sales_tax_liability,
+    get_unclassified_sales, add_recurring_transaction, get_recurring_transactions, end_recurring_transaction,
+    post_recurring_occurrences, set_budget, get_budget_vs_actual, get_over_budget_categories, copy_actuals_to_budget,
+    get_transaction_categories, get_cogs_report, inventory_value, update_inventory_category, get_inventory_valuation,
+    take_inventory_snapshot, get_inventory_snapshots, get_snapshot_on_or_before, get_snapshot_items,
+    write_csv_inventory_report, write_csv_recipe_report, write_csv_recipe_ingredients_report, write_csv_recipe_costing_report,
+    read_transactions_between, get_recipe_costing, get_revenue_expense_series, get_low_stock_items,
+    get_formula_for_recipe, update_inventory_role, add_recipe_with_ingredients, add_recipe_variant, find_recipe_id_by_name,
+    get_preferment, set_preferment, update_recipe_dough_temp, get_formula_lines_for_recipe,
+    add_pan, get_pans, set_recipe_pan, get_recipe_steps, replace_recipe_steps,
+    add_recipe_step, update_recipe_step, delete_recipe_step, recipe_steps_from_instructions, get_resources,
+    update_resource_capacity, add_production_plan, get_production_plans, get_production_plan, get_production_plan_items,
+    delete_production_plan, post_production_plan, get_daily_unit_sales, add_forecast_event, get_forecast_events,
+    delete_forecast_event
 };
+use crate::models::{Transaction, TRANSACTION_TYPES, RecipeCollection, Order, ORDER_STATUSES, SaleTax, Customer, CUSTOMER_TYPES,
+    Invoice, format_invoice_number, TAX_LEVELS, RecurringTransaction, RECURRING_FREQUENCIES, RECURRING_TRANSACTION_TYPES, RecurringOccurrences, BUDGET_TYPES,
+    compare_snapshots, InventoryItem, Pan, PAN_SHAPES, pan_scaling, RecipeStep, STEP_TYPES,
+    ProductionPlanItem, PullListLine, ForecastEvent};
+use crate::bakers_math::{self, BakersMathError, DoughTarget, DoughTemperature, FormulaIngredient, FormulaPercentage, IngredientRole,
+    Preferment, PrefermentKind, StarterSwap, TempUnit, YeastType};
+use crate::schedule::{build_schedule, Schedule, ScheduleItem};
+use crate::steps::{format_minutes, parse_duration_range, parse_temperature_f, step_totals};
+use crate::plan::{PlannedRecipe, build_pull_list, friendly_quantity};
+use crate::forecast::{forecast, ForecastLine, SalesHistory};
+use crate::archive::{export_json, import_json, ImportMode, ARCHIVE_SCHEMA_VERSION};
+use crate::import::{import_inventory_csv, import_recipes_csv, import_recipe_ingredients_csv};
+use crate::reports::{write_invoice_documents, render_invoice_text, render_ar_aging, render_cogs_report,
+    render_inventory_valuation, render_snapshot_comparison, write_inventory_valuation_csv, write_snapshot_comparison_csv,
+    export_path, write_bookkeeping_workbook, render_dashboard_html, Dashboard, write_pull_list_csv};
 use rusqlite::Connection;
 use std::io::{self, Write};
-use chrono::Local;
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, Duration, Datelike};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::env;
 
 // CLI Helper Functions
@@ -20,6 +57,46 @@ pub fn wait_for_enter() {
     io::stdin().read_line(&mut dummy_input).unwrap();
 }
 
+// Prompt for a single line of input and return it trimmed
+pub fn prompt(label: &str) -> String {
+    let mut input = String::new();
+    print!("{}", label);
+    io::stdout().flush().unwrap();
+    io::stdin().read_line(&mut input).unwrap();
+    input.trim().to_string()
+}
+
+// Ask where to write an export and whether to timestamp the file name
+pub fn prompt_export_path(default_name: &str) -> PathBuf {
+    let location = prompt(&format!("Output file or folder (default reports/{}): ", default_name));
+    let timestamped = prompt("Add a timestamp to the file name? (y/N): ").eq_ignore_ascii_case("y");
+    export_path(&location, default_name, timestamped)
+}
+
+// Print transactions as a table
+pub fn print_transaction_table(transactions: &[Transaction]) {
+    println!("Transactions:");
+    println!(
+        "\n{:<4} | {:<12} | {:<10} | {:<12} | {:<14} | {:>9} | Description",
+        "ID", "Date", "Type", "Account", "Category", "Amount"
+    );
+    println!("{}", "-".repeat(92));
+    for transaction in transactions {
+        let status = if transaction.locked {
+            " 🔒"
+        } else if transaction.cleared {
+            " ✓"
+        } else {
+            ""
+        };
+        println!(
+            "{:<4} | {:<12} | {:<10} | {:<12} | {:<14} | ${:>8.2} | {}{}",
+            transaction.id, transaction.date, transaction.transaction_type, transaction.account,
+            transaction.category.as_deref().unwrap_or("-"), transaction.amount, transaction.description, status
+        )
+    }
+}
+
 // Backup database utility function
 pub fn backup_database() {
     let source_path = Path::new("bakery.db");
@@ -116,204 +193,93 @@ pub fn handle_vacuum(conn: &Connection) {
     wait_for_enter();
 }
 
-// Helper function to calculate baker's percentages
-fn calculate_bakers_percentages(ingredients: &Vec<(String, f32, String)>) {
-    // Step 1: Find the total flour weight
-    let mut total_flour_weight: f32 = 0.0;
-    
-    for (name, qty, unit) in ingredients {
-        // Make sure we're only counting ingredients with weight units
-        if unit.to_lowercase() == "g" || 
-           unit.to_lowercase() == "grams" || 
-           unit.to_lowercase() == "kg" {
-            
-            // Convert to grams if needed
-            let quantity_in_grams = if unit.to_lowercase() == "kg" {
-                qty * 1000.0
-            } else {
-                *qty
-            };
-            
-            // Check if the ingredient name contains "flour" (case-insensitive)
-            if name.to_lowercase().contains("flour") {
-                total_flour_weight += quantity_in_grams;
-            }
-        }
+// Prompt for an ingredient role; blank or unrecognised input means no role
+fn prompt_role() -> Option<IngredientRole> {
+    let roles: Vec<&str> = IngredientRole::ALL.iter().map(|r| r.as_str()).collect();
+    let input = prompt(&format!("Baker's math role ({}, blank for none): ", roles.join("/")));
+    if input.is_empty() {
+        return None;
     }
+    let role = IngredientRole::parse(&input);
+    if role.is_none() {
+        println!("⚠️ Unknown role '{}', leaving it untagged.", input);
+    }
+    role
+}
+
+// Helper function to print baker's percentages for a recipe
+fn print_bakers_percentages(ingredients: &[FormulaIngredient]) {
     // Display the ingredients
     println!("\nIngredients:");
-    for (name, qty, unit) in ingredients {
-        println!("- {} {} {}", qty, unit, name);
-    }
-    // If no flour was found, we can't calculate baker's percentages
-    if total_flour_weight == 0.0 {
-        println!("\n⚠️ No flour found in the recipe. Cannot calculate baker's percentages.");
-        return;
+    for ingredient in ingredients {
+        let role = ingredient.role.map(|r| r.as_str()).unwrap_or("untagged");
+        println!("- {} {} {} [{}]", ingredient.quantity, ingredient.unit, ingredient.name, role);
     }
-    
-    // Step 2: Calculate and display the baker's percentages
+
+    let result = match bakers_math::calculate(ingredients) {
+        Ok(result) => result,
+        Err(e) => {
+            println!("\n⚠️ Cannot calculate baker's percentages: {}.", e);
+            println!("Tag flour items with Inventory > Update Inventory Item > Update Ingredient Role.");
+            return;
+        }
+    };
+
     println!("\nBaker's Percentages:");
     println!("---------------------");
-    
-    // First, print the flour entries (should total 100%)
-    let mut flour_percentage_total: f32 = 0.0;
     println!("Flour Components:");
-    
-    for (name, qty, unit) in ingredients {
-        // Check if it's a weight unit
-        if unit.to_lowercase() == "g" || 
-           unit.to_lowercase() == "grams" || 
-           unit.to_lowercase() == "kg" {
-            
-            // Convert to grams if needed
-            let quantity_in_grams = if unit.to_lowercase() == "kg" {
-                qty * 1000.0
-            } else {
-                *qty
-            };
-            
-            if name.to_lowercase().contains("flour") {
-                let percentage = (quantity_in_grams / total_flour_weight) * 100.0;
-                flour_percentage_total += percentage;
-                println!("- {}: {:.1}%", name, percentage);
-            }
-        }
+    for flour in &result.flour {
+        println!("- {}: {:.1}%", flour.name, flour.percent);
     }
-    
-    println!("Total Flour: {:.1}% ({:.1} grams)", flour_percentage_total, total_flour_weight);
-    
-    // Then print all other ingredients
-    println!("\nOther Ingredients:");
-    for (name, qty, unit) in ingredients {
-        if unit.to_lowercase() == "g" || 
-           unit.to_lowercase() == "grams" || 
-           unit.to_lowercase() == "kg" {
-            
-            // Convert to grams if needed
-            let quantity_in_grams = if unit.to_lowercase() == "kg" {
-                qty * 1000.0
-            } else {
-                *qty
-            };
-            
-            if !name.to_lowercase().contains("flour") {
-                let percentage = (quantity_in_grams / total_flour_weight) * 100.0;
-                println!("- {}: {:.1}%", name, percentage);
-                
-                // Add special labels for common ingredients
-                if name.to_lowercase().contains("water") {
-                    println!("  (Hydration: {:.1}%)", percentage);
-                } else if name.to_lowercase().contains("salt") {
-                    println!("  (Salt percentage: {:.1}%)", percentage);
-                } else if name.to_lowercase().contains("yeast") && percentage < 2.0 {
-                    println!("  (Yeast percentage: {:.2}%)", percentage);
-                } else if name.to_lowercase().contains("starter") || 
-                          name.to_lowercase().contains("levain") || 
-                          name.to_lowercase().contains("sourdough") {
-                    println!("  (Starter percentage: {:.1}%)", percentage);
-                }
-            }
-        } else {
-            // For non-weight ingredients, just show them without percentages
-            println!("- {}: {} {} (not included in baker's percentages)", 
-                name, qty, unit);
+    let flour_total = result.flour.iter().fold(0.0, |sum, f| sum + f.percent);
+    println!("Total Flour: {:.1}% ({:.1} grams)", flour_total, result.total_flour_grams);
+
+    if !result.preferments.is_empty() {
+        println!("\nPreferments:");
+        for p in &result.preferments {
+            let seed = if p.preferment.kind.seeded_with_starter() { "starter" } else { "yeast" };
+            println!(
+                "- {} ({}, {:.0}% hydration, {:.1}% inoculation{}): {:.1} g = {:.1} g flour + {:.1} g water, built with {:.1} g {}",
+                p.name, p.preferment.kind.as_str(), p.preferment.hydration, p.preferment.inoculation,
+                if p.assumed { ", assumed" } else { "" },
+                p.grams, p.flour_grams, p.water_grams, p.preferment.seed_grams(p.grams), seed
+            );
         }
     }
-    
-    // Step 3: Detect and display special information about the recipe
-    let mut hydration = 0.0;
-    let mut salt_percentage = 0.0;
-    let mut has_commercial_yeast = false;
-    let mut has_sourdough = false;
-    let mut starter_qty = 0.0;
-    
-    for (name, qty, unit) in ingredients {
-        // Check if it's a weight unit
-        if unit.to_lowercase() == "g" || 
-           unit.to_lowercase() == "grams" || 
-           unit.to_lowercase() == "kg" {
-            
-            // Convert to grams if needed
-            let quantity_in_grams = if unit.to_lowercase() == "kg" {
-                qty * 1000.0
-            } else {
-                *qty
-            };
-            
-            let percentage = (quantity_in_grams / total_flour_weight) * 100.0;
-            
-            if name.to_lowercase().contains("water") {
-                hydration += percentage;
-            } else if name.to_lowercase().contains("salt") {
-                salt_percentage += percentage;
-            } else if name.to_lowercase().contains("yeast") && 
-                      !name.to_lowercase().contains("sourdough") {
-                has_commercial_yeast = true;
-            } else if name.to_lowercase().contains("starter") || 
-                      name.to_lowercase().contains("levain") || 
-                      name.to_lowercase().contains("sourdough") {
-                has_sourdough = true;
-                starter_qty = quantity_in_grams;
-            }
-        }
+
+    println!("\nOther Ingredients:");
+    for other in &result.others {
+        let role = other.role.map(|r| r.as_str()).unwrap_or("untagged");
+        println!("- {}: {:.1}% ({})", other.name, other.percent, role);
     }
-    
-    // Adjust hydration if sourdough starter is present (assuming 100% hydration starter)
-    if has_sourdough && starter_qty > 0.0 {
-        // Calculate effective hydration from starter
-        // For 100% hydration starter, half is water and half is flour
-        let starter_water = starter_qty / 2.0;
-        
-        // Add water from starter to total hydration calculation
-        let starter_water_percentage = (starter_water / total_flour_weight) * 100.0;
-        println!("\nStarter contribution to hydration: {:.1}%", starter_water_percentage);
-        
-        // Note: We don't need to adjust total_flour_weight here since
-        // the flour in the starter was already counted in the total flour calculation
+    for unweighed in &result.unweighed {
+        println!("- {}: {} {} (not included in baker's percentages)",
+            unweighed.name, unweighed.quantity, unweighed.unit);
     }
-    
+
     // Print a recipe summary
     println!("\nRecipe Summary:");
-    println!("Total flour: {:.1} grams", total_flour_weight);
-    if hydration > 0.0 {
-        println!("Hydration: {:.1}%", hydration);
-    }
-    if salt_percentage > 0.0 {
-        println!("Salt: {:.1}%", salt_percentage);
-    }
-    
-    // Detect what kind of bread this is
-    if has_commercial_yeast && has_sourdough {
-        println!("Type: Hybrid (commercial yeast + sourdough)");
-    } else if has_sourdough {
-        println!("Type: Sourdough");
-    } else if has_commercial_yeast {
-        println!("Type: Commercial yeast");
-    } else {
-        println!("Type: Quick bread or unknown (no yeast/starter detected)");
-    }
-    
-    // Baker's percentage analysis
-    println!("\nBaker's Percentage Analysis:");
-    if hydration >= 65.0 && hydration < 70.0 {
-        println!("Standard hydration level for most bread types");
-    } else if hydration >= 70.0 && hydration < 80.0 {
-        println!("High hydration - suitable for ciabatta, focaccia, or rustic breads");
-    } else if hydration >= 80.0 {
-        println!("Very high hydration - may be challenging to handle, consider stretch & folds");
-    } else if hydration < 65.0 && hydration >= 60.0 {
-        println!("Lower hydration - produces denser bread, easier to handle");
-    } else if hydration < 60.0 {
-        println!("Low hydration - typical for bagels, pretzels, or sandwich bread");
+    println!("Total flour: {:.1} grams", result.total_flour_grams);
+    let summary = [
+        ("Hydration", result.hydration),
+        ("Salt", result.salt),
+        ("Sugar", result.sugar),
+        ("Fat", result.fat),
+        ("Leavener", result.leavener),
+        ("Preferment", result.preferment),
+        ("Prefermented flour", result.prefermented_flour),
+    ];
+    for (label, percent) in summary {
+        if percent > 0.0 {
+            println!("{}: {:.1}%", label, percent);
+        }
     }
-    
-    if salt_percentage > 0.0 {
-        if salt_percentage < 1.8 {
-            println!("Salt is below standard range (1.8-2.2%)");
-        } else if salt_percentage > 2.2 {
-            println!("Salt is above standard range (1.8-2.2%)");
-        } else {
-            println!("Salt is within standard range (1.8-2.2%)");
+    println!("Type: {}", result.leavening.label());
+
+    if !result.notes.is_empty() {
+        println!("\nBaker's Percentage Analysis:");
+        for note in &result.notes {
+            println!("{}", note);
         }
     }
 }
@@ -325,6 +291,9 @@ pub fn handle_inventory_menu(conn: &Connection) {
     println!("1. View Inventory");
     println!("2. Add Inventory Item");
     println!("3. Update Inventory Item");
+    println!("4. Inventory Valuation Report");
+    println!("5. Take Inventory Snapshot");
+    println!("6. View & Compare Snapshots");
     println!("100. Exit");
 
     print!("Choose an option: ");
@@ -340,8 +309,9 @@ pub fn handle_inventory_menu(conn: &Connection) {
             println!("\n📦 Inventory:");
             for item in inventory {
                 println!(
-                    "{} - {}: {:.2} {} @ ${:.2}",
-                    item.id, item.name, item.quantity, item.unit, item.cost_per_unit
+                    "{} - {}: {:.2} {} @ ${:.2} [{}] role: {}",
+                    item.id, item.name, item.quantity, item.unit, item.cost_per_unit, item.category_label(),
+                    item.role.as_deref().unwrap_or("-")
                 );
             }
             // Pause app and wait for user input
@@ -372,10 +342,14 @@ pub fn handle_inventory_menu(conn: &Connection) {
             io::stdout().flush().unwrap();
             io::stdin().read_line(&mut cost_str).unwrap();
 
+            let category = prompt("Category (e.g. Flour & Grains, blank for none): ");
+            let role = prompt_role();
+
             let quantity: f32 = quantity_str.trim().parse().unwrap_or(0.0);
             let cost: f32 = cost_str.trim().parse().unwrap_or(0.0);
+            let category = if category.is_empty() { None } else { Some(category.as_str()) };
 
-            if let Err(e) = add_inventory_item(conn, name.trim(), unit.trim(), quantity, cost) {
+            if let Err(e) = add_inventory_item(conn, name.trim(), unit.trim(), quantity, cost, category, role.map(|r| r.as_str())) {
                 println!("❌ Failed to add item: {}", e);
             } else {
                 println!(
@@ -418,7 +392,7 @@ pub fn handle_inventory_menu(conn: &Connection) {
             input.clear();
             
             // Identify which value to update
-            println!("What would you like to update?\n1. Update Item Cost\n2. Update Item Quantity");
+            println!("What would you like to update?\n1. Update Item Cost\n2. Update Item Quantity\n3. Update Item Category\n4. Update Ingredient Role\n5. Update Preferment Details");
             io::stdout().flush().unwrap();
             io::stdin().read_line(&mut input).unwrap();
 
@@ -474,6 +448,98 @@ pub fn handle_inventory_menu(conn: &Connection) {
                     // Pause app and wait for user input
                     wait_for_enter();
                 }
+                // Update item category
+                "3" => {
+                    if let Some(item) = selected_item {
+                        println!("Current category for {}: {}", item.name, item.category_label());
+                        let category = prompt("New category (blank to clear): ");
+                        let category = if category.is_empty() { None } else { Some(category.as_str()) };
+
+                        match update_inventory_category(conn, inventory_item_id, category) {
+                            Ok(()) => println!("✅ Category set to {}", category.unwrap_or("Uncategorized")),
+                            Err(e) => println!("❌ Failed to update category: {}", e),
+                        }
+                    } else {
+                        println!("❌ Item not found!");
+                    }
+
+                    // Pause app and wait for user input
+                    wait_for_enter();
+                }
+                // Update ingredient role
+                "4" => {
+                    if let Some(item) = selected_item {
+                        println!("Current role for {}: {}", item.name, item.role.as_deref().unwrap_or("none"));
+                        let role = prompt_role().map(|r| r.as_str());
+
+                        match update_inventory_role(conn, inventory_item_id, role) {
+                            Ok(()) => println!("✅ Role set to {}", role.unwrap_or("none")),
+                            Err(e) => println!("❌ Failed to update role: {}", e),
+                        }
+                    } else {
+                        println!("❌ Item not found!");
+                    }
+
+                    // Pause app and wait for user input
+                    wait_for_enter();
+                }
+                // Update preferment details
+                "5" => {
+                    if let Some(item) = selected_item {
+                        let current = match get_preferment(conn, inventory_item_id) {
+                            Ok(Some(p)) => {
+                                println!(
+                                    "Current details for {}: {} at {:.0}% hydration, {:.1}% inoculation",
+                                    item.name, p.kind.as_str(), p.hydration, p.inoculation
+                                );
+                                p
+                            }
+                            Ok(None) => {
+                                println!("No preferment details recorded for {}.", item.name);
+                                Preferment::DEFAULT
+                            }
+                            Err(e) => {
+                                println!("❌ Failed to load preferment details: {}", e);
+                                wait_for_enter();
+                                return;
+                            }
+                        };
+
+                        let kinds: Vec<&str> = PrefermentKind::ALL.iter().map(|k| k.as_str()).collect();
+                        let kind_input = prompt(&format!("Type ({}, default {}): ", kinds.join("/"), current.kind.as_str()));
+                        let kind = if kind_input.is_empty() { Some(current.kind) } else { PrefermentKind::parse(&kind_input) };
+                        match kind {
+                            Some(kind) => {
+                                let hydration = prompt_percent(
+                                    &format!("Hydration % (water per 100 flour, default {:.0}): ", current.hydration),
+                                    current.hydration,
+                                    Preferment::MAX_HYDRATION,
+                                );
+                                let seed = if kind.seeded_with_starter() { "starter" } else { "yeast" };
+                                let inoculation = prompt_percent(
+                                    &format!("Inoculation % ({} per 100 fresh flour, default {}): ", seed, current.inoculation),
+                                    current.inoculation,
+                                    Preferment::MAX_INOCULATION,
+                                );
+
+                                let preferment = Preferment { kind, hydration, inoculation };
+                                match set_preferment(conn, inventory_item_id, &preferment) {
+                                    Ok(()) => println!(
+                                        "✅ {} is a {} at {:.0}% hydration, {:.1}% inoculation",
+                                        item.name, kind.as_str(), hydration, inoculation
+                                    ),
+                                    Err(e) => println!("❌ Failed to update preferment: {}", e),
+                                }
+                            }
+                            None => println!("❌ Unknown preferment type '{}'", kind_input),
+                        }
+                    } else {
+                        println!("❌ Item not found!");
+                    }
+
+                    // Pause app and wait for user input
+                    wait_for_enter();
+                }
                 &_ => {
                     println!("Error--Invalid option\n Returning to Main Menu...");
                 }
@@ -481,6 +547,51 @@ pub fn handle_inventory_menu(conn: &Connection) {
             // Pause app and wait for user input
             wait_for_enter();
         }
+        // Inventory Valuation Report
+        "4" => {
+            handle_inventory_valuation(conn);
+        }
+        // Take Inventory Snapshot
+        "5" => {
+            let default_date = today();
+            let mut snapshot_date = prompt(&format!("Snapshot date (default {}): ", default_date));
+            if snapshot_date.is_empty() {
+                snapshot_date = default_date.clone();
+            }
+            if NaiveDate::parse_from_str(&snapshot_date, "%Y-%m-%d").is_err() {
+                println!("❌ Invalid snapshot date.");
+                wait_for_enter();
+                return;
+            }
+            let label = prompt("Label (e.g. Month-end count, blank for none): ");
+            // The snapshot copies today's quantities, so one dated another day says when it was really counted
+            let label = if snapshot_date != default_date {
+                println!("⚠️ Quantities are captured as of now, not as of {}.", snapshot_date);
+                Some(if label.is_empty() {
+                    format!("captured {}", default_date)
+                } else {
+                    format!("{} (captured {})", label, default_date)
+                })
+            } else if label.is_empty() {
+                None
+            } else {
+                Some(label)
+            };
+
+            match take_inventory_snapshot(conn, &snapshot_date, label.as_deref()) {
+                Ok(id) => println!(
+                    "✅ Snapshot #{} taken for {} (value ${:.2})",
+                    id, snapshot_date, inventory_value(conn).unwrap_or(0.0)
+                ),
+                Err(e) => println!("❌ Failed to take snapshot: {}", e),
+            }
+            // Pause app and wait for user input
+            wait_for_enter();
+        }
+        // View & Compare Snapshots
+        "6" => {
+            handle_inventory_snapshots(conn);
+        }
         // Exit Inventory Menu
         "100" => {
             println!("👋 Exiting. Goodbye!");
@@ -493,6 +604,82 @@ pub fn handle_inventory_menu(conn: &Connection) {
     }
 }
 
+// Current stock valuation, optionally exported to CSV
+fn handle_inventory_valuation(conn: &Connection) {
+    let items = get_inventory_valuation(conn).expect("Error fetching inventory");
+    println!("\n{}", render_inventory_valuation(&format!("as of {}", today()), &items));
+
+    if prompt("Export to CSV? (y/N): ").eq_ignore_ascii_case("y") {
+        let path = prompt_export_path(&format!("inventory-valuation-{}.csv", today()));
+        match write_inventory_valuation_csv(&path, &items) {
+            Ok(()) => println!("✅ Valuation written to {}", path.display()),
+            Err(e) => println!("❌ Failed to write CSV: {}", e),
+        }
+    }
+    wait_for_enter();
+}
+
+// List snapshots, then view one or compare two
+fn handle_inventory_snapshots(conn: &Connection) {
+    let snapshots = get_inventory_snapshots(conn).expect("Error fetching snapshots");
+    if snapshots.is_empty() {
+        println!("⚠️ No inventory snapshots yet. Take one from the Inventory menu.");
+        wait_for_enter();
+        return;
+    }
+
+    println!("\n📸 Inventory Snapshots:");
+    for snapshot in &snapshots {
+        println!("{} — {} items, ${:.2}", snapshot.title(), snapshot.item_count, snapshot.total_value);
+    }
+
+    let first: i32 = prompt("\nSnapshot ID to view (or earlier ID to compare): ").parse().unwrap_or(0);
+    let before = match snapshots.iter().find(|s| s.id == first) {
+        Some(snapshot) => snapshot,
+        None => {
+            println!("❌ Snapshot not found!");
+            wait_for_enter();
+            return;
+        }
+    };
+    let before_items = get_snapshot_items(conn, before.id).expect("Error fetching snapshot items");
+
+    let second = prompt("Later snapshot ID to compare with (blank to view only): ");
+    if second.is_empty() {
+        println!("\n{}", render_inventory_valuation(&before.title(), &before_items));
+        if prompt("Export to CSV? (y/N): ").eq_ignore_ascii_case("y") {
+            let path = prompt_export_path(&format!("inventory-snapshot-{}.csv", before.id));
+            match write_inventory_valuation_csv(&path, &before_items) {
+                Ok(()) => println!("✅ Snapshot written to {}", path.display()),
+                Err(e) => println!("❌ Failed to write CSV: {}", e),
+            }
+        }
+        wait_for_enter();
+        return;
+    }
+
+    let after = match snapshots.iter().find(|s| s.id.to_string() == second) {
+        Some(snapshot) => snapshot,
+        None => {
+            println!("❌ Snapshot not found!");
+            wait_for_enter();
+            return;
+        }
+    };
+    let after_items = get_snapshot_items(conn, after.id).expect("Error fetching snapshot items");
+    let changes = compare_snapshots(&before_items, &after_items);
+    println!("\n{}", render_snapshot_comparison(&before.title(), &after.title(), &changes));
+
+    if prompt("Export to CSV? (y/N): ").eq_ignore_ascii_case("y") {
+        let path = prompt_export_path(&format!("inventory-changes-{}-to-{}.csv", before.id, after.id));
+        match write_snapshot_comparison_csv(&path, &changes) {
+            Ok(()) => println!("✅ Comparison written to {}", path.display()),
+            Err(e) => println!("❌ Failed to write CSV: {}", e),
+        }
+    }
+    wait_for_enter();
+}
+
 // Recipe Menu
 pub fn handle_recipe_menu(conn: &Connection) {
     println!("📖 Recipe Management");
@@ -502,6 +689,16 @@ pub fn handle_recipe_menu(conn: &Connection) {
     println!("4. Deduct Recipe from Inventory");
     println!("5. Calculate Unit MSRP for Recipe");
     println!("6. Calculate Baker's Percentage for Recipe");
+    println!("7. Record Production Run");
+    println!("8. View Finished Goods");
+    println!("9. Formula Designer");
+    println!("10. Dough Temperature Calculator");
+    println!("11. Substitute Yeast / Leavening");
+    println!("12. Pans & Pan Scaling");
+    println!("13. Recipe Steps");
+    println!("14. Production Schedule");
+    println!("15. Production Plans & Pull List");
+    println!("16. Demand Forecast");
     println!("100. Exit");
 
     print!("Choose an option: ");
@@ -520,6 +717,10 @@ pub fn handle_recipe_menu(conn: &Connection) {
                     "ID: {} - {} MSRP: ${:?}\nCategory: {:#} \n(yields {})\nPrep time: {}\nBake time: {}\nTotal time: {}\n: \n{:#}\n",
                     recipe.id, recipe.name, recipe.msrp_per_unit, recipe.category, recipe.yield_quantity, recipe.prep_time.unwrap_or("N/A".to_string()), recipe.bake_time.unwrap_or("N/A".to_string()), recipe.total_time.unwrap_or("N/A".to_string()), recipe.instructions
                 );
+                let steps = get_recipe_steps(conn, recipe.id).unwrap_or_default();
+                if !steps.is_empty() {
+                    println!("Time from steps: {}\n", step_totals(&steps).label());
+                }
             }
             // Pause app and wait for user input
             wait_for_enter();
@@ -591,7 +792,7 @@ pub fn handle_recipe_menu(conn: &Connection) {
             io::stdin().read_line(&mut input).unwrap();
             let recipe_id: i32 = input.trim().parse().unwrap_or(0);
 
-            let result = deduct_recipe_from_inventory(conn, recipe_id);
+            let result = deduct_recipe_from_inventory(conn, recipe_id, 1.0);
 
             match result {
                 Ok(_) => println!("✅ Recipe deducted from inventory."),
@@ -673,7 +874,7 @@ pub fn handle_recipe_menu(conn: &Connection) {
             };
         
             // Get the recipe ingredients with their details from the database
-            let ingredients = get_ingredients_for_recipe(conn, recipe_id)
+            let ingredients = get_formula_for_recipe(conn, recipe_id)
                 .expect("Failed to load recipe ingredients");
             
             if ingredients.is_empty() {
@@ -682,18 +883,82 @@ pub fn handle_recipe_menu(conn: &Connection) {
                 println!("\nRecipe: {}", recipe.name);
                 println!("Yield: {} units", recipe.yield_quantity);
                 
-                // Display the ingredients
-                println!("\nIngredients:");
-                for (name, qty, unit) in &ingredients {
-                    println!("- {} {} {}", qty, unit, name);
-                }
-                
                 // Calculate and display baker's percentages
-                calculate_bakers_percentages(&ingredients);
+                print_bakers_percentages(&ingredients);
+            }
+            // Pause app and wait for user input
+            wait_for_enter();
+        }
+        // Record Production Run
+        "7" => {
+            let recipes = get_recipe_collection(conn).expect("Error fetching recipes");
+
+            println!("\nSelect a recipe that was baked:");
+            for recipe in &recipes {
+                println!("{}: {} (yield: {})", recipe.id, recipe.name, recipe.yield_quantity);
+            }
+
+            let recipe_id: i32 = prompt("Enter recipe ID: ").parse().unwrap_or(0);
+            let batches: f32 = prompt("Number of batches (default 1): ").parse().unwrap_or(1.0);
+            if batches <= 0.0 {
+                println!("❌ Batches must be more than zero.");
+                wait_for_enter();
+                return;
+            }
+            let mut date = prompt("Date (YYYY-MM-DD, default today): ");
+            if date.is_empty() {
+                date = Local::now().format("%Y-%m-%d").to_string();
+            }
+
+            match record_production_run(conn, recipe_id, batches, &date) {
+                Ok(units) => println!("✅ Produced {} units; ingredients deducted from inventory.", units),
+                Err(e) => println!("❌ Error recording production run: {}", e),
+            }
+            // Pause app and wait for user input
+            wait_for_enter();
+        }
+        // View Finished Goods
+        "8" => {
+            let goods = get_finished_goods(conn).expect("Error fetching finished goods");
+            println!("\n🧁 Finished Goods:");
+            for (recipe_id, name, quantity) in goods {
+                println!("{} - {}: {} units", recipe_id, name, quantity);
             }
             // Pause app and wait for user input
             wait_for_enter();
         }
+        // Formula Designer
+        "9" => {
+            handle_formula_designer(conn);
+        }
+        // Dough Temperature Calculator
+        "10" => {
+            handle_dough_temperature(conn);
+        }
+        // Substitute Yeast / Leavening
+        "11" => {
+            handle_leavening_substitution(conn);
+        }
+        // Pans & Pan Scaling
+        "12" => {
+            handle_pan_menu(conn);
+        }
+        // Recipe Steps
+        "13" => {
+            handle_recipe_steps(conn);
+        }
+        // Production Schedule
+        "14" => {
+            handle_production_schedule(conn);
+        }
+        // Production Plans & Pull List
+        "15" => {
+            handle_production_plans(conn);
+        }
+        // Demand Forecast
+        "16" => {
+            handle_demand_forecast(conn);
+        }
         // Exit Recipe Menu
         "100" => {
             println!("👋 Exiting. Goodbye!");
@@ -706,167 +971,3635 @@ pub fn handle_recipe_menu(conn: &Connection) {
 
 }
 
-// Transaction Menu
-pub fn handle_transaction_menu(conn: &Connection) {
-    println!("💰 Transaction Management");
-    println!("1. Add Transaction");
-    println!("2. View Transactions");
-    println!("3. Filter Transactions");
-    println!("4. Print CSV Transaction Report");
-    println!("100. Exit");
+// Build a new recipe from baker's percentages and a target dough weight
+fn handle_formula_designer(conn: &Connection) {
+    println!("\n🧮 Formula Designer");
+    let inventory = get_all_inventory(conn).expect("Error fetching inventory");
+    // Only items stocked by weight can be scaled from percentages
+    let weighed: Vec<&InventoryItem> = inventory
+        .iter()
+        .filter(|item| bakers_math::to_grams(1.0, &item.unit).is_some())
+        .collect();
 
-    print!("Choose an option: ");
-    io::stdout().flush().unwrap();
+    println!("\nIngredients stocked by weight:");
+    for item in &weighed {
+        println!("{} - {} ({}) [{}]", item.id, item.name, item.unit, item.role.as_deref().unwrap_or("untagged"));
+    }
 
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).unwrap();
+    let mut chosen: Vec<&InventoryItem> = Vec::new();
+    let mut formula: Vec<FormulaPercentage> = Vec::new();
+    loop {
+        let input = prompt("\nIngredient ID (blank when done): ");
+        if input.is_empty() {
+            break;
+        }
+        let item_id: i32 = input.parse().unwrap_or(0);
+        let item = match weighed.iter().find(|item| item.id == item_id) {
+            Some(item) => *item,
+            None => {
+                println!("⚠️ No weighed ingredient with that ID.");
+                continue;
+            }
+        };
+        if chosen.iter().any(|c| c.id == item.id) {
+            println!("⚠️ {} is already in the formula.", item.name);
+            continue;
+        }
+        let percent: f32 = match prompt(&format!("Baker's % for {}: ", item.name)).parse() {
+            Ok(p) if p > 0.0 => p,
+            _ => {
+                println!("⚠️ Enter a percentage greater than zero.");
+                continue;
+            }
+        };
+        let role = item.role.as_deref().and_then(IngredientRole::parse);
+        formula.push(FormulaPercentage { name: item.name.clone(), role, percent });
+        chosen.push(item);
 
-    match input.trim() {
-        // Add Transaction
-        "1" => {
-            let mut date = String::new();
-            let mut transaction_type = String::new();
-            let mut amount_str = String::new();
-            let mut description = String::new();
+        let flour_total = formula
+            .iter()
+            .filter(|line| line.role == Some(IngredientRole::Flour))
+            .fold(0.0, |sum, line| sum + line.percent);
+        println!("Flour so far: {:.1}% (flour items must total 100%)", flour_total);
+    }
 
-            println!("💰 Add New Transaction");
+    if formula.is_empty() {
+        println!("⚠️ No ingredients entered.");
+        wait_for_enter();
+        return;
+    }
 
-            print!("Date (YYYY-MM-DD): ");
-            io::stdout().flush().unwrap();
-            io::stdin().read_line(&mut date).unwrap();
+    println!("\nTarget:\n1. Total dough weight\n2. Number of pieces x weight per piece");
+    let target = match prompt("Choose an option: ").as_str() {
+        "1" => DoughTarget::TotalWeight(prompt("Total dough weight (grams): ").parse().unwrap_or(0.0)),
+        "2" => DoughTarget::Pieces {
+            count: prompt("Number of pieces: ").parse().unwrap_or(0),
+            grams_each: prompt("Weight per piece (grams): ").parse().unwrap_or(0.0),
+        },
+        _ => {
+            println!("Error--Invalid option\n Returning to Main Menu...");
+            return;
+        }
+    };
+    let loss_percent: f32 = prompt("Mixing loss allowance % (default 2): ").parse().unwrap_or(2.0);
+    let rounding: f32 = prompt("Round weights to the nearest grams (default 1): ").parse().unwrap_or(1.0);
 
-            print!("Transaction type (sale/expense): ");
-            io::stdout().flush().unwrap();
-            io::stdin().read_line(&mut transaction_type).unwrap();
+    let scaled = match bakers_math::scale_formula(&formula, target, loss_percent, rounding) {
+        Ok(scaled) => scaled,
+        Err(e) => {
+            println!("❌ Cannot scale formula: {}.", e);
+            wait_for_enter();
+            return;
+        }
+    };
 
-            print!("Amount: ");
-            io::stdout().flush().unwrap();
-            io::stdin().read_line(&mut amount_str).unwrap();
+    println!("\n{:<36} {:>8} {:>10} {:>12}", "Ingredient", "Baker %", "Grams", "Stock unit");
+    for (line, item) in scaled.lines.iter().zip(&chosen) {
+        let in_unit = bakers_math::from_grams(line.grams, &item.unit).unwrap_or(0.0);
+        println!("{:<36} {:>7.1}% {:>10.1} {:>8.3} {}", line.name, line.percent, line.grams, in_unit, item.unit);
+    }
+    println!("{:<36} {:>7.1}% {:>10.1}", "Total", scaled.total_percent, scaled.total_dough_grams);
+    println!(
+        "Target {:.1} g after {:.1}% mixing loss; flour {:.1} g",
+        scaled.target_grams, scaled.loss_percent, scaled.total_flour_grams
+    );
 
-            print!("Description: ");
-            io::stdout().flush().unwrap();
-            io::stdin().read_line(&mut description).unwrap();
+    if prompt("\nSave as a new recipe? (y/n): ").to_lowercase() != "y" {
+        println!("Formula not saved.");
+        wait_for_enter();
+        return;
+    }
 
-            let amount: f32 = amount_str.trim().parse().unwrap_or(0.0);
+    let name = prompt("Recipe name: ");
+    if name.is_empty() {
+        println!("❌ A recipe name is required.");
+        wait_for_enter();
+        return;
+    }
+    if let Ok(Some(_)) = find_recipe_id_by_name(conn, &name) {
+        println!("❌ A recipe named '{}' already exists.", name);
+        wait_for_enter();
+        return;
+    }
+    let mut category = prompt("Category (default Bread): ");
+    if category.is_empty() {
+        category = "Bread".to_string();
+    }
+    let default_yield = match target {
+        DoughTarget::Pieces { count, .. } => count as i32,
+        DoughTarget::TotalWeight(_) => 1,
+    };
+    let yield_quantity: i32 = prompt(&format!("Yield in units (default {}): ", default_yield))
+        .parse()
+        .unwrap_or(default_yield);
+    let instructions = prompt("Instructions (optional): ");
 
-            if let Err(e) = add_transaction(
-                conn,
-                date.trim(),
-                transaction_type.trim(),
-                amount,
-                description.trim(),
-            ) {
-                println!("❌ Failed to add transaction: {}", e);
-            } else {
-                println!(
-                    "✅ Logged ${:.2} {} on {} — {}",
-                    amount,
-                    transaction_type.trim(),
-                    date.trim(),
-                    description.trim()
-                );
-            }
-            // Pause app and wait for user input
-            wait_for_enter();
+    let recipe = RecipeCollection {
+        id: 0,
+        name,
+        instructions,
+        yield_quantity,
+        category,
+        prep_time: None,
+        bake_time: None,
+        total_time: None,
+        msrp_per_unit: None,
+        target_ddt_c: None,
+        friction_factor_c: None,
+        pan_id: None,
+    };
+    // Quantities are stored in each inventory item's own unit
+    let lines: Vec<(i32, f32)> = scaled
+        .lines
+        .iter()
+        .zip(&chosen)
+        .map(|(line, item)| (item.id, bakers_math::from_grams(line.grams, &item.unit).unwrap_or(0.0)))
+        .collect();
+
+    match add_recipe_with_ingredients(conn, &recipe, &lines) {
+        Ok(recipe_id) => println!("✅ Saved recipe {} '{}' with {} ingredients.", recipe_id, recipe.name, lines.len()),
+        Err(e) => println!("❌ Failed to save recipe: {}", e),
+    }
+    wait_for_enter();
+}
+
+// Work out the water temperature that hits a recipe's desired dough temperature
+fn handle_dough_temperature(conn: &Connection) {
+    println!("\n🌡️ Dough Temperature Calculator");
+    let recipes = get_recipe_collection(conn).expect("Error fetching recipes");
+    for recipe in &recipes {
+        println!("{}: {}", recipe.id, recipe.name);
+    }
+    let recipe_id: i32 = prompt("Recipe ID (blank for none): ").parse().unwrap_or(0);
+    let recipe = recipes.iter().find(|r| r.id == recipe_id);
+
+    let unit_input = prompt("Units (F/C, default F): ");
+    let unit = if unit_input.is_empty() { Some(TempUnit::Fahrenheit) } else { TempUnit::parse(&unit_input) };
+    let unit = match unit {
+        Some(unit) => unit,
+        None => {
+            println!("Error--Invalid option\n Returning to Main Menu...");
+            return;
         }
-        // View Transactions
-        "2" => {
-            let transactions = read_transactions(conn).expect("Error fetching transactions");
-            println!("Transactions:");
+    };
+    let symbol = unit.symbol();
+
+    // Stored settings are in °C; fall back to 76°F (24.4°C) and a 26°F (14.4°C) mixer friction
+    let default_target = unit.in_unit(recipe.and_then(|r| r.target_ddt_c).unwrap_or(24.4));
+    let default_friction = unit.delta_in_unit(recipe.and_then(|r| r.friction_factor_c).unwrap_or(14.4));
+
+    println!("Method:\n3. 3-factor (room, flour, friction)\n4. 4-factor (adds preferment temperature)");
+    let factors = prompt("Choose a method (default 3): ");
+    if !factors.is_empty() && factors != "3" && factors != "4" {
+        println!("Error--Invalid option\n Returning to Main Menu...");
+        return;
+    }
+
+    let temperature = |label: &str, default: Option<f32>| -> Option<f32> {
+        let input = match default {
+            Some(default) => prompt(&format!("{} ({}, default {:.1}): ", label, symbol, default)),
+            None => prompt(&format!("{} ({}): ", label, symbol)),
+        };
+        match input.parse::<f32>() {
+            Ok(value) => Some(value),
+            Err(_) if input.is_empty() => default,
+            Err(_) => None,
+        }
+    };
+
+    let target = temperature("Desired dough temperature", Some(default_target));
+    let room = temperature("Room temperature", None);
+    let flour = temperature("Flour temperature", None);
+    let preferment = if factors == "4" { temperature("Preferment temperature", None) } else { Some(0.0) };
+    let friction = temperature("Mixer friction factor", Some(default_friction));
+
+    let ddt = match (target, room, flour, preferment, friction) {
+        (Some(target), Some(room), Some(flour), Some(preferment), Some(friction)) => DoughTemperature {
+            target,
+            room,
+            flour,
+            preferment: if factors == "4" { Some(preferment) } else { None },
+            friction,
+        },
+        _ => {
+            println!("❌ Every temperature must be a number.");
+            wait_for_enter();
+            return;
+        }
+    };
+
+    let water = ddt.water_temperature();
+    println!(
+        "\n💧 Water temperature: {:.1}{} ({}-factor: {:.1} x {} - known temperatures)",
+        water, symbol, ddt.factors(), ddt.target, ddt.factors()
+    );
+    if water <= unit.freezing() {
+        println!("⚠️ That is at or below freezing; use ice water and chill the flour or preferment.");
+    } else if water >= unit.in_unit(50.0) {
+        println!("⚠️ Water that hot can damage yeast; warm the room or ingredients instead.");
+    }
+
+    if let Some(recipe) = recipe {
+        if prompt(&format!("\nSave target {:.1}{} and friction {:.1}{} to {}? (y/n): ",
+            ddt.target, symbol, ddt.friction, symbol, recipe.name)).to_lowercase() == "y" {
+            match update_recipe_dough_temp(conn, recipe.id, unit.to_celsius(ddt.target), unit.delta_to_celsius(ddt.friction)) {
+                Ok(()) => println!("✅ Saved dough temperature settings for {}.", recipe.name),
+                Err(e) => println!("❌ Failed to save settings: {}", e),
+            }
+        }
+    }
+    wait_for_enter();
+}
+
+// Convert a recipe's yeast line to another yeast type or a starter
+fn handle_leavening_substitution(conn: &Connection) {
+    println!("\n🔁 Substitute Yeast / Leavening");
+    let recipes = get_recipe_collection(conn).expect("Error fetching recipes");
+    for recipe in &recipes {
+        println!("{}: {}", recipe.id, recipe.name);
+    }
+    let recipe_id: i32 = prompt("Enter recipe ID: ").parse().unwrap_or(0);
+    let recipe = match recipes.iter().find(|r| r.id == recipe_id) {
+        Some(recipe) => recipe,
+        None => {
+            println!("⚠️ Recipe not found.");
+            wait_for_enter();
+            return;
+        }
+    };
+
+    let lines = get_formula_lines_for_recipe(conn, recipe_id).expect("Failed to load recipe ingredients");
+    let leavening: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, i))| matches!(i.role, Some(IngredientRole::Leavener) | Some(IngredientRole::Preferment)))
+        .map(|(index, _)| index)
+        .collect();
+    if leavening.is_empty() {
+        println!("⚠️ No ingredient in {} is tagged as a leavener or preferment.", recipe.name);
+        wait_for_enter();
+        return;
+    }
+
+    println!("\nLeavening lines:");
+    for (n, index) in leavening.iter().enumerate() {
+        let ingredient = &lines[*index].1;
+        println!("{}. {} {} {}", n + 1, ingredient.quantity, ingredient.unit, ingredient.name);
+    }
+    let choice: usize = prompt("Line to replace (default 1): ").parse().unwrap_or(1);
+    let index = match leavening.get(choice.wrapping_sub(1)) {
+        Some(index) => *index,
+        None => {
+            println!("Error--Invalid option\n Returning to Main Menu...");
+            return;
+        }
+    };
+    let source = &lines[index].1;
+    if let Some(p) = source.preferment.filter(|p| !p.kind.seeded_with_starter()) {
+        println!("❌ Cannot substitute: {}.", BakersMathError::YeastedPreferment(source.name.clone(), p.kind));
+        wait_for_enter();
+        return;
+    }
+
+    let types: Vec<&str> = YeastType::ALL.iter().map(|t| t.as_str()).collect();
+    let guess = if source.role == Some(IngredientRole::Preferment) { YeastType::Starter } else { YeastType::Instant };
+    let from_input = prompt(&format!("Current type ({}, default {}): ", types.join("/"), guess.as_str()));
+    let from = if from_input.is_empty() { Some(guess) } else { YeastType::parse(&from_input) };
+    let to = YeastType::parse(&prompt(&format!("Convert to ({}): ", types.join("/"))));
+    let (from, to) = match (from, to) {
+        (Some(from), Some(to)) if from != to => (from, to),
+        _ => {
+            println!("❌ Choose two different yeast types.");
+            wait_for_enter();
+            return;
+        }
+    };
+
+    let mut swap = StarterSwap::DEFAULT;
+    if from == YeastType::Starter || to == YeastType::Starter {
+        let default_hydration = source.preferment.map(|p| p.hydration).unwrap_or(swap.hydration);
+        swap.hydration = prompt(&format!("Starter hydration % (default {:.0}): ", default_hydration))
+            .parse()
+            .unwrap_or(default_hydration);
+        if to == YeastType::Starter {
+            swap.starter_percent = prompt(&format!("Starter as % of flour (default {:.0}): ", swap.starter_percent))
+                .parse()
+                .unwrap_or(swap.starter_percent);
+        } else {
+            swap.instant_percent = prompt(&format!("Instant yeast equivalent as % of flour (default {:.1}): ", swap.instant_percent))
+                .parse()
+                .unwrap_or(swap.instant_percent);
+        }
+    }
+
+    let formula: Vec<FormulaIngredient> = lines.iter().map(|(_, i)| i.clone()).collect();
+    let result = match bakers_math::substitute_leavening(&formula, index, from, to, swap) {
+        Ok(result) => result,
+        Err(e) => {
+            println!("❌ Cannot substitute: {}.", e);
+            wait_for_enter();
+            return;
+        }
+    };
+
+    let batches: f32 = prompt("Batches to show (default 1): ").parse().unwrap_or(1.0);
+    println!("\n{} with {} instead of {} ({} batch(es))", recipe.name, to.as_str(), from.as_str(), batches);
+    println!("{:<36} {:>12} {:>12}", "Ingredient", "Before", "After");
+    for (line, (_, ingredient)) in result.lines.iter().zip(&lines) {
+        match (line.before_grams, line.after_grams) {
+            (Some(before), Some(after)) => println!(
+                "{:<36} {:>10.1} g {:>10.1} g", line.name, before * batches, after * batches
+            ),
+            _ => println!(
+                "{:<36} {:>12} {:>12}", line.name,
+                format!("{} {}", ingredient.quantity * batches, ingredient.unit), "unchanged"
+            ),
+        }
+    }
+    println!("{:<36} {:>12} {:>10.1} g", format!("+ {} ({})", to.item_name(), to.as_str()), "", result.new_leavener_grams * batches);
+    for note in &result.notes {
+        println!("💡 {}", note);
+    }
+
+    if prompt("\nSave as a variant recipe? (y/n): ").to_lowercase() != "y" {
+        wait_for_enter();
+        return;
+    }
+
+    let default_name = format!("{} ({})", recipe.name, to.as_str());
+    let mut name = prompt(&format!("Variant name (default {}): ", default_name));
+    if name.is_empty() {
+        name = default_name;
+    }
+    if let Ok(Some(_)) = find_recipe_id_by_name(conn, &name) {
+        println!("❌ A recipe named '{}' already exists.", name);
+        wait_for_enter();
+        return;
+    }
+
+    // Pick the inventory item for the new leavening, or add one when the variant is saved
+    let inventory = get_all_inventory(conn).expect("Error fetching inventory");
+    let candidates: Vec<&InventoryItem> = inventory
+        .iter()
+        .filter(|item| matches!(item.role.as_deref(), Some("leavener") | Some("preferment")))
+        .filter(|item| bakers_math::to_grams(1.0, &item.unit).is_some())
+        .collect();
+    println!("\nLeavening items stocked by weight:");
+    for item in &candidates {
+        println!("{} - {} ({})", item.id, item.name, item.unit);
+    }
+    let default_item = candidates.iter().find(|item| item.name.eq_ignore_ascii_case(to.item_name()));
+    let item_input = prompt(&format!(
+        "Item ID for {} (blank for {}): ",
+        to.as_str(),
+        default_item.map(|i| i.name.as_str()).unwrap_or("a new item")
+    ));
+    let leavener = match (item_input.parse::<i32>(), default_item) {
+        (Ok(id), _) => candidates.iter().find(|item| item.id == id).map(|item| Some(*item)),
+        (Err(_), Some(item)) if item_input.is_empty() => Some(Some(*item)),
+        (Err(_), None) if item_input.is_empty() => Some(None),
+        _ => None,
+    };
+    let leavener = match leavener {
+        Some(leavener) => leavener,
+        None => {
+            println!("❌ Item not found!");
+            wait_for_enter();
+            return;
+        }
+    };
+    let new_item = leavener.is_none().then(|| {
+        let role = if to == YeastType::Starter { IngredientRole::Preferment } else { IngredientRole::Leavener };
+        InventoryItem {
+            id: 0,
+            name: to.item_name().to_string(),
+            unit: "grams".to_string(),
+            quantity: 0.0,
+            cost_per_unit: 0.0,
+            category: Some("Leaveners".to_string()),
+            role: Some(role.as_str().to_string()),
+        }
+    });
+    let leavener_unit = leavener.map_or("grams", |item| item.unit.as_str());
+
+    // Quantities go back into each item's own unit; unweighed lines are copied as they were
+    let mut variant_lines: Vec<(i32, f32)> = Vec::new();
+    for (n, (line, (ingredient_id, ingredient))) in result.lines.iter().zip(&lines).enumerate() {
+        if n == index {
+            continue;
+        }
+        let quantity = line
+            .after_grams
+            .and_then(|grams| bakers_math::from_grams(grams, &ingredient.unit))
+            .unwrap_or(ingredient.quantity);
+        variant_lines.push((*ingredient_id, quantity));
+    }
+    let leavener_quantity = bakers_math::from_grams(result.new_leavener_grams, leavener_unit).unwrap_or(0.0);
+    if let Some(leavener) = leavener {
+        match variant_lines.iter_mut().find(|(id, _)| *id == leavener.id) {
+            Some((_, quantity)) => *quantity += leavener_quantity,
+            None => variant_lines.push((leavener.id, leavener_quantity)),
+        }
+    }
+
+    let variant = RecipeCollection {
+        id: 0,
+        name,
+        instructions: recipe.instructions.clone(),
+        yield_quantity: recipe.yield_quantity,
+        category: recipe.category.clone(),
+        prep_time: recipe.prep_time.clone(),
+        bake_time: recipe.bake_time.clone(),
+        total_time: recipe.total_time.clone(),
+        msrp_per_unit: recipe.msrp_per_unit,
+        target_ddt_c: recipe.target_ddt_c,
+        friction_factor_c: recipe.friction_factor_c,
+        pan_id: recipe.pan_id,
+    };
+    // The variant keeps the original's method, including any edited steps
+    let saved = add_recipe_variant(
+        conn,
+        &variant,
+        &variant_lines,
+        recipe.id,
+        new_item.as_ref().map(|item| (item, leavener_quantity)),
+    );
+    match saved {
+        Ok(id) => {
+            if let Some(item) = &new_item {
+                println!("✅ Added {} to inventory (0 grams on hand).", item.name);
+            }
+            println!("✅ Saved variant recipe {} '{}'.", id, variant.name);
+        }
+        Err(e) => println!("❌ Failed to save variant: {}", e),
+    }
+    wait_for_enter();
+}
+
+// Optional measurement prompt; blank gives None, anything else must be a number above zero
+fn prompt_optional_number(label: &str) -> Option<f32> {
+    loop {
+        let input = prompt(label);
+        if input.is_empty() {
+            return None;
+        }
+        match input.parse::<f32>() {
+            Ok(value) if value > 0.0 && value.is_finite() => return Some(value),
+            _ => println!("❌ Enter a number above zero, or leave it blank."),
+        }
+    }
+}
+
+// Prompt for a percentage from 0 to `max`, re-asking until it's in range; blank keeps the default
+fn prompt_percent(label: &str, default: f32, max: f32) -> f32 {
+    loop {
+        let input = prompt(label);
+        if input.is_empty() {
+            return default;
+        }
+        match input.parse::<f32>() {
+            Ok(value) if (0.0..=max).contains(&value) => return value,
+            _ => println!("❌ Enter a percentage from 0 to {:.0}.", max),
+        }
+    }
+}
+
+// Pick a pan from the library by ID, offering a default
+fn prompt_pan<'a>(pans: &'a [Pan], label: &str, default: Option<i32>) -> Option<&'a Pan> {
+    let input = match default.and_then(|id| pans.iter().find(|p| p.id == id)) {
+        Some(pan) => prompt(&format!("{} (default {} - {}): ", label, pan.id, pan.name)),
+        None => prompt(&format!("{}: ", label)),
+    };
+    let pan_id = if input.is_empty() { default } else { input.parse().ok() };
+    pan_id.and_then(|id| pans.iter().find(|p| p.id == id))
+}
+
+// Pan library, recipe pans and scaling between pans
+fn handle_pan_menu(conn: &Connection) {
+    println!("\n🥧 Pans & Pan Scaling");
+    println!("1. View Pan Library");
+    println!("2. Add Pan");
+    println!("3. Set Recipe Pan");
+    println!("4. Scale Recipe to Another Pan");
+
+    let pans = get_pans(conn).expect("Error fetching pans");
+    match prompt("Choose an option: ").as_str() {
+        // View Pan Library
+        "1" => {
+            println!("\n🥧 Pans:");
+            for pan in &pans {
+                let area = pan.area().map_or("-".to_string(), |a| format!("{:.1} sq in", a));
+                println!("{} - {} [area {}]", pan.id, pan.describe(), area);
+            }
+            wait_for_enter();
+        }
+        // Add Pan
+        "2" => {
+            let name = prompt("Pan name: ");
+            let shape = prompt(&format!("Shape ({}): ", PAN_SHAPES.join("/"))).to_lowercase();
+            if name.is_empty() || !PAN_SHAPES.contains(&shape.as_str()) {
+                println!("❌ A name and one of the listed shapes are required.");
+                wait_for_enter();
+                return;
+            }
+
+            let mut pan = Pan {
+                id: 0,
+                name,
+                shape,
+                length_in: None,
+                width_in: None,
+                diameter_in: None,
+                tube_diameter_in: None,
+                depth_in: None,
+                volume_cups: None,
+            };
+            match pan.shape.as_str() {
+                "round" => pan.diameter_in = prompt_optional_number("Diameter (in): "),
+                "square" => {
+                    pan.length_in = prompt_optional_number("Side (in): ");
+                    pan.width_in = pan.length_in;
+                }
+                "rectangular" | "loaf" => {
+                    pan.length_in = prompt_optional_number("Length (in): ");
+                    pan.width_in = prompt_optional_number("Width (in): ");
+                }
+                _ => {
+                    pan.diameter_in = prompt_optional_number("Outer diameter (in, blank if unknown): ");
+                    pan.tube_diameter_in = prompt_optional_number("Tube diameter (in, blank if unknown): ");
+                }
+            }
+            pan.depth_in = prompt_optional_number("Depth (in, blank if unknown): ");
+            pan.volume_cups = prompt_optional_number("Volume (cups, blank to work it out): ");
+
+            if pan.area().is_none() && pan.volume_cups().is_none() {
+                println!("❌ Enter enough dimensions or a volume to size the pan.");
+            } else {
+                match add_pan(conn, &pan) {
+                    Ok(id) => println!("✅ Added pan {}: {}", id, pan.describe()),
+                    Err(e) => println!("❌ Failed to add pan: {}", e),
+                }
+            }
+            wait_for_enter();
+        }
+        // Set Recipe Pan
+        "3" => {
+            let recipes = get_recipe_collection(conn).expect("Error fetching recipes");
+            for recipe in &recipes {
+                let pan = recipe.pan_id.and_then(|id| pans.iter().find(|p| p.id == id));
+                println!("{}: {} [{}]", recipe.id, recipe.name, pan.map_or("no pan", |p| p.name.as_str()));
+            }
+            let recipe_id: i32 = prompt("Enter recipe ID: ").parse().unwrap_or(0);
+            if !recipes.iter().any(|r| r.id == recipe_id) {
+                println!("⚠️ Recipe not found.");
+                wait_for_enter();
+                return;
+            }
+
+            for pan in &pans {
+                println!("{} - {}", pan.id, pan.describe());
+            }
+            let input = prompt("Pan ID (blank to clear): ");
+            let pan_id = if input.is_empty() {
+                None
+            } else {
+                match pans.iter().find(|p| Some(p.id) == input.parse().ok()) {
+                    Some(pan) => Some(pan.id),
+                    None => {
+                        println!("❌ Pan not found.");
+                        wait_for_enter();
+                        return;
+                    }
+                }
+            };
+            match set_recipe_pan(conn, recipe_id, pan_id) {
+                Ok(()) => println!("✅ Recipe pan updated."),
+                Err(e) => println!("❌ Failed to update recipe pan: {}", e),
+            }
+            wait_for_enter();
+        }
+        // Scale Recipe to Another Pan
+        "4" => {
+            let recipes = get_recipe_collection(conn).expect("Error fetching recipes");
+            for recipe in &recipes {
+                println!("{}: {}", recipe.id, recipe.name);
+            }
+            let recipe_id: i32 = prompt("Enter recipe ID: ").parse().unwrap_or(0);
+            let recipe = match recipes.iter().find(|r| r.id == recipe_id) {
+                Some(recipe) => recipe,
+                None => {
+                    println!("⚠️ Recipe not found.");
+                    wait_for_enter();
+                    return;
+                }
+            };
+
+            println!();
+            for pan in &pans {
+                println!("{} - {}", pan.id, pan.describe());
+            }
+            let from = prompt_pan(&pans, "Pan the recipe is written for", recipe.pan_id);
+            let to = prompt_pan(&pans, "Pan to bake in", None);
+            let (from, to) = match (from, to) {
+                (Some(from), Some(to)) => (from, to),
+                _ => {
+                    println!("❌ Pan not found.");
+                    wait_for_enter();
+                    return;
+                }
+            };
+            let by_volume = prompt("Scale by 1. area (same batter depth) or 2. volume (default 2): ") != "1";
+
+            let scaling = match pan_scaling(from, to, by_volume) {
+                Some(scaling) => scaling,
+                None => {
+                    println!("❌ Not enough measurements on these pans to scale by {}.", if by_volume { "volume" } else { "area" });
+                    wait_for_enter();
+                    return;
+                }
+            };
+
+            println!(
+                "\n{}: {} ➜ {} by {} (x{:.2})",
+                recipe.name, from.name, to.name, if scaling.by_volume { "volume" } else { "area" }, scaling.factor
+            );
+            let ingredients = get_ingredients_for_recipe(conn, recipe.id).expect("Failed to load ingredients");
+            for (name, quantity, unit) in &ingredients {
+                println!("- {:.3} {} {} (was {})", quantity * scaling.factor, unit, name, quantity);
+            }
+            println!("Yield: about {:.1} units (was {})", recipe.yield_quantity as f32 * scaling.factor, recipe.yield_quantity);
+            if let Some(ratio) = scaling.depth_ratio {
+                println!("Batter depth: x{:.2} of the original", ratio);
+            }
+            if let Some(bake_time) = &recipe.bake_time {
+                println!("Original bake time: {}", bake_time);
+            }
+            for note in &scaling.notes {
+                println!("💡 {}", note);
+            }
+            wait_for_enter();
+        }
+        &_ => {
+            println!("Error--Invalid option\n Returning to Main Menu...");
+        }
+    }
+}
+
+// Print a recipe's steps with durations and computed totals
+fn print_recipe_steps(steps: &[RecipeStep]) {
+    if steps.is_empty() {
+        println!("⚠️ This recipe has no steps yet.");
+        return;
+    }
+    for step in steps {
+        let temperature = step.temperature_f.map_or(String::new(), |t| format!(" @ {:.0}°F", t));
+        println!(
+            "{}. [{}] {} ({}{})",
+            step.position, step.step_type, step.description, step.duration_label(), temperature
+        );
+        if let Some(notes) = &step.notes {
+            println!("     📝 {}", notes);
+        }
+    }
+
+    let totals = step_totals(steps);
+    println!("\nTotal time: {}", totals.label());
+    for (step_type, min, max) in &totals.by_type {
+        let range = if *max == 0 {
+            "untimed".to_string()
+        } else if max > min {
+            format!("{}-{}", format_minutes(*min), format_minutes(*max))
+        } else {
+            format_minutes(*min)
+        };
+        println!("  {:<13} {}", step_type, range);
+    }
+}
+
+// Prompt for a step's fields, keeping the current values on blank input
+fn prompt_recipe_step(current: &RecipeStep) -> Option<RecipeStep> {
+    let mut step = current.clone();
+
+    let step_type = prompt(&format!("Step type ({}) [{}]: ", STEP_TYPES.join("/"), current.step_type)).to_lowercase();
+    if !step_type.is_empty() {
+        if !STEP_TYPES.contains(&step_type.as_str()) {
+            println!("❌ Unknown step type.");
+            return None;
+        }
+        step.step_type = step_type;
+    }
+
+    let description = prompt(&format!("Description [{}]: ", current.description));
+    if !description.is_empty() {
+        step.description = description;
+    }
+    if step.description.is_empty() {
+        println!("❌ A description is required.");
+        return None;
+    }
+
+    let duration = prompt(&format!(
+        "Duration, e.g. 45 min or 1-2 hours ('-' to clear) [{}]: ", current.duration_label()
+    ));
+    if duration == "-" {
+        step.min_minutes = None;
+        step.max_minutes = None;
+    } else if !duration.is_empty() {
+        match parse_duration_range(&duration) {
+            Some((min, max)) => {
+                step.min_minutes = Some(min);
+                step.max_minutes = Some(max);
+            }
+            None => {
+                println!("❌ Could not read that duration.");
+                return None;
+            }
+        }
+    }
+
+    let current_temperature = current.temperature_f.map_or("none".to_string(), |t| format!("{:.0}°F", t));
+    let temperature = prompt(&format!("Temperature, e.g. 350F or 180C ('-' to clear) [{}]: ", current_temperature));
+    if temperature == "-" {
+        step.temperature_f = None;
+    } else if !temperature.is_empty() {
+        match parse_temperature_f(&temperature).or_else(|| temperature.parse().ok()) {
+            Some(t) => step.temperature_f = Some(t),
+            None => {
+                println!("❌ Could not read that temperature.");
+                return None;
+            }
+        }
+    }
+
+    let notes = prompt(&format!("Notes ('-' to clear) [{}]: ", current.notes.as_deref().unwrap_or("none")));
+    if notes == "-" {
+        step.notes = None;
+    } else if !notes.is_empty() {
+        step.notes = Some(notes);
+    }
+
+    Some(step)
+}
+
+// View and edit a recipe's structured steps
+fn handle_recipe_steps(conn: &Connection) {
+    let recipes = get_recipe_collection(conn).expect("Error fetching recipes");
+    for recipe in &recipes {
+        println!("{}: {}", recipe.id, recipe.name);
+    }
+    let recipe_id: i32 = prompt("Enter recipe ID: ").parse().unwrap_or(0);
+    let recipe = match recipes.iter().find(|r| r.id == recipe_id) {
+        Some(recipe) => recipe,
+        None => {
+            println!("⚠️ Recipe not found.");
+            wait_for_enter();
+            return;
+        }
+    };
+
+    let steps = get_recipe_steps(conn, recipe.id).expect("Failed to load recipe steps");
+    println!("\n🧾 {} steps:", recipe.name);
+    print_recipe_steps(&steps);
+
+    println!("\n1. Add Step");
+    println!("2. Edit Step");
+    println!("3. Delete Step");
+    println!("4. Rebuild Steps from Instructions");
+    println!("(blank to go back)");
+
+    match prompt("Choose an option: ").as_str() {
+        "" => {}
+        // Add Step
+        "1" => {
+            let position: i32 = prompt(&format!("Insert at position (default {}): ", steps.len() + 1))
+                .parse()
+                .unwrap_or(steps.len() as i32 + 1)
+                .clamp(1, steps.len() as i32 + 1);
+            let blank = RecipeStep {
+                id: 0,
+                recipe_id: recipe.id,
+                position,
+                step_type: "other".to_string(),
+                description: String::new(),
+                min_minutes: None,
+                max_minutes: None,
+                temperature_f: None,
+                notes: None,
+            };
+            if let Some(step) = prompt_recipe_step(&blank) {
+                match add_recipe_step(conn, recipe.id, &step) {
+                    Ok(_) => println!("✅ Step added."),
+                    Err(e) => println!("❌ Failed to add step: {}", e),
+                }
+            }
+            wait_for_enter();
+        }
+        // Edit Step
+        "2" => {
+            let position: i32 = prompt("Step number: ").parse().unwrap_or(0);
+            match steps.iter().find(|s| s.position == position) {
+                Some(current) => {
+                    if let Some(step) = prompt_recipe_step(current) {
+                        match update_recipe_step(conn, &step) {
+                            Ok(()) => println!("✅ Step updated."),
+                            Err(e) => println!("❌ Failed to update step: {}", e),
+                        }
+                    }
+                }
+                None => println!("⚠️ Step not found."),
+            }
+            wait_for_enter();
+        }
+        // Delete Step
+        "3" => {
+            let position: i32 = prompt("Step number: ").parse().unwrap_or(0);
+            match steps.iter().find(|s| s.position == position) {
+                Some(step) => match delete_recipe_step(conn, step) {
+                    Ok(()) => println!("✅ Step deleted."),
+                    Err(e) => println!("❌ Failed to delete step: {}", e),
+                },
+                None => println!("⚠️ Step not found."),
+            }
+            wait_for_enter();
+        }
+        // Rebuild Steps from Instructions
+        "4" => {
+            let parsed = recipe_steps_from_instructions(recipe);
+            println!("\nParsed from the instructions text:");
+            print_recipe_steps(&parsed);
+            if prompt("\nReplace the current steps with these? (y/N): ").eq_ignore_ascii_case("y") {
+                match replace_recipe_steps(conn, recipe.id, &parsed) {
+                    Ok(()) => println!("✅ Steps rebuilt."),
+                    Err(e) => println!("❌ Failed to rebuild steps: {}", e),
+                }
+            }
+            wait_for_enter();
+        }
+        &_ => {
+            println!("Error--Invalid option\n Returning to Main Menu...");
+        }
+    }
+}
+
+// Read "HH:MM" on the given day, or a full "YYYY-MM-DD HH:MM"
+fn parse_ready_by(input: &str, day: NaiveDate) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(input, "%Y-%m-%d %H:%M")
+        .ok()
+        .or_else(|| NaiveTime::parse_from_str(input, "%H:%M").ok().map(|time| day.and_time(time)))
+}
+
+// Print a schedule as a timeline, then start times, conflicts and warnings
+fn print_schedule(schedule: &Schedule, items: &[ScheduleItem]) {
+    println!("\n🗓 Production timeline:");
+    let mut day = None;
+    for step in &schedule.steps {
+        if day != Some(step.start.date()) {
+            day = Some(step.start.date());
+            println!("\n{}", step.start.format("%a %Y-%m-%d"));
+        }
+        let loads = if step.loads > 1 && step.end > step.start { format!(" ({} loads)", step.loads) } else { String::new() };
+        let resource = step.resource.as_ref().map_or(String::new(), |r| format!(" [{}]", r));
+        println!(
+            "  {}-{}  {:<24} {:<13} {}{}{}",
+            step.start.format("%H:%M"), step.end.format("%H:%M"), step.label, step.step_type, step.description,
+            loads, resource
+        );
+    }
+
+    println!("\n⏰ Start times:");
+    for (item, start) in items.iter().zip(&schedule.starts) {
+        println!(
+            "  {:<24} start {}  ready {}",
+            item.label(), start.format("%a %H:%M"), item.ready_by.format("%a %H:%M")
+        );
+    }
+
+    if schedule.conflicts.is_empty() {
+        println!("\n✅ No equipment conflicts.");
+    }
+    for conflict in &schedule.conflicts {
+        println!(
+            "❌ {} overbooked {}-{}: {} batches for {} slot{} ({})",
+            conflict.resource, conflict.start.format("%a %H:%M"), conflict.end.format("%H:%M"), conflict.peak,
+            conflict.capacity, if conflict.capacity == 1 { "" } else { "s" }, conflict.labels.join(", ")
+        );
+    }
+    if !schedule.conflicts.is_empty() {
+        println!("💡 Move a ready-by time earlier or add capacity under Set Equipment Capacity.");
+    }
+    for warning in &schedule.warnings {
+        println!("⚠️ {}", warning);
+    }
+}
+
+// Backward scheduling from ready-by times, and the equipment it checks
+fn handle_production_schedule(conn: &Connection) {
+    println!("\n🗓 Production Schedule");
+    println!("1. Build Schedule");
+    println!("2. View Equipment");
+    println!("3. Set Equipment Capacity");
+
+    let resources = get_resources(conn).expect("Error fetching equipment");
+    match prompt("Choose an option: ").as_str() {
+        // Build Schedule
+        "1" => {
+            let tomorrow = Local::now().date_naive() + Duration::days(1);
+            let input = prompt(&format!("Ready on (YYYY-MM-DD, default {}): ", tomorrow));
+            let day = if input.is_empty() {
+                tomorrow
+            } else {
+                match NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
+                    Ok(day) => day,
+                    Err(_) => {
+                        println!("❌ Invalid date.");
+                        wait_for_enter();
+                        return;
+                    }
+                }
+            };
+
+            let recipes = get_recipe_collection(conn).expect("Error fetching recipes");
+            for recipe in &recipes {
+                println!("{}: {}", recipe.id, recipe.name);
+            }
+            let mut items = Vec::new();
+            loop {
+                let input = prompt("Recipe ID (blank to finish): ");
+                if input.is_empty() {
+                    break;
+                }
+                let recipe = match recipes.iter().find(|r| Some(r.id) == input.parse().ok()) {
+                    Some(recipe) => recipe,
+                    None => {
+                        println!("⚠️ Recipe not found.");
+                        continue;
+                    }
+                };
+                let batches: f32 = match prompt("Batches (default 1): ").as_str() {
+                    "" => 1.0,
+                    text => match text.parse() {
+                        Ok(batches) if batches > 0.0 => batches,
+                        _ => {
+                            println!("❌ Batches must be a positive number.");
+                            continue;
+                        }
+                    },
+                };
+                let ready_by = match parse_ready_by(&prompt("Ready by (HH:MM or YYYY-MM-DD HH:MM): "), day) {
+                    Some(ready_by) => ready_by,
+                    None => {
+                        println!("❌ Invalid time.");
+                        continue;
+                    }
+                };
+                items.push(ScheduleItem {
+                    recipe_name: recipe.name.clone(),
+                    batches,
+                    ready_by,
+                    steps: get_recipe_steps(conn, recipe.id).expect("Failed to load recipe steps"),
+                });
+            }
+
+            if items.is_empty() {
+                println!("⚠️ Nothing to schedule.");
+            } else {
+                print_schedule(&build_schedule(&items, &resources), &items);
+            }
+            wait_for_enter();
+        }
+        // View Equipment
+        "2" => {
+            println!("\n🔧 Equipment:");
+            for resource in &resources {
+                println!(
+                    "{} - {}: {} steps, holds {} batch{}",
+                    resource.id, resource.name, resource.step_type, resource.capacity,
+                    if resource.capacity == 1 { "" } else { "es" }
+                );
+            }
+            wait_for_enter();
+        }
+        // Set Equipment Capacity
+        "3" => {
+            for resource in &resources {
+                println!("{} - {} (holds {})", resource.id, resource.name, resource.capacity);
+            }
+            let resource_id: i32 = prompt("Equipment ID: ").parse().unwrap_or(0);
+            if !resources.iter().any(|r| r.id == resource_id) {
+                println!("⚠️ Equipment not found.");
+                wait_for_enter();
+                return;
+            }
+            match prompt("Batches it holds at once: ").parse::<i32>() {
+                Ok(capacity) if capacity > 0 => match update_resource_capacity(conn, resource_id, capacity) {
+                    Ok(()) => println!("✅ Capacity updated."),
+                    Err(e) => println!("❌ Failed to update capacity: {}", e),
+                },
+                _ => println!("❌ Capacity must be a whole number above zero."),
+            }
+            wait_for_enter();
+        }
+        &_ => {
+            println!("Error--Invalid option\n Returning to Main Menu...");
+        }
+    }
+}
+
+// Explode a plan's recipes through their ingredients into one pull list
+fn load_pull_list(conn: &Connection, items: &[ProductionPlanItem]) -> (Vec<PullListLine>, Vec<String>) {
+    let recipe_ingredients = get_all_recipe_ingredients(conn).expect("Failed to load ingredients");
+    let recipes: Vec<PlannedRecipe> = items
+        .iter()
+        .map(|item| {
+            let ingredients = recipe_ingredients.iter().filter(|line| line.recipe_id == item.recipe_id).cloned().collect();
+            (item.recipe_name.clone(), item.batches, ingredients)
+        })
+        .collect();
+    let inventory = get_all_inventory(conn).expect("Error fetching inventory");
+    build_pull_list(&recipes, &inventory)
+}
+
+// Print a pull list with on-hand stock and shortfalls; returns how many lines are short
+fn print_pull_list(lines: &[PullListLine], warnings: &[String]) -> usize {
+    println!("\n📋 Pull list:");
+    let mut short = 0;
+    for line in lines {
+        let on_hand = line.on_hand.map_or("not in inventory".to_string(), |q| format!("{:.2} on hand", q));
+        let status = if line.shortfall() > 0.0 {
+            short += 1;
+            format!("❌ short {}", friendly_quantity(line.shortfall(), &line.unit))
+        } else {
+            "✅".to_string()
+        };
+        println!(
+            "- {:<32} {:>10.2} {:<6} ({})  {}  {}",
+            line.name, line.required, line.unit, friendly_quantity(line.required, &line.unit), on_hand, status
+        );
+    }
+    if lines.is_empty() {
+        println!("⚠️ These recipes have no ingredients.");
+    }
+    for warning in warnings {
+        println!("⚠️ {}", warning);
+    }
+    short
+}
+
+// Pick a plan by ID from the list
+fn prompt_production_plan(conn: &Connection) -> Option<i32> {
+    let plans = get_production_plans(conn).expect("Error fetching production plans");
+    if plans.is_empty() {
+        println!("⚠️ No production plans yet.");
+        return None;
+    }
+    for plan in &plans {
+        println!("{} - {} [{}]", plan.id, plan.plan_date, plan.status);
+    }
+    let plan_id: i32 = prompt("Plan ID: ").parse().unwrap_or(0);
+    if plans.iter().any(|p| p.id == plan_id) {
+        Some(plan_id)
+    } else {
+        println!("⚠️ Plan not found.");
+        None
+    }
+}
+
+// Daily production plans, their consolidated pull lists, and posting them as production runs
+fn handle_production_plans(conn: &Connection) {
+    println!("\n📋 Production Plans");
+    println!("1. New Plan");
+    println!("2. View Plans");
+    println!("3. View Plan & Pull List");
+    println!("4. Post Plan as Production Runs");
+    println!("5. Delete Draft Plan");
+
+    match prompt("Choose an option: ").as_str() {
+        // New Plan
+        "1" => {
+            let today = Local::now().format("%Y-%m-%d").to_string();
+            let mut plan_date = prompt(&format!("Plan date (YYYY-MM-DD, default {}): ", today));
+            if plan_date.is_empty() {
+                plan_date = today;
+            } else if NaiveDate::parse_from_str(&plan_date, "%Y-%m-%d").is_err() {
+                println!("❌ Invalid date.");
+                wait_for_enter();
+                return;
+            }
+
+            let recipes = get_recipe_collection(conn).expect("Error fetching recipes");
+            for recipe in &recipes {
+                println!("{}: {} (yield: {})", recipe.id, recipe.name, recipe.yield_quantity);
+            }
+            let mut items: Vec<(i32, f32)> = Vec::new();
+            loop {
+                let input = prompt("Recipe ID (blank to finish): ");
+                if input.is_empty() {
+                    break;
+                }
+                let recipe = match recipes.iter().find(|r| Some(r.id) == input.parse().ok()) {
+                    Some(recipe) => recipe,
+                    None => {
+                        println!("⚠️ Recipe not found.");
+                        continue;
+                    }
+                };
+                match prompt("Batch multiplier (default 1): ").as_str() {
+                    "" => items.push((recipe.id, 1.0)),
+                    text => match text.parse::<f32>() {
+                        Ok(batches) if batches > 0.0 => items.push((recipe.id, batches)),
+                        _ => println!("❌ Batches must be a positive number."),
+                    },
+                }
+            }
+            if items.is_empty() {
+                println!("⚠️ Nothing to plan.");
+                wait_for_enter();
+                return;
+            }
+            let notes = prompt("Notes (optional): ");
+            let notes = if notes.is_empty() { None } else { Some(notes.as_str()) };
+
+            match add_production_plan(conn, &plan_date, notes, &items) {
+                Ok(plan_id) => {
+                    println!("✅ Saved plan {} for {}.", plan_id, plan_date);
+                    let plan_items = get_production_plan_items(conn, plan_id).expect("Failed to load plan");
+                    let (lines, warnings) = load_pull_list(conn, &plan_items);
+                    print_pull_list(&lines, &warnings);
+                }
+                Err(e) => println!("❌ Failed to save plan: {}", e),
+            }
+            wait_for_enter();
+        }
+        // View Plans
+        "2" => {
+            let plans = get_production_plans(conn).expect("Error fetching production plans");
+            println!("\n📋 Production plans:");
+            for plan in &plans {
+                let items = get_production_plan_items(conn, plan.id).expect("Failed to load plan");
+                let posted = plan.posted_date.as_ref().map_or(String::new(), |d| format!(" on {}", d));
+                println!("{} - {} [{}{}]", plan.id, plan.plan_date, plan.status, posted);
+                for item in &items {
+                    println!("    {} x{}", item.recipe_name, item.batches);
+                }
+                if let Some(notes) = &plan.notes {
+                    println!("    📝 {}", notes);
+                }
+            }
+            if plans.is_empty() {
+                println!("⚠️ No production plans yet.");
+            }
+            wait_for_enter();
+        }
+        // View Plan & Pull List
+        "3" => {
+            let Some(plan_id) = prompt_production_plan(conn) else {
+                wait_for_enter();
+                return;
+            };
+            let items = get_production_plan_items(conn, plan_id).expect("Failed to load plan");
+            println!();
+            for item in &items {
+                println!("🥐 {} x{}", item.recipe_name, item.batches);
+            }
+            let (lines, warnings) = load_pull_list(conn, &items);
+            print_pull_list(&lines, &warnings);
+
+            if prompt("\nExport pull list to CSV? (y/N): ").eq_ignore_ascii_case("y") {
+                let path = prompt_export_path(&format!("pull-list-plan-{}.csv", plan_id));
+                match write_pull_list_csv(&path, &lines) {
+                    Ok(()) => println!("✅ Pull list written to {}", path.display()),
+                    Err(e) => println!("❌ Failed to write CSV: {}", e),
+                }
+            }
+            wait_for_enter();
+        }
+        // Post Plan as Production Runs
+        "4" => {
+            let Some(plan_id) = prompt_production_plan(conn) else {
+                wait_for_enter();
+                return;
+            };
+            let plan = get_production_plan(conn, plan_id).ok().flatten();
+            let Some(plan) = plan.filter(|p| p.status == "draft") else {
+                println!("⚠️ That plan has already been posted.");
+                wait_for_enter();
+                return;
+            };
+
+            let items = get_production_plan_items(conn, plan.id).expect("Failed to load plan");
+            let (lines, warnings) = load_pull_list(conn, &items);
+            let short = print_pull_list(&lines, &warnings);
+            if short > 0 {
+                println!("\n⚠️ {} ingredient(s) are short; posting will take inventory below zero.", short);
+            }
+            if !prompt("\nPost every recipe in this plan as baked? (y/N): ").eq_ignore_ascii_case("y") {
+                println!("Plan not posted.");
+                wait_for_enter();
+                return;
+            }
+            let mut date = prompt(&format!("Date baked (YYYY-MM-DD, default {}): ", plan.plan_date));
+            if date.is_empty() {
+                date = plan.plan_date.clone();
+            }
+            if NaiveDate::parse_from_str(&date, "%Y-%m-%d").is_err() {
+                println!("❌ Invalid date. Plan not posted.");
+                wait_for_enter();
+                return;
+            }
+
+            match post_production_plan(conn, plan.id, &date) {
+                Ok(Some(produced)) => {
+                    for (name, units) in &produced {
+                        println!("✅ {}: {} units", name, units);
+                    }
+                    println!("✅ Plan {} posted as {} production run(s).", plan.id, produced.len());
+                }
+                Ok(None) => println!("⚠️ That plan has already been posted."),
+                Err(e) => println!("❌ Failed to post plan: {}", e),
+            }
+            wait_for_enter();
+        }
+        // Delete Draft Plan
+        "5" => {
+            let Some(plan_id) = prompt_production_plan(conn) else {
+                wait_for_enter();
+                return;
+            };
+            match delete_production_plan(conn, plan_id) {
+                Ok(true) => println!("✅ Plan deleted."),
+                Ok(false) => println!("⚠️ Only draft plans can be deleted."),
+                Err(e) => println!("❌ Failed to delete plan: {}", e),
+            }
+            wait_for_enter();
+        }
+        &_ => {
+            println!("Error--Invalid option\n Returning to Main Menu...");
+        }
+    }
+}
+
+// Print forecast lines grouped by date
+fn print_forecast(lines: &[ForecastLine]) {
+    let mut day = None;
+    for line in lines {
+        if day != Some(line.date) {
+            day = Some(line.date);
+            println!("\n{}", line.date.format("%a %Y-%m-%d"));
+        }
+        let events = if line.events.is_empty() { String::new() } else { format!("  [{}]", line.events.join(", ")) };
+        // Less than two of each weekday makes the weekday index unreliable
+        let short = if line.history_days < 14 { format!("  (only {} days of sales)", line.history_days) } else { String::new() };
+        println!(
+            "  {:<28} {:>4} units ({} batches)  avg {:.1}/day x{:.2} weekday x{:.2} events{}{}",
+            line.recipe_name, line.units, line.batches, line.daily_average, line.weekday_index, line.event_factor, events, short
+        );
+    }
+}
+
+// Sales-driven production suggestions, the events that adjust them, and turning them into a plan
+fn handle_demand_forecast(conn: &Connection) {
+    println!("\n📈 Demand Forecast");
+    println!("1. Forecast Upcoming Days");
+    println!("2. View Upcoming Events");
+    println!("3. Add Event Adjustment");
+    println!("4. Delete Event Adjustment");
+
+    let today = Local::now().date_naive();
+    match prompt("Choose an option: ").as_str() {
+        // Forecast Upcoming Days
+        "1" => {
+            let tomorrow = today + Duration::days(1);
+            let input = prompt(&format!("First day to forecast (YYYY-MM-DD, default {}): ", tomorrow));
+            let first_day = if input.is_empty() {
+                tomorrow
+            } else {
+                match NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
+                    Ok(day) => day,
+                    Err(_) => {
+                        println!("❌ Invalid date.");
+                        wait_for_enter();
+                        return;
+                    }
+                }
+            };
+            let days: i64 = prompt("Days to forecast (default 7): ").parse().unwrap_or(7).clamp(1, 28);
+            let weeks: i64 = prompt("Weeks of sales history to use (default 4): ").parse().unwrap_or(4).clamp(1, 52);
+
+            // History runs up to the day before the forecast, in whole weeks so every weekday counts equally.
+            // It stops at yesterday: today's sales are still coming in and would drag its weekday down.
+            let history_end = (first_day - Duration::days(1)).min(today - Duration::days(1));
+            let history_start = history_end - Duration::days(weeks * 7 - 1);
+            let sales = get_daily_unit_sales(
+                conn,
+                &history_start.format("%Y-%m-%d").to_string(),
+                &history_end.format("%Y-%m-%d").to_string(),
+            ).expect("Error fetching sales");
+
+            let recipes = get_recipe_collection(conn).expect("Error fetching recipes");
+            let history: Vec<SalesHistory> = recipes
+                .iter()
+                .filter(|recipe| sales.iter().any(|(id, _, _)| *id == recipe.id))
+                .map(|recipe| SalesHistory {
+                    recipe_id: recipe.id,
+                    recipe_name: recipe.name.clone(),
+                    yield_quantity: recipe.yield_quantity,
+                    daily: sales
+                        .iter()
+                        .filter(|(id, _, _)| *id == recipe.id)
+                        .filter_map(|(_, date, units)| {
+                            NaiveDate::parse_from_str(date, "%Y-%m-%d").ok().map(|d| (d, *units))
+                        })
+                        .collect(),
+                })
+                .collect();
+            if history.is_empty() {
+                println!(
+                    "⚠️ No itemized sales between {} and {}. Record sales by product to build a forecast.",
+                    history_start, history_end
+                );
+                wait_for_enter();
+                return;
+            }
+
+            let dates: Vec<NaiveDate> = (0..days).map(|offset| first_day + Duration::days(offset)).collect();
+            let events = get_forecast_events(conn, &first_day.format("%Y-%m-%d").to_string())
+                .expect("Error fetching forecast events");
+            let lines = forecast(&history, history_start, history_end, &dates, &events);
+            println!("\n📈 Suggested production from sales {} to {}:", history_start, history_end);
+            print_forecast(&lines);
+
+            let input = prompt("\nCreate a production plan for one of these days? (YYYY-MM-DD, blank to skip): ");
+            if input.is_empty() {
+                wait_for_enter();
+                return;
+            }
+            let plan_day = match NaiveDate::parse_from_str(&input, "%Y-%m-%d") {
+                Ok(day) if dates.contains(&day) => day,
+                _ => {
+                    println!("❌ Pick one of the forecast days.");
+                    wait_for_enter();
+                    return;
+                }
+            };
+            let items: Vec<(i32, f32)> = lines
+                .iter()
+                .filter(|line| line.date == plan_day && line.batches > 0.0)
+                .map(|line| (line.recipe_id, line.batches))
+                .collect();
+            if items.is_empty() {
+                println!("⚠️ Nothing is forecast to sell that day.");
+                wait_for_enter();
+                return;
+            }
+
+            let plan_date = plan_day.format("%Y-%m-%d").to_string();
+            match add_production_plan(conn, &plan_date, Some("From demand forecast"), &items) {
+                Ok(plan_id) => {
+                    println!("✅ Saved draft plan {} for {}; adjust or post it under Production Plans.", plan_id, plan_date);
+                    let plan_items = get_production_plan_items(conn, plan_id).expect("Failed to load plan");
+                    let (pull_lines, warnings) = load_pull_list(conn, &plan_items);
+                    print_pull_list(&pull_lines, &warnings);
+                }
+                Err(e) => println!("❌ Failed to save plan: {}", e),
+            }
+            wait_for_enter();
+        }
+        // View Upcoming Events
+        "2" => {
+            let events = get_forecast_events(conn, &today.format("%Y-%m-%d").to_string())
+                .expect("Error fetching forecast events");
+            let recipes = get_recipe_collection(conn).expect("Error fetching recipes");
+            println!("\n📅 Upcoming events:");
+            for event in &events {
+                let applies_to = event
+                    .recipe_id
+                    .and_then(|id| recipes.iter().find(|r| r.id == id))
+                    .map_or("all recipes", |r| r.name.as_str());
+                println!("{} - {} {} ({})", event.id, event.event_date, event.describe(), applies_to);
+            }
+            if events.is_empty() {
+                println!("⚠️ No upcoming events.");
+            }
+            wait_for_enter();
+        }
+        // Add Event Adjustment
+        "3" => {
+            let event_date = prompt("Event date (YYYY-MM-DD): ");
+            if NaiveDate::parse_from_str(&event_date, "%Y-%m-%d").is_err() {
+                println!("❌ Invalid date.");
+                wait_for_enter();
+                return;
+            }
+            let name = prompt("Event name (e.g. Farmers market, Holiday): ");
+            let adjust_percent: f32 = match prompt("Demand change in % (e.g. 50 or -30): ").trim_end_matches('%').parse() {
+                Ok(percent) if percent >= -100.0 => percent,
+                _ => {
+                    println!("❌ Enter a percentage of -100 or more.");
+                    wait_for_enter();
+                    return;
+                }
+            };
+            let recipes = get_recipe_collection(conn).expect("Error fetching recipes");
+            for recipe in &recipes {
+                println!("{}: {}", recipe.id, recipe.name);
+            }
+            let input = prompt("Recipe ID (blank for all recipes): ");
+            let recipe_id = if input.is_empty() {
+                None
+            } else {
+                match recipes.iter().find(|r| Some(r.id) == input.parse().ok()) {
+                    Some(recipe) => Some(recipe.id),
+                    None => {
+                        println!("⚠️ Recipe not found.");
+                        wait_for_enter();
+                        return;
+                    }
+                }
+            };
+
+            let event = ForecastEvent { id: 0, event_date, name, recipe_id, adjust_percent };
+            match add_forecast_event(conn, &event) {
+                Ok(id) => println!("✅ Added event {}: {} on {}", id, event.describe(), event.event_date),
+                Err(e) => println!("❌ Failed to add event: {}", e),
+            }
+            wait_for_enter();
+        }
+        // Delete Event Adjustment
+        "4" => {
+            let event_id: i32 = prompt("Event ID: ").parse().unwrap_or(0);
+            match delete_forecast_event(conn, event_id) {
+                Ok(true) => println!("✅ Event deleted."),
+                Ok(false) => println!("⚠️ Event not found."),
+                Err(e) => println!("❌ Failed to delete event: {}", e),
+            }
+            wait_for_enter();
+        }
+        &_ => {
+            println!("Error--Invalid option\n Returning to Main Menu...");
+        }
+    }
+}
+
+// Transaction Menu
+pub fn handle_transaction_menu(conn: &Connection) {
+    println!("💰 Transaction Management");
+    println!("1. Add Transaction");
+    println!("2. View Transactions");
+    println!("3. Filter Transactions");
+    println!("4. Print CSV Transaction Report");
+    println!("5. Edit Transaction");
+    println!("6. Reconcile Account");
+    println!("7. View Reconciliation Reports");
+    println!("8. Unlock Reconciled Transaction");
+    println!("9. Record Itemized Sale");
+    println!("10. View Sale Line Items");
+    println!("11. Product Sales & Margin Report");
+    println!("12. Sales Tax");
+    println!("13. Recurring Transactions");
+    println!("14. Budgets");
+    println!("15. Cost of Goods Sold Report");
+    println!("100. Exit");
+
+    print!("Choose an option: ");
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).unwrap();
+
+    match input.trim() {
+        // Add Transaction
+        "1" => {
+            let mut date = String::new();
+            let mut amount_str = String::new();
+            let mut description = String::new();
+
+            println!("💰 Add New Transaction");
+
+            print!("Date (YYYY-MM-DD): ");
+            io::stdout().flush().unwrap();
+            io::stdin().read_line(&mut date).unwrap();
+
+            let transaction_type = prompt(&format!("Transaction type ({}): ", TRANSACTION_TYPES.join("/"))).to_lowercase();
+            if !TRANSACTION_TYPES.contains(&transaction_type.as_str()) {
+                println!("❌ Transaction type must be one of {}.", TRANSACTION_TYPES.join(", "));
+                wait_for_enter();
+                return;
+            }
+
+            print!("Amount: ");
+            io::stdout().flush().unwrap();
+            io::stdin().read_line(&mut amount_str).unwrap();
+
+            print!("Description: ");
+            io::stdout().flush().unwrap();
+            io::stdin().read_line(&mut description).unwrap();
+
+            let mut account = prompt("Account (default Operating): ");
+            if account.is_empty() {
+                account = "Operating".to_string();
+            }
+
+            let category = prompt_category(conn, None);
+            let customer_id = prompt_customer(conn).map(|customer| customer.id);
+
+            let amount: f32 = amount_str.trim().parse().unwrap_or(0.0);
+
+            let transaction = Transaction {
+                id: 0,
+                date: date.trim().to_string(),
+                transaction_type: transaction_type.clone(),
+                amount,
+                description: description.trim().to_string(),
+                account,
+                cleared: false,
+                locked: false,
+                customer_id,
+                invoice_id: None,
+                category,
+                reconciliation_id: None,
+                recurring_id: None,
+                order_id: None,
+            };
+
+            if let Err(e) = add_transaction(conn, &transaction) {
+                println!("❌ Failed to add transaction: {}", e);
+            } else {
+                println!(
+                    "✅ Logged ${:.2} {} on {} — {}",
+                    amount,
+                    transaction_type,
+                    date.trim(),
+                    description.trim()
+                );
+            }
+            // Pause app and wait for user input
+            wait_for_enter();
+        }
+        // View Transactions
+        "2" => {
+            let transactions = read_transactions(conn).expect("Error fetching transactions");
+            print_transaction_table(&transactions);
+            // Pause app and wait for user input
+            wait_for_enter();
+        }
+        // Filter Transactions
+        "3" => {
+            // Filter by type or date
+            let mut input = String::new();
+            print!("1. Filter by transaction type\n2. Filter by date\n");
+            io::stdout().flush().unwrap();
+            io::stdin().read_line(&mut input).unwrap();
+
+            match input.trim() {
+                "1" => {
+                    let mut query = String::new();
+                    print!("Transaction type (sale/expense): ");
+                    io::stdout().flush().unwrap();
+                    io::stdin().read_line(&mut query).unwrap();
+
+                    let transactions = transaction_filter(conn, query.trim()).expect("Error fetching transactions");
+                    print_transaction_table(&transactions);
+                    // Pause app and wait for user input
+                    wait_for_enter();
+                }
+                "2" => {
+                        let mut date = String::new();
+                        print!("Date (YYYY-MM-DD): ");
+                        io::stdout().flush().unwrap();
+                        io::stdin().read_line(&mut date).unwrap();
+                        
+                        let transactions = filter_by_date(conn, date.trim()).expect("Error fetching transactions");
+                        print_transaction_table(&transactions);
+                        // Pause app and wait for user input
+                        wait_for_enter();
+                    }
+                &_ => {
+                    println!("Error--input not accepted")
+                }
+                
+
+            }
+            // Pause app and wait for user input
+            wait_for_enter();
+        }
+        // Print CSV Transaction Report
+        "4" => {
+            let path = prompt_export_path("transaction-report.csv");
+            match write_csv_transaction_report(conn, &path) {
+                Ok(()) => println!("✅ Transactions printed to {}", path.display()),
+                Err(e) => println!("❌ Error: Failed to create report: {}", e),
+            }
+
+            // Pause app and wait for user input
+            wait_for_enter();
+        }
+        // Edit Transaction
+        "5" => {
+            handle_edit_transaction(conn);
+        }
+        // Reconcile Account
+        "6" => {
+            handle_reconciliation(conn);
+        }
+        // View Reconciliation Reports
+        "7" => {
+            handle_reconciliation_reports(conn);
+        }
+        // Unlock Reconciled Transaction
+        "8" => {
+            let transaction_id: i32 = prompt("Transaction ID to unlock: ").parse().unwrap_or(0);
+            println!("⚠️  Unlocked transactions can be edited but stay attached to their reconciliation.");
+            let confirm = prompt("Type 'YES' to confirm: ");
+
+            if confirm == "YES" {
+                match unlock_transaction(conn, transaction_id) {
+                    Ok(true) => println!("🔓 Transaction {} unlocked.", transaction_id),
+                    Ok(false) => println!("⚠️ Transaction {} is not locked or doesn't exist.", transaction_id),
+                    Err(e) => println!("❌ Failed to unlock transaction: {}", e),
+                }
+            } else {
+                println!("❌ Unlock cancelled.");
+            }
+            // Pause app and wait for user input
+            wait_for_enter();
+        }
+        // Record Itemized Sale
+        "9" => {
+            handle_itemized_sale(conn);
+        }
+        // View Sale Line Items
+        "10" => {
+            let transaction_id: i32 = prompt("Sale transaction ID: ").parse().unwrap_or(0);
+            let recipes = get_recipe_collection(conn).expect("Error fetching recipes");
+            let items = get_sale_items(conn, transaction_id).expect("Error fetching sale items");
+
+            if items.is_empty() {
+                println!("⚠️ No line items recorded for that transaction.");
+            } else {
+                println!("\n{:<28} | {:>5} | {:>9} | {:>9}", "Product", "Qty", "Price", "Total");
+                println!("{}", "-".repeat(60));
+                for item in &items {
+                    let name = recipes
+                        .iter()
+                        .find(|r| r.id == item.recipe_id)
+                        .map(|r| r.name.as_str())
+                        .unwrap_or("(deleted recipe)");
+                    println!(
+                        "{:<28} | {:>5} | ${:>8.2} | ${:>8.2}",
+                        name, item.quantity, item.unit_price, item.quantity as f32 * item.unit_price
+                    );
+                }
+            }
+            // Pause app and wait for user input
+            wait_for_enter();
+        }
+        // Product Sales & Margin Report
+        "11" => {
+            handle_product_sales_report(conn);
+        }
+        // Sales Tax
+        "12" => {
+            handle_sales_tax_menu(conn);
+        }
+        // Recurring Transactions
+        "13" => {
+            handle_recurring_menu(conn);
+        }
+        // Budgets
+        "14" => {
+            handle_budget_menu(conn);
+        }
+        // Cost of Goods Sold
+        "15" => {
+            handle_cogs_report(conn);
+        }
+        // Exit Transaction Menu
+        "100" => {
+            println!("👋 Exiting. Goodbye!");
+            std::process::exit(0);
+        }
+        &_ => {
+            println!("Error--Invalid option\n Returning to Main Menu...");
+        }
+    }
+}
+
+// Edit an existing transaction, keeping current values on blank input
+fn handle_edit_transaction(conn: &Connection) {
+    let transaction_id: i32 = prompt("Transaction ID to edit: ").parse().unwrap_or(0);
+
+    let transaction = match get_transaction(conn, transaction_id) {
+        Ok(Some(tx)) => tx,
+        Ok(None) => {
+            println!("❌ Transaction not found!");
+            wait_for_enter();
+            return;
+        }
+        Err(e) => {
+            println!("❌ Error fetching transaction: {}", e);
+            wait_for_enter();
+            return;
+        }
+    };
+
+    if transaction.locked {
+        println!("🔒 Transaction {} is reconciled and locked. Unlock it first to edit.", transaction_id);
+        wait_for_enter();
+        return;
+    }
+
+    println!("Press Enter to keep the current value.");
+    let date = prompt(&format!("Date [{}]: ", transaction.date));
+    // Invoice payments stay receipts so the invoice keeps its balance
+    let transaction_type = if transaction.transaction_type == "receipt" {
+        String::new()
+    } else {
+        prompt(&format!("Transaction type ({}) [{}]: ", TRANSACTION_TYPES.join("/"), transaction.transaction_type)).to_lowercase()
+    };
+    if !transaction_type.is_empty() && !TRANSACTION_TYPES.contains(&transaction_type.as_str()) {
+        println!("❌ Transaction type must be one of {}.", TRANSACTION_TYPES.join(", "));
+        wait_for_enter();
+        return;
+    }
+    let amount = prompt(&format!("Amount [{:.2}]: ", transaction.amount));
+    let description = prompt(&format!("Description [{}]: ", transaction.description));
+    let category = prompt_category(conn, transaction.category.as_deref());
+
+    let date = if date.is_empty() { transaction.date } else { date };
+    let transaction_type = if transaction_type.is_empty() { transaction.transaction_type } else { transaction_type };
+    let amount: f32 = amount.parse().unwrap_or(transaction.amount);
+    let description = if description.is_empty() { transaction.description } else { description };
+
+    match update_transaction(conn, transaction_id, &date, &transaction_type, amount, &description, category.as_deref()) {
+        Ok(true) => println!("✅ Transaction {} updated.", transaction_id),
+        Ok(false) => println!("🔒 Transaction {} is locked and was not changed.", transaction_id),
+        Err(e) => println!("❌ Failed to update transaction: {}", e),
+    }
+    wait_for_enter();
+}
+
+// Reconcile an account against a bank statement
+fn handle_reconciliation(conn: &Connection) {
+    println!("🏦 Bank Reconciliation");
+
+    let mut account = prompt("Account (default Operating): ");
+    if account.is_empty() {
+        account = "Operating".to_string();
+    }
+
+    // Resume an open reconciliation or start a new one
+    let reconciliation_id = match get_open_reconciliation(conn, &account) {
+        Ok(Some(rec)) => {
+            println!(
+                "▶️  Resuming reconciliation for {} ({} to {})",
+                rec.account, rec.period_start, rec.period_end
+            );
+            rec.id
+        }
+        Ok(None) => {
+            let period_start = prompt("Statement start date (YYYY-MM-DD): ");
+            let period_end = prompt("Statement end date (YYYY-MM-DD): ");
+            // The opening balance carries over from the last completed reconciliation;
+            // the first one for an account takes it from the statement
+            let opening_balance = match last_reconciled_balance(conn, &account) {
+                Ok(Some(balance)) => {
+                    println!("Opening balance carried over: ${:.2}", balance);
+                    balance
+                }
+                Ok(None) => match prompt("Statement opening balance: ").parse() {
+                    Ok(balance) => balance,
+                    Err(_) => {
+                        println!("❌ Invalid balance.");
+                        wait_for_enter();
+                        return;
+                    }
+                },
+                Err(e) => {
+                    println!("❌ Error loading the last reconciliation: {}", e);
+                    wait_for_enter();
+                    return;
+                }
+            };
+            let statement_balance: f32 = match prompt("Statement ending balance: ").parse() {
+                Ok(balance) => balance,
+                Err(_) => {
+                    println!("❌ Invalid balance.");
+                    wait_for_enter();
+                    return;
+                }
+            };
+
+            match start_reconciliation(conn, &account, &period_start, &period_end, opening_balance, statement_balance) {
+                Ok(id) => id,
+                Err(e) => {
+                    println!("❌ Failed to start reconciliation: {}", e);
+                    wait_for_enter();
+                    return;
+                }
+            }
+        }
+        Err(e) => {
+            println!("❌ Error loading reconciliation: {}", e);
+            wait_for_enter();
+            return;
+        }
+    };
+
+    loop {
+        let reconciliation = get_reconciliation(conn, reconciliation_id)
+            .expect("Error fetching reconciliation")
+            .expect("Reconciliation not found");
+        let transactions = get_reconciliation_transactions(conn, &reconciliation)
+            .expect("Error fetching transactions");
+        let cleared_balance = reconciliation_cleared_balance(&reconciliation, &transactions);
+        let difference = reconciliation.statement_balance - cleared_balance;
+
+        println!("\n🏦 {} — statement {} to {}", reconciliation.account, reconciliation.period_start, reconciliation.period_end);
+        println!("{:<5} | {:<4} | {:<12} | {:<10} | {:>9} | Description", "Clr", "ID", "Date", "Type", "Amount");
+        println!("{}", "-".repeat(70));
+        for tx in &transactions {
+            println!(
+                "{:<5} | {:<4} | {:<12} | {:<10} | ${:>8.2} | {}",
+                if tx.cleared { "[x]" } else { "[ ]" },
+                tx.id, tx.date, tx.transaction_type, tx.amount, tx.description
+            );
+        }
+        println!("{}", "-".repeat(70));
+        println!("Opening balance:   ${:.2}", reconciliation.opening_balance);
+        println!("Cleared balance:   ${:.2}", cleared_balance);
+        println!("Statement balance: ${:.2}", reconciliation.statement_balance);
+        println!("Difference:        ${:.2}", difference);
+
+        let choice = prompt("\nEnter a transaction ID to tick/untick, F to finish, or Q to save and quit: ");
+
+        match choice.to_uppercase().as_str() {
+            "Q" => {
+                println!("💾 Progress saved. Resume from Reconcile Account.");
+                break;
+            }
+            "F" => {
+                if difference.abs() >= 0.005 {
+                    println!("❌ Difference must be $0.00 to finish (currently ${:.2}).", difference);
+                    continue;
+                }
+                match complete_reconciliation(conn, reconciliation_id) {
+                    Ok(report) => {
+                        println!("✅ Reconciliation complete. Cleared transactions are now locked.\n");
+                        println!("{}", report);
+                    }
+                    Err(e) => println!("❌ Failed to complete reconciliation: {}", e),
+                }
+                break;
+            }
+            id => {
+                let transaction_id: i32 = id.parse().unwrap_or(0);
+                match transactions.iter().find(|tx| tx.id == transaction_id) {
+                    Some(tx) => {
+                        if let Err(e) = set_transaction_cleared(conn, tx.id, !tx.cleared) {
+                            println!("❌ Failed to update transaction: {}", e);
+                        }
+                    }
+                    None => println!("❌ Transaction {} is not part of this reconciliation.", id),
+                }
+            }
+        }
+    }
+
+    wait_for_enter();
+}
+
+// Prompt for product line items until a blank recipe ID is entered
+// Unit price defaults to the customer's tier price (or MSRP); returns (recipe_id, quantity, unit_price)
+fn prompt_line_items(conn: &Connection, recipes: &[RecipeCollection], price_tier_id: Option<i32>) -> Vec<(i32, i32, f32)> {
+    let default_price = |recipe_id: i32| resolve_unit_price(conn, price_tier_id, recipe_id).unwrap_or(None);
+
+    for recipe in recipes {
+        match default_price(recipe.id) {
+            Some(price) => println!("{}: {} (${:.2})", recipe.id, recipe.name, price),
+            None => println!("{}: {} (no MSRP set)", recipe.id, recipe.name),
+        }
+    }
+
+    let mut items: Vec<(i32, i32, f32)> = Vec::new();
+    loop {
+        let input = prompt("\nRecipe ID (blank to finish): ");
+        if input.is_empty() {
+            break;
+        }
+
+        let recipe_id: i32 = input.parse().unwrap_or(0);
+        let recipe = match recipes.iter().find(|r| r.id == recipe_id) {
+            Some(r) => r,
+            None => {
+                println!("❌ Recipe not found!");
+                continue;
+            }
+        };
+
+        let quantity: i32 = prompt("Quantity: ").parse().unwrap_or(0);
+        if quantity <= 0 {
+            println!("❌ Quantity must be greater than zero.");
+            continue;
+        }
+
+        let recipe_price = default_price(recipe.id);
+        let price_label = match recipe_price {
+            Some(price) => format!("Unit price (default ${:.2}): ", price),
+            None => "Unit price: ".to_string(),
+        };
+        let unit_price = match (prompt(&price_label).parse::<f32>(), recipe_price) {
+            (Ok(price), _) => price,
+            (Err(_), Some(price)) => price,
+            (Err(_), None) => {
+                println!("❌ No MSRP set for {}; enter a unit price.", recipe.name);
+                continue;
+            }
+        };
+
+        println!("➕ {} x {} @ ${:.2}", quantity, recipe.name, unit_price);
+        items.push((recipe_id, quantity, unit_price));
+    }
+
+    items
+}
+
+// Record a sale made up of product line items
+fn handle_itemized_sale(conn: &Connection) {
+    println!("🧾 Record Itemized Sale");
+
+    let customer = prompt_customer(conn);
+    let recipes = get_recipe_collection(conn).expect("Error fetching recipes");
+    let items = prompt_line_items(conn, &recipes, customer.as_ref().and_then(|c| c.price_tier_id));
+
+    if items.is_empty() {
+        println!("⚠️ No line items entered. Sale not recorded.");
+        wait_for_enter();
+        return;
+    }
+
+    let date = prompt("Date (YYYY-MM-DD): ");
+    let description = prompt("Description: ");
+    let mut account = prompt("Account (default Operating): ");
+    if account.is_empty() {
+        account = "Operating".to_string();
+    }
+    let category = prompt_category(conn, Some("Sales"));
+
+    let customer_id = customer.map(|c| c.id);
+    let sale_tax = compute_sale_tax(conn, customer_id, &items).expect("Error calculating sales tax");
+    println!("Subtotal:  ${:.2}", sale_tax.gross);
+    println!("Sales tax: ${:.2}", sale_tax.total_tax());
+
+    match add_itemized_sale(conn, &date, &description, &account, category.as_deref(), customer_id, &items) {
+        Ok(id) => println!(
+            "✅ Logged sale {} for ${:.2} ({} line items)",
+            id, sale_tax.total(), items.len()
+        ),
+        Err(e) => println!("❌ Failed to record sale: {}", e),
+    }
+    wait_for_enter();
+}
+
+// Units sold, revenue, cost and gross margin per product
+fn handle_product_sales_report(conn: &Connection) {
+    let start_date = prompt("Start date (YYYY-MM-DD): ");
+    let end_date = prompt("End date (YYYY-MM-DD): ");
+    let by_month = prompt("Break out by month? (y/N): ").eq_ignore_ascii_case("y");
+
+    let sales = get_product_sales(conn, &start_date, &end_date, by_month).expect("Error fetching product sales");
+
+    if sales.is_empty() {
+        println!("⚠️ No itemized sales found for that period.");
+        wait_for_enter();
+        return;
+    }
+
+    println!(
+        "\n{:<24} | {:<4} | {:<24} | {:>6} | {:>10} | {:>9} | {:>10} | {:>7}",
+        "Period", "ID", "Product", "Units", "Revenue", "Cost", "Margin", "Margin%"
+    );
+    println!("{}", "-".repeat(112));
+    for row in &sales {
+        println!(
+            "{:<24} | {:<4} | {:<24} | {:>6} | ${:>9.2} | ${:>8.2} | ${:>9.2} | {:>6.1}%",
+            row.period, row.recipe_id, row.recipe_name, row.units_sold, row.revenue, row.cost,
+            row.gross_margin, row.margin_percent()
+        );
+    }
+
+    let revenue: f32 = sales.iter().map(|row| row.revenue).sum();
+    let cost: f32 = sales.iter().map(|row| row.cost).sum();
+    println!("{}", "-".repeat(112));
+    println!("Total revenue: ${:.2}", revenue);
+    println!("Total cost:    ${:.2}", cost);
+    println!("Gross margin:  ${:.2}", revenue - cost);
+
+    wait_for_enter();
+}
+
+// Theoretical vs actual COGS for a period
+fn handle_cogs_report(conn: &Connection) {
+    let start_date = prompt("Start date (YYYY-MM-DD): ");
+    let end_date = prompt("End date (YYYY-MM-DD): ");
+
+    let mut purchase_category = prompt("Purchases category (default Ingredients): ");
+    if purchase_category.is_empty() {
+        purchase_category = "Ingredients".to_string();
+    }
+
+    // Default opening and closing values to the nearest snapshots
+    let opening_default = match NaiveDate::parse_from_str(&start_date, "%Y-%m-%d") {
+        Ok(start) => {
+            let day_before = (start - Duration::days(1)).format("%Y-%m-%d").to_string();
+            get_snapshot_on_or_before(conn, &day_before).unwrap_or(None)
+        }
+        Err(_) => None,
+    };
+    let closing_default = get_snapshot_on_or_before(conn, &end_date).unwrap_or(None);
+
+    let opening_value: f32 = match &opening_default {
+        Some(snapshot) => prompt(&format!(
+            "Opening inventory value (default ${:.2} from snapshot {}): ", snapshot.total_value, snapshot.title()
+        ))
+        .parse()
+        .unwrap_or(snapshot.total_value),
+        None => match prompt("Opening inventory value: ").parse() {
+            Ok(value) => value,
+            Err(_) => {
+                println!("❌ Invalid opening inventory value.");
+                wait_for_enter();
+                return;
+            }
+        },
+    };
+    let (closing_default, closing_source) = match &closing_default {
+        Some(snapshot) if snapshot.snapshot_date == end_date => (snapshot.total_value, format!("snapshot {}", snapshot.title())),
+        _ => (inventory_value(conn).unwrap_or(0.0), "current stock".to_string()),
+    };
+    let closing_value: f32 = prompt(&format!("Closing inventory value (default ${:.2} from {}): ", closing_default, closing_source))
+        .parse()
+        .unwrap_or(closing_default);
+
+    match get_cogs_report(conn, &start_date, &end_date, &purchase_category, opening_value, closing_value) {
+        Ok(report) => println!("\n{}", render_cogs_report(&report)),
+        Err(e) => println!("❌ Error building COGS report: {}", e),
+    }
+    wait_for_enter();
+}
+
+// Preview recurring transactions that have come due and post them on confirmation
+// Returns false (without printing) when nothing is due
+pub fn handle_post_due_recurring(conn: &Connection) -> bool {
+    let templates = get_recurring_transactions(conn).expect("Error fetching recurring transactions");
+    let today = Local::now().date_naive();
+
+    let due: Vec<(&RecurringTransaction, Vec<NaiveDate>)> = templates
+        .iter()
+        .map(|rt| (rt, rt.due_dates(today)))
+        .filter(|(_, dates)| !dates.is_empty())
+        .collect();
+
+    if due.is_empty() {
+        return false;
+    }
+
+    println!("\n🔁 Recurring transactions due:");
+    println!("{:<12} | {:<10} | {:>9} | Description", "Date", "Type", "Amount");
+    println!("{}", "-".repeat(60));
+    let mut count = 0;
+    for (rt, dates) in &due {
+        for date in dates {
+            count += 1;
+            println!(
+                "{:<12} | {:<10} | ${:>8.2} | {}{}",
+                date.format("%Y-%m-%d"), rt.transaction_type, rt.amount, rt.description,
+                if rt.is_estimate { " (estimated)" } else { "" }
+            );
+        }
+    }
+
+    if prompt(&format!("\nPost these {} transactions now? (y/N): ", count)).eq_ignore_ascii_case("y") {
+        // Estimated templates post whatever was actually billed; blank keeps the estimate
+        let occurrences: Vec<RecurringOccurrences> = due
+            .iter()
+            .map(|(rt, dates)| {
+                let dates = dates
+                    .iter()
+                    .map(|date| {
+                        let actual = if rt.is_estimate {
+                            prompt_optional_number(&format!(
+                                "Actual amount for {} on {} (blank to post the ${:.2} estimate): ",
+                                rt.description, date.format("%Y-%m-%d"), rt.amount
+                            ))
+                        } else {
+                            None
+                        };
+                        (*date, actual)
+                    })
+                    .collect();
+                (*rt, dates)
+            })
+            .collect();
+        match post_recurring_occurrences(conn, &occurrences) {
+            Ok(posted) => println!("✅ Posted {} recurring transactions.", posted),
+            Err(e) => println!("❌ Failed to post recurring transactions: {}", e),
+        }
+    } else {
+        println!("⏭  Skipped; they'll be offered again next time.");
+    }
+    true
+}
+
+// Prompt for a budget category, listing the ones already in use
+// Blank input keeps `current`
+fn prompt_category(conn: &Connection, current: Option<&str>) -> Option<String> {
+    let categories = get_transaction_categories(conn).unwrap_or_default();
+    if !categories.is_empty() {
+        println!("Categories: {}", categories.join(", "));
+    }
+    let category = match current {
+        Some(current) => prompt(&format!("Category [{}]: ", current)),
+        None => prompt("Category (blank for none): "),
+    };
+
+    if category.is_empty() {
+        current.map(|c| c.to_string())
+    } else {
+        // Reuse the existing spelling of a category typed in a different case
+        Some(categories.into_iter().find(|c| c.eq_ignore_ascii_case(&category)).unwrap_or(category))
+    }
+}
+
+// Monthly budgets and budget-vs-actual variance
+fn handle_budget_menu(conn: &Connection) {
+    println!("\n📊 Budgets");
+    println!("1. Budget vs Actual Report");
+    println!("2. Set Category Budget");
+    println!("3. Copy Last Year's Actuals to Budget");
+    println!("(blank to return)");
+
+    match prompt("Choose an option: ").as_str() {
+        "1" => {
+            let current_month = Local::now().format("%Y-%m").to_string();
+            let mut month = prompt(&format!("Month (YYYY-MM, default {}): ", current_month));
+            if month.is_empty() {
+                month = current_month;
+            }
+
+            let lines = match get_budget_vs_actual(conn, &month) {
+                Ok(lines) => lines,
+                Err(e) => {
+                    println!("❌ Error building budget report: {}", e);
+                    wait_for_enter();
+                    return;
+                }
+            };
+
+            println!("\nBudget vs Actual — {}", month);
+            println!(
+                "{:<8} | {:<20} | {:>10} | {:>10} | {:>10} | {:>8}",
+                "Type", "Category", "Budget", "Actual", "Variance", "Var %"
+            );
+            println!("{}", "-".repeat(82));
+            for budget_type in BUDGET_TYPES {
+                let section: Vec<_> = lines.iter().filter(|l| l.budget_type == budget_type).collect();
+                if section.is_empty() {
+                    continue;
+                }
+                for line in &section {
+                    let percent = match line.variance_percent() {
+                        Some(p) => format!("{:+.1}%", p),
+                        None => "n/a".to_string(),
+                    };
+                    let flag = if line.variance() < 0.0 { " ⚠️" } else { "" };
+                    println!(
+                        "{:<8} | {:<20} | {:>10.2} | {:>10.2} | {:>+10.2} | {:>8}{}",
+                        line.budget_type, line.category, line.budgeted, line.actual, line.variance(), percent, flag
+                    );
+                }
+                let budgeted = section.iter().fold(0.0, |sum, l| sum + l.budgeted);
+                let actual = section.iter().fold(0.0, |sum, l| sum + l.actual);
+                let variance = section.iter().fold(0.0, |sum, l| sum + l.variance());
+                println!(
+                    "{:<8} | {:<20} | {:>10.2} | {:>10.2} | {:>+10.2} |",
+                    "", format!("Total {}", budget_type), budgeted, actual, variance
+                );
+                println!("{}", "-".repeat(82));
+            }
+            if lines.is_empty() {
+                println!("(no budgets or activity for {})", month);
+            }
+            println!("Positive variance is favourable: revenue above budget or spending below it.");
+        }
+        "2" => {
+            let month = prompt("Month (YYYY-MM): ");
+            if NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").is_err() {
+                println!("❌ Month must be in YYYY-MM format.");
+                wait_for_enter();
+                return;
+            }
+            let category = match prompt_category(conn, None) {
+                Some(category) => category,
+                None => {
+                    println!("❌ A category is required.");
+                    wait_for_enter();
+                    return;
+                }
+            };
+            let budget_type = prompt(&format!("Budget type ({}): ", BUDGET_TYPES.join("/"))).to_lowercase();
+            if !BUDGET_TYPES.contains(&budget_type.as_str()) {
+                println!("❌ Budget type must be one of {}.", BUDGET_TYPES.join(", "));
+                wait_for_enter();
+                return;
+            }
+            let amount: f32 = match prompt("Budget amount: ").parse() {
+                Ok(amount) => amount,
+                Err(_) => {
+                    println!("❌ Invalid amount.");
+                    wait_for_enter();
+                    return;
+                }
+            };
+
+            match set_budget(conn, &month, &category, &budget_type, amount) {
+                Ok(()) => println!("✅ {} {} budget for {} set to ${:.2}", category, budget_type, month, amount),
+                Err(e) => println!("❌ Failed to set budget: {}", e),
+            }
+        }
+        "3" => {
+            let this_year = Local::now().year();
+            let to_year: i32 = prompt(&format!("Budget year (default {}): ", this_year)).parse().unwrap_or(this_year);
+            let uplift: f32 = prompt("Uplift % over last year's actuals (default 0): ").parse().unwrap_or(0.0);
+
+            match copy_actuals_to_budget(conn, to_year - 1, to_year, uplift) {
+                Ok(0) => println!("⚠️ No {} sales or expenses to copy.", to_year - 1),
+                Ok(n) => println!("✅ Wrote {} budget lines for {} from {} actuals (+{:.1}%)", n, to_year, to_year - 1, uplift),
+                Err(e) => println!("❌ Failed to copy budgets: {}", e),
+            }
+        }
+        "" => return,
+        _ => println!("Error--Invalid option\n Returning to Main Menu..."),
+    }
+    wait_for_enter();
+}
+
+// Manage recurring transaction templates
+fn handle_recurring_menu(conn: &Connection) {
+    let templates = get_recurring_transactions(conn).expect("Error fetching recurring transactions");
+    let today = Local::now().date_naive();
+
+    println!("\n🔁 Recurring Transactions:");
+    for rt in &templates {
+        let status = match rt.due_dates(today).len() {
+            0 => String::new(),
+            n => format!(" — {} due", n),
+        };
+        println!(
+            "{}: {} {} ${:.2}{} ({}, {} to {}){}",
+            rt.id, rt.description, rt.transaction_type, rt.amount,
+            if rt.is_estimate { " est." } else { "" },
+            rt.schedule_label(), rt.start_date, rt.end_date.as_deref().unwrap_or("open-ended"), status
+        );
+    }
+    if templates.is_empty() {
+        println!("(none)");
+    }
+
+    println!("\n1. Add Recurring Transaction");
+    println!("2. End Recurring Transaction");
+    println!("3. Post Due Transactions");
+    println!("(blank to return)");
+
+    match prompt("Choose an option: ").as_str() {
+        "1" => {
+            let description = prompt("Description (e.g. Rent): ");
+            let transaction_type = prompt(&format!("Transaction type ({}): ", RECURRING_TRANSACTION_TYPES.join("/"))).to_lowercase();
+            if !RECURRING_TRANSACTION_TYPES.contains(&transaction_type.as_str()) {
+                println!("❌ Transaction type must be one of {}.", RECURRING_TRANSACTION_TYPES.join(", "));
+                wait_for_enter();
+                return;
+            }
+            let amount: f32 = match prompt("Amount: ").parse() {
+                Ok(amount) => amount,
+                Err(_) => {
+                    println!("❌ Invalid amount.");
+                    wait_for_enter();
+                    return;
+                }
+            };
+            let is_estimate = prompt("Is the amount an estimate? (y/N): ").eq_ignore_ascii_case("y");
+            let mut account = prompt("Account (default Operating): ");
+            if account.is_empty() {
+                account = "Operating".to_string();
+            }
+            let category = prompt_category(conn, None);
+
+            let frequency = prompt(&format!("Frequency ({}): ", RECURRING_FREQUENCIES.join("/"))).to_lowercase();
+            if !RECURRING_FREQUENCIES.contains(&frequency.as_str()) {
+                println!("❌ Frequency must be one of {}.", RECURRING_FREQUENCIES.join(", "));
+                wait_for_enter();
+                return;
+            }
+            let interval: i32 = prompt("Repeat every N periods (default 1): ").parse().unwrap_or(1);
+
+            let start_date = prompt("Start date (YYYY-MM-DD): ");
+            let start = match NaiveDate::parse_from_str(&start_date, "%Y-%m-%d") {
+                Ok(date) => date,
+                Err(_) => {
+                    println!("❌ Invalid start date.");
+                    wait_for_enter();
+                    return;
+                }
+            };
+            // Monthly templates post on a fixed day of the month
+            let day_of_month = if frequency == "monthly" {
+                let default_day = start.day() as i32;
+                Some(prompt(&format!("Day of month (default {}): ", default_day)).parse().unwrap_or(default_day))
+            } else {
+                None
+            };
+            let end_date = prompt("End date (YYYY-MM-DD, blank for none): ");
+
+            let recurring = RecurringTransaction {
+                id: 0,
+                description,
+                transaction_type,
+                amount,
+                is_estimate,
+                account,
+                frequency,
+                interval: interval.max(1),
+                day_of_month,
+                start_date,
+                end_date: if end_date.is_empty() { None } else { Some(end_date) },
+                last_posted_date: None,
+                category,
+            };
+
+            match add_recurring_transaction(conn, &recurring) {
+                Ok(id) => println!("✅ Added recurring transaction {} ({})", id, recurring.schedule_label()),
+                Err(e) => println!("❌ Failed to add recurring transaction: {}", e),
+            }
+        }
+        "2" => {
+            let recurring_id: i32 = prompt("Recurring transaction ID: ").parse().unwrap_or(0);
+            let mut end_date = prompt("Last date to post (YYYY-MM-DD, default today): ");
+            if end_date.is_empty() {
+                end_date = today.format("%Y-%m-%d").to_string();
+            }
+            match end_recurring_transaction(conn, recurring_id, &end_date) {
+                Ok(true) => println!("✅ Recurring transaction {} ends {}", recurring_id, end_date),
+                Ok(false) => println!("❌ Recurring transaction not found!"),
+                Err(e) => println!("❌ Failed to update recurring transaction: {}", e),
+            }
+        }
+        "3" => {
+            if !handle_post_due_recurring(conn) {
+                println!("✅ No recurring transactions are due.");
+            }
+        }
+        _ => return,
+    }
+    wait_for_enter();
+}
+
+// Sales tax rates, category taxability and the filing-period liability report
+fn handle_sales_tax_menu(conn: &Connection) {
+    let jurisdictions = get_tax_jurisdictions(conn).expect("Error fetching tax jurisdictions");
+
+    println!("\n🏛  Tax Jurisdictions:");
+    for j in &jurisdictions {
+        println!(
+            "{}: {} ({}) {:.3}%{}",
+            j.id, j.name, j.level, j.rate, if j.active { "" } else { " — inactive" }
+        );
+    }
+    if jurisdictions.is_empty() {
+        println!("(none — sales are recorded without tax)");
+    }
+    println!("Combined active rate: {:.3}%", combined_tax_rate(conn).unwrap_or(0.0));
+
+    println!("\n1. Add Jurisdiction");
+    println!("2. Update Jurisdiction Rate / Active");
+    println!("3. Category Taxability");
+    println!("4. Sales Tax Liability Report");
+    println!("(blank to return)");
+
+    match prompt("Choose an option: ").as_str() {
+        "1" => {
+            let name = prompt("Jurisdiction name (e.g. Ohio, Franklin County): ");
+            let level = prompt(&format!("Level ({}): ", TAX_LEVELS.join("/"))).to_lowercase();
+            if !TAX_LEVELS.contains(&level.as_str()) {
+                println!("❌ Level must be one of {}.", TAX_LEVELS.join(", "));
+            } else {
+                match prompt("Rate %: ").parse::<f32>() {
+                    Ok(rate) => match add_tax_jurisdiction(conn, &name, &level, rate) {
+                        Ok(id) => println!("✅ Added jurisdiction {} - {} at {:.3}%", id, name, rate),
+                        Err(e) => println!("❌ Failed to add jurisdiction: {}", e),
+                    },
+                    Err(_) => println!("❌ Invalid rate."),
+                }
+            }
+        }
+        "2" => {
+            let jurisdiction_id: i32 = prompt("Jurisdiction ID: ").parse().unwrap_or(0);
+            match jurisdictions.iter().find(|j| j.id == jurisdiction_id) {
+                Some(j) => {
+                    let rate: f32 = prompt(&format!("Rate % [{:.3}]: ", j.rate)).parse().unwrap_or(j.rate);
+                    let active_input = prompt(&format!("Active? (y/n) [{}]: ", if j.active { "y" } else { "n" }));
+                    let active = if active_input.is_empty() { j.active } else { active_input.eq_ignore_ascii_case("y") };
+                    match update_tax_jurisdiction(conn, j.id, rate, active) {
+                        Ok(_) => println!("✅ Updated {}", j.name),
+                        Err(e) => println!("❌ Failed to update jurisdiction: {}", e),
+                    }
+                }
+                None => println!("❌ Jurisdiction not found!"),
+            }
+        }
+        "3" => {
+            let rules = get_category_tax_rules(conn).expect("Error fetching tax rules");
+            println!("\nRecipe categories:");
+            for (category, taxable) in &rules {
+                println!("- {}: {}", category, if *taxable { "taxable" } else { "exempt" });
+            }
+            let category = prompt("Category to change (blank to return): ");
+            if !category.is_empty() {
+                let taxable = prompt("Taxable? (y/n): ").eq_ignore_ascii_case("y");
+                match set_category_taxable(conn, &category, taxable) {
+                    Ok(_) => println!("✅ {} is now {}", category, if taxable { "taxable" } else { "exempt" }),
+                    Err(e) => println!("❌ Failed to save rule: {}", e),
+                }
+            }
+        }
+        "4" => {
+            let start_date = prompt("Filing period start (YYYY-MM-DD): ");
+            let end_date = prompt("Filing period end (YYYY-MM-DD): ");
+            let liabilities = get_sales_tax_liability(conn, &start_date, &end_date)
+                .expect("Error building sales tax report");
+
+            println!("\nSales Tax Liability {} to {}", start_date, end_date);
+            println!(
+                "{:<22} | {:<6} | {:>7} | {:>11} | {:>11} | {:>11} | {:>10}",
+                "Jurisdiction", "Level", "Rate", "Gross", "Exempt", "Taxable", "Tax"
+            );
+            println!("{}", "-".repeat(98));
+            for l in &liabilities {
+                println!(
+                    "{:<22} | {:<6} | {:>6.3}% | ${:>10.2} | ${:>10.2} | ${:>10.2} | ${:>9.2}",
+                    l.jurisdiction, l.level, l.rate, l.gross_sales, l.exempt_sales, l.taxable_sales, l.tax_collected
+                );
+            }
+            println!("{}", "-".repeat(98));
+            let total_tax: f32 = liabilities.iter().map(|l| l.tax_collected).sum();
+            println!("Total tax collected: ${:.2}", total_tax);
+
+            let (count, amount) = get_unclassified_sales(conn, &start_date, &end_date).unwrap_or((0, 0.0));
+            if count > 0 {
+                println!(
+                    "⚠️ {} lump-sum sales (${:.2}) have no line items and aren't included above.",
+                    count, amount
+                );
+            }
+        }
+        _ => return,
+    }
+    wait_for_enter();
+}
+
+// List completed reconciliations and reprint a stored report
+fn handle_reconciliation_reports(conn: &Connection) {
+    let reconciliations = get_reconciliations(conn).expect("Error fetching reconciliations");
+
+    if reconciliations.is_empty() {
+        println!("⚠️ No reconciliations found.");
+        wait_for_enter();
+        return;
+    }
+
+    println!("\n🏦 Reconciliations:");
+    for rec in &reconciliations {
+        let status = match &rec.completed_at {
+            Some(completed_at) => format!("{} {}", rec.status, completed_at),
+            None => rec.status.clone(),
+        };
+        println!(
+            "{}: {} {} to {} — ${:.2} ({})",
+            rec.id, rec.account, rec.period_start, rec.period_end, rec.statement_balance, status
+        );
+    }
+
+    let reconciliation_id: i32 = prompt("Enter reconciliation ID to reprint: ").parse().unwrap_or(0);
+
+    match reconciliations.iter().find(|rec| rec.id == reconciliation_id) {
+        Some(rec) => match &rec.report {
+            Some(report) => println!("\n{}", report),
+            None => println!("⚠️ Reconciliation {} is still open; finish it to generate a report.", rec.id),
+        },
+        None => println!("❌ Reconciliation not found!"),
+    }
+
+    wait_for_enter();
+}
+
+// Optionally pick a customer from the directory; blank input means no customer
+fn prompt_customer(conn: &Connection) -> Option<Customer> {
+    let customers = get_customers(conn).expect("Error fetching customers");
+    if customers.is_empty() {
+        return None;
+    }
+
+    for customer in &customers {
+        println!("{}: {} ({})", customer.id, customer.name, customer.customer_type);
+    }
+
+    let input = prompt("Customer ID (blank for none): ");
+    if input.is_empty() {
+        return None;
+    }
+
+    let customer_id: i32 = input.parse().unwrap_or(0);
+    let customer = customers.into_iter().find(|c| c.id == customer_id);
+    if customer.is_none() {
+        println!("⚠️ Customer not found; continuing without one.");
+    }
+    customer
+}
+
+// Pick a price tier by id; blank keeps the current one, 0 clears it
+fn prompt_price_tier(conn: &Connection, current: Option<i32>) -> Option<i32> {
+    let tiers = get_price_tiers(conn).expect("Error fetching price tiers");
+    if tiers.is_empty() {
+        return current;
+    }
+
+    println!("0: MSRP (no tier)");
+    for tier in &tiers {
+        match tier.discount_percent {
+            Some(discount) => println!("{}: {} ({:.1}% off MSRP)", tier.id, tier.name, discount),
+            None => println!("{}: {} (fixed prices)", tier.id, tier.name),
+        }
+    }
+
+    match prompt("Price tier: ").parse::<i32>() {
+        Ok(0) => None,
+        Ok(id) if tiers.iter().any(|t| t.id == id) => Some(id),
+        Ok(_) => {
+            println!("⚠️ Price tier not found; keeping current.");
+            current
+        }
+        Err(_) => current,
+    }
+}
+
+// Add a new customer, or edit an existing one keeping values on blank input
+fn handle_customer_form(conn: &Connection, editing: bool) {
+    let mut customer = if editing {
+        let customer_id: i32 = prompt("Customer ID: ").parse().unwrap_or(0);
+        match get_customer(conn, customer_id) {
+            Ok(Some(customer)) => {
+                println!("Press Enter to keep the current value.");
+                customer
+            }
+            _ => {
+                println!("❌ Customer not found!");
+                wait_for_enter();
+                return;
+            }
+        }
+    } else {
+        println!("👥 Add Customer");
+        Customer {
+            id: 0,
+            name: String::new(),
+            email: None,
+            phone: None,
+            address: None,
+            customer_type: "retail".to_string(),
+            price_tier_id: None,
+            tax_exempt: false,
+            notes: None,
+        }
+    };
+
+    // Blank input keeps the current value
+    let keep = |label: &str, current: Option<&str>| -> Option<String> {
+        let input = prompt(&format!("{} [{}]: ", label, current.unwrap_or("")));
+        if input.is_empty() {
+            current.map(|c| c.to_string())
+        } else {
+            Some(input)
+        }
+    };
+
+    customer.name = keep("Name", Some(&customer.name)).unwrap_or_default();
+    if customer.name.is_empty() {
+        println!("❌ Customer name is required.");
+        wait_for_enter();
+        return;
+    }
+    customer.email = keep("Email", customer.email.as_deref());
+    customer.phone = keep("Phone", customer.phone.as_deref());
+    customer.address = keep("Address", customer.address.as_deref());
+
+    let customer_type = keep(&format!("Type ({})", CUSTOMER_TYPES.join("/")), Some(&customer.customer_type))
+        .unwrap_or_default()
+        .to_lowercase();
+    if CUSTOMER_TYPES.contains(&customer_type.as_str()) {
+        customer.customer_type = customer_type;
+    } else {
+        println!("⚠️ Unknown type '{}'; keeping {}.", customer_type, customer.customer_type);
+    }
+
+    customer.price_tier_id = prompt_price_tier(conn, customer.price_tier_id);
+    let tax_exempt = prompt(&format!("Tax exempt? (y/n) [{}]: ", if customer.tax_exempt { "y" } else { "n" }));
+    if !tax_exempt.is_empty() {
+        customer.tax_exempt = tax_exempt.eq_ignore_ascii_case("y");
+    }
+    customer.notes = keep("Notes", customer.notes.as_deref());
+
+    let result = if editing {
+        update_customer(conn, &customer).map(|_| customer.id)
+    } else {
+        add_customer(conn, &customer)
+    };
+    match result {
+        Ok(id) => println!("✅ Saved customer {} - {}", id, customer.name),
+        Err(e) => println!("❌ Failed to save customer: {}", e),
+    }
+    wait_for_enter();
+}
+
+// Lifetime view of a customer's orders and payments
+fn handle_customer_history(conn: &Connection) {
+    let customer_id: i32 = prompt("Customer ID: ").parse().unwrap_or(0);
+    let customer = match get_customer(conn, customer_id) {
+        Ok(Some(customer)) => customer,
+        _ => {
+            println!("❌ Customer not found!");
+            wait_for_enter();
+            return;
+        }
+    };
+
+    println!("\n👥 {} ({})", customer.name, customer.customer_type);
+    if let Some(email) = &customer.email {
+        println!("Email: {}", email);
+    }
+    if let Some(phone) = &customer.phone {
+        println!("Phone: {}", phone);
+    }
+    if let Some(address) = &customer.address {
+        println!("Address: {}", address);
+    }
+    if customer.tax_exempt {
+        println!("Tax exempt");
+    }
+    if let Some(notes) = &customer.notes {
+        println!("Notes: {}", notes);
+    }
+
+    let orders = get_orders_for_customer(conn, customer.id).expect("Error fetching orders");
+    println!("\nOrders:");
+    let mut open_balance = 0.0;
+    for order in &orders {
+        let total = order_total(conn, order).total();
+        if order.status != "fulfilled" && order.status != "cancelled" {
+            open_balance += total - order.deposit_paid;
+        }
+        println!(
+            "#{:<4} {} | {:<13} | total ${:>8.2} | deposit ${:>7.2}",
+            order.id, order.due_date, order.status, total, order.deposit_paid
+        );
+    }
+    if orders.is_empty() {
+        println!("(none)");
+    }
+
+    let transactions = get_transactions_for_customer(conn, customer.id).expect("Error fetching transactions");
+    println!();
+    print_transaction_table(&transactions);
+
+    let sales: Vec<&Transaction> = transactions.iter().filter(|tx| tx.transaction_type == "sale").collect();
+    let lifetime_sales: f32 = sales.iter().map(|tx| tx.amount).sum();
+    // An order's deposit and fulfilment rows are one sale
+    let mut order_ids: Vec<i32> = sales.iter().filter_map(|tx| tx.order_id).collect();
+    order_ids.sort();
+    order_ids.dedup();
+    let sale_count = sales.iter().filter(|tx| tx.order_id.is_none()).count() + order_ids.len();
+    let fulfilled = orders.iter().filter(|o| o.status == "fulfilled").count();
+    println!("\nLifetime sales:      ${:.2}", lifetime_sales);
+    println!("Orders placed:       {} ({} fulfilled)", orders.len(), fulfilled);
+    println!("Open order balance:  ${:.2}", open_balance);
+    if sale_count > 0 {
+        println!("Average sale:        ${:.2}", lifetime_sales / sale_count as f32);
+    }
+
+    wait_for_enter();
+}
+
+// List price tiers, add tiers and set fixed per-product prices
+fn handle_price_tiers(conn: &Connection) {
+    let tiers = get_price_tiers(conn).expect("Error fetching price tiers");
+
+    println!("\n🏷  Price Tiers:");
+    for tier in &tiers {
+        match tier.discount_percent {
+            Some(discount) => println!("{}: {} — {:.1}% off MSRP", tier.id, tier.name, discount),
+            None => println!("{}: {} — fixed prices only", tier.id, tier.name),
+        }
+        for (_, recipe_name, unit_price) in get_tier_prices(conn, tier.id).expect("Error fetching tier prices") {
+            println!("    {}: ${:.2}", recipe_name, unit_price);
+        }
+    }
+    if tiers.is_empty() {
+        println!("(none)");
+    }
+
+    println!("\n1. Add Price Tier\n2. Set Fixed Product Price\n(blank to return)");
+    match prompt("Choose an option: ").as_str() {
+        "1" => {
+            let name = prompt("Tier name (e.g. Wholesale): ");
+            let discount = prompt("Discount % off MSRP (blank for fixed prices only): ");
+            match add_price_tier(conn, &name, discount.parse().ok()) {
+                Ok(id) => println!("✅ Added price tier {} - {}", id, name),
+                Err(e) => println!("❌ Failed to add price tier: {}", e),
+            }
+        }
+        "2" => {
+            let tier_id: i32 = prompt("Tier ID: ").parse().unwrap_or(0);
+            if !tiers.iter().any(|t| t.id == tier_id) {
+                println!("❌ Price tier not found!");
+            } else {
+                let recipes = get_recipe_collection(conn).expect("Error fetching recipes");
+                for recipe in &recipes {
+                    println!("{}: {}", recipe.id, recipe.name);
+                }
+                let recipe_id: i32 = prompt("Recipe ID: ").parse().unwrap_or(0);
+                let unit_price: Option<f32> = prompt("Fixed unit price: ").parse().ok();
+                match (recipes.iter().find(|r| r.id == recipe_id), unit_price) {
+                    (Some(recipe), Some(price)) => match set_tier_price(conn, tier_id, recipe_id, price) {
+                        Ok(_) => println!("✅ {} set to ${:.2} for this tier", recipe.name, price),
+                        Err(e) => println!("❌ Failed to set price: {}", e),
+                    },
+                    (None, _) => println!("❌ Recipe not found!"),
+                    (_, None) => println!("❌ Invalid price."),
+                }
+            }
+        }
+        _ => {}
+    }
+    wait_for_enter();
+}
+
+// Print a one-line order summary
+fn print_order_summary(order: &Order) {
+    println!(
+        "#{:<4} {} {} | {:<20} | {:<8} | {:<13} | deposit ${:.2}",
+        order.id,
+        order.due_date,
+        order.due_time.as_deref().unwrap_or("--:--"),
+        order.customer_name,
+        order.fulfilment,
+        order.status,
+        order.deposit_paid
+    );
+}
+
+// Prompt for an order ID and load it
+fn prompt_order(conn: &Connection) -> Option<Order> {
+    let order_id: i32 = prompt("Order ID: ").parse().unwrap_or(0);
+    match get_order(conn, order_id) {
+        Ok(Some(order)) => Some(order),
+        Ok(None) => {
+            println!("❌ Order not found!");
+            None
+        }
+        Err(e) => {
+            println!("❌ Error fetching order: {}", e);
+            None
+        }
+    }
+}
+
+// An order's products and sales tax, worked out the same way fulfilment will charge them
+fn order_total(conn: &Connection, order: &Order) -> SaleTax {
+    get_order_sale_tax(conn, order).expect("Error calculating the order total")
+}
+
+// Take a deposit against an order, up to the balance still due
+fn handle_order_deposit(conn: &Connection, order: &Order) {
+    let balance = order_total(conn, order).total() - order.deposit_paid;
+    let amount: f32 = prompt(&format!("Deposit amount (balance due ${:.2}): ", balance)).parse().unwrap_or(0.0);
+    if amount <= 0.0 {
+        println!("❌ Deposit must be more than zero.");
+        return;
+    }
+    if amount > balance + 0.005 {
+        println!("❌ Deposit is more than the ${:.2} balance due.", balance);
+        return;
+    }
+
+    let mut date = prompt("Date received (YYYY-MM-DD, default today): ");
+    if date.is_empty() {
+        date = Local::now().format("%Y-%m-%d").to_string();
+    }
+    if NaiveDate::parse_from_str(&date, "%Y-%m-%d").is_err() {
+        println!("❌ Invalid date.");
+        return;
+    }
+    let mut account = prompt("Account (default Operating): ");
+    if account.is_empty() {
+        account = "Operating".to_string();
+    }
+    let category = prompt_category(conn, Some("Sales"));
+
+    match add_order_deposit(conn, order.id, amount, &date, &account, category.as_deref()) {
+        Ok(true) => println!("✅ Recorded ${:.2} deposit on order #{}.", amount, order.id),
+        Ok(false) => println!("⚠️ Order #{} is closed or already paid up; deposit not recorded.", order.id),
+        Err(e) => println!("❌ Failed to record deposit: {}", e),
+    }
+}
+
+// Take a new customer or catering order
+fn handle_new_order(conn: &Connection) {
+    println!("🧁 New Order");
+
+    // Pick a customer from the directory, or take a one-off name and contact
+    let customer = prompt_customer(conn);
+    let (customer_name, customer_contact) = match &customer {
+        Some(c) => (c.name.clone(), c.phone.clone().or(c.email.clone()).unwrap_or_default()),
+        None => (prompt("Customer name: "), prompt("Contact (phone/email, optional): ")),
+    };
+
+    let recipes = get_recipe_collection(conn).expect("Error fetching recipes");
+    println!("\nRequested products:");
+    let items = prompt_line_items(conn, &recipes, customer.as_ref().and_then(|c| c.price_tier_id));
+    if items.is_empty() {
+        println!("⚠️ No products entered. Order not created.");
+        wait_for_enter();
+        return;
+    }
+
+    let due_date = prompt("Pickup/delivery date (YYYY-MM-DD): ");
+    let due_time = prompt("Pickup/delivery time (HH:MM, optional): ");
+    let fulfilment = if prompt("Delivery? (y/N): ").eq_ignore_ascii_case("y") { "delivery" } else { "pickup" };
+    let deposit_paid: f32 = prompt("Deposit paid (default 0): ").parse().unwrap_or(0.0);
+    if deposit_paid < 0.0 {
+        println!("❌ Deposit can't be negative. Order not created.");
+        wait_for_enter();
+        return;
+    }
+    // Quote what fulfilment will charge: the products plus sales tax
+    let sale_tax = compute_sale_tax(conn, customer.as_ref().map(|c| c.id), &items).expect("Error calculating sales tax");
+    let total = sale_tax.total();
+    if deposit_paid > total + 0.005 {
+        println!("❌ Deposit is more than the ${:.2} order total. Order not created.", total);
+        wait_for_enter();
+        return;
+    }
+    let confirmed = prompt("Confirmed by customer? (y/N): ").eq_ignore_ascii_case("y");
+    let notes = prompt("Notes (optional): ");
+
+    let order = Order {
+        id: 0,
+        customer_name,
+        customer_contact: if customer_contact.is_empty() { None } else { Some(customer_contact) },
+        due_date,
+        due_time: if due_time.is_empty() { None } else { Some(due_time) },
+        fulfilment: fulfilment.to_string(),
+        // Recorded below with add_order_deposit so it is posted to the ledger
+        deposit_paid: 0.0,
+        status: if confirmed { "confirmed" } else { "quoted" }.to_string(),
+        notes: if notes.is_empty() { None } else { Some(notes) },
+        created_date: Local::now().format("%Y-%m-%d").to_string(),
+        sale_transaction_id: None,
+        customer_id: customer.map(|c| c.id),
+    };
+
+    match add_order(conn, &order, &items) {
+        Ok(id) => {
             println!(
-                "\n{:<4} | {:<12} | {:<10} | {:>8} | {}",
-                "ID", "Date", "Type", "Amount", "Description"
+                "✅ Order #{} created ({}) — ${:.2} + ${:.2} tax = total ${:.2}, balance due ${:.2}",
+                id, order.status, sale_tax.gross, sale_tax.total_tax(), total, total - deposit_paid
             );
-            println!("{}", "-".repeat(60));
-            for transaction in transactions {
-                println!(
-                    "{:<4} | {:<12} | {:<10} | ${:>7.2} | {}",
-                    transaction.id, transaction.date, transaction.transaction_type, transaction.amount, transaction.description
-                )
+            if deposit_paid > 0.0 {
+                let mut account = prompt("Deposit account (default Operating): ");
+                if account.is_empty() {
+                    account = "Operating".to_string();
+                }
+                let category = prompt_category(conn, Some("Sales"));
+                match add_order_deposit(conn, id, deposit_paid, &order.created_date, &account, category.as_deref()) {
+                    Ok(_) => println!("✅ Recorded ${:.2} deposit.", deposit_paid),
+                    Err(e) => println!("❌ Failed to record deposit: {}", e),
+                }
+            }
+        }
+        Err(e) => println!("❌ Failed to create order: {}", e),
+    }
+    wait_for_enter();
+}
+
+// Show an order with its products and balance
+fn handle_order_details(conn: &Connection) {
+    let order = match prompt_order(conn) {
+        Some(order) => order,
+        None => {
+            wait_for_enter();
+            return;
+        }
+    };
+    let items = get_order_items(conn, order.id).expect("Error fetching order items");
+    let totals = order_total(conn, &order);
+    let total = totals.total();
+
+    println!("\n🧁 Order #{} — {}", order.id, order.customer_name);
+    if let Some(contact) = &order.customer_contact {
+        println!("Contact: {}", contact);
+    }
+    println!(
+        "Due: {} {} ({})",
+        order.due_date, order.due_time.as_deref().unwrap_or(""), order.fulfilment
+    );
+    println!("Status: {}", order.status);
+    println!("Created: {}", order.created_date);
+    println!("\n{:<28} | {:>5} | {:>9} | {:>9}", "Product", "Qty", "Price", "Total");
+    println!("{}", "-".repeat(60));
+    for item in &items {
+        println!(
+            "{:<28} | {:>5} | ${:>8.2} | ${:>8.2}",
+            item.recipe_name, item.quantity, item.unit_price, item.quantity as f32 * item.unit_price
+        );
+    }
+    println!("{}", "-".repeat(60));
+    println!("Subtotal:     ${:.2}", totals.gross);
+    println!("Sales tax:    ${:.2}", totals.total_tax());
+    println!("Order total:  ${:.2}", total);
+    println!("Deposit paid: ${:.2}", order.deposit_paid);
+    println!("Balance due:  ${:.2}", total - order.deposit_paid);
+    if let Some(notes) = &order.notes {
+        println!("Notes: {}", notes);
+    }
+    if let Some(transaction_id) = order.sale_transaction_id {
+        println!("Sale transaction: {}", transaction_id);
+    }
+
+    wait_for_enter();
+}
+
+// Fulfil an order: post the sale and consume finished goods
+fn handle_fulfil_order(conn: &Connection) {
+    let order = match prompt_order(conn) {
+        Some(order) => order,
+        None => {
+            wait_for_enter();
+            return;
+        }
+    };
+
+    if order.status == "fulfilled" || order.status == "cancelled" {
+        println!("⚠️ Order #{} is already {}.", order.id, order.status);
+        wait_for_enter();
+        return;
+    }
+
+    // Warn about any products we don't have enough finished stock for
+    let items = get_order_items(conn, order.id).expect("Error fetching order items");
+    let goods = get_finished_goods(conn).expect("Error fetching finished goods");
+    let mut short = false;
+    for item in &items {
+        let on_hand = goods
+            .iter()
+            .find(|(recipe_id, _, _)| *recipe_id == item.recipe_id)
+            .map(|(_, _, qty)| *qty)
+            .unwrap_or(0);
+        if on_hand < item.quantity {
+            short = true;
+            println!("⚠️ {}: need {}, only {} finished on hand", item.recipe_name, item.quantity, on_hand);
+        }
+    }
+    if short && !prompt("Fulfil anyway? (y/N): ").eq_ignore_ascii_case("y") {
+        println!("❌ Fulfilment cancelled.");
+        wait_for_enter();
+        return;
+    }
+
+    let mut date = prompt("Sale date (YYYY-MM-DD, default today): ");
+    if date.is_empty() {
+        date = Local::now().format("%Y-%m-%d").to_string();
+    }
+    let mut account = prompt("Account (default Operating): ");
+    if account.is_empty() {
+        account = "Operating".to_string();
+    }
+    let category = prompt_category(conn, Some("Sales"));
+
+    match fulfil_order(conn, order.id, &date, &account, category.as_deref()) {
+        Ok(Some(transaction_id)) => {
+            println!("✅ Order #{} fulfilled; sale transaction {} recorded.", order.id, transaction_id);
+            if order.deposit_paid > 0.0 {
+                println!("💡 Deposits already posted for this order were netted out of the sale.");
+            }
+        }
+        Ok(None) => println!("⚠️ Order #{} was already fulfilled or cancelled.", order.id),
+        Err(e) => println!("❌ Failed to fulfil order: {}", e),
+    }
+    wait_for_enter();
+}
+
+// Customers & Orders Menu
+pub fn handle_order_menu(conn: &Connection) {
+    println!("🧁 Customers & Orders");
+    println!("1. View Open Orders");
+    println!("2. View All Orders");
+    println!("3. New Order");
+    println!("4. View Order Details");
+    println!("5. Update Order Status");
+    println!("6. Record Deposit");
+    println!("7. Fulfil Order");
+    println!("8. View Customers");
+    println!("9. Add Customer");
+    println!("10. Edit Customer");
+    println!("11. Customer History");
+    println!("12. Price Tiers");
+    println!("100. Exit");
+
+    let choice = prompt("Choose an option: ");
+
+    match choice.as_str() {
+        // View Open Orders / View All Orders
+        "1" | "2" => {
+            let orders = get_orders(conn, choice == "1").expect("Error fetching orders");
+            if orders.is_empty() {
+                println!("⚠️ No orders found.");
+            } else {
+                println!("\n🧁 Orders:");
+                for order in &orders {
+                    print_order_summary(order);
+                }
             }
             // Pause app and wait for user input
             wait_for_enter();
         }
-        // Filter Transactions
+        // New Order
         "3" => {
-            // Filter by type or date
-            let mut input = String::new();
-            print!("1. Filter by transaction type\n2. Filter by date\n");
-            io::stdout().flush().unwrap();
-            io::stdin().read_line(&mut input).unwrap();
+            handle_new_order(conn);
+        }
+        // View Order Details
+        "4" => {
+            handle_order_details(conn);
+        }
+        // Update Order Status
+        "5" => {
+            if let Some(order) = prompt_order(conn) {
+                println!("Current status: {}", order.status);
+                // Fulfilment goes through its own option so the sale gets posted
+                for (i, status) in ORDER_STATUSES.iter().enumerate().filter(|(_, s)| **s != "fulfilled") {
+                    println!("{}. {}", i + 1, status);
+                }
+                let index: usize = prompt("New status: ").parse().unwrap_or(0);
 
-            match input.trim() {
-                "1" => {
-                    let mut query = String::new();
-                    print!("Transaction type (sale/expense): ");
-                    io::stdout().flush().unwrap();
-                    io::stdin().read_line(&mut query).unwrap();
+                match ORDER_STATUSES.get(index.wrapping_sub(1)) {
+                    Some(&"fulfilled") => println!("⚠️ Use Fulfil Order to post the sale."),
+                    Some(status) => match update_order_status(conn, order.id, status) {
+                        Ok(true) => println!("✅ Order #{} is now {}.", order.id, status),
+                        Ok(false) => println!("⚠️ Order #{} is {} and can't be changed.", order.id, order.status),
+                        Err(e) => println!("❌ Failed to update order: {}", e),
+                    },
+                    None => println!("❌ Invalid status."),
+                }
+            }
+            // Pause app and wait for user input
+            wait_for_enter();
+        }
+        // Record Deposit
+        "6" => {
+            if let Some(order) = prompt_order(conn) {
+                handle_order_deposit(conn, &order);
+            }
+            // Pause app and wait for user input
+            wait_for_enter();
+        }
+        // Fulfil Order
+        "7" => {
+            handle_fulfil_order(conn);
+        }
+        // View Customers
+        "8" => {
+            let customers = get_customers(conn).expect("Error fetching customers");
+            let tiers = get_price_tiers(conn).expect("Error fetching price tiers");
+            println!("\n👥 Customers:");
+            for customer in &customers {
+                let tier = customer
+                    .price_tier_id
+                    .and_then(|id| tiers.iter().find(|t| t.id == id))
+                    .map(|t| t.name.as_str())
+                    .unwrap_or("MSRP");
+                println!(
+                    "{} - {} ({}, {} pricing{})",
+                    customer.id,
+                    customer.name,
+                    customer.customer_type,
+                    tier,
+                    if customer.tax_exempt { ", tax exempt" } else { "" }
+                );
+            }
+            // Pause app and wait for user input
+            wait_for_enter();
+        }
+        // Add Customer / Edit Customer
+        "9" | "10" => {
+            handle_customer_form(conn, choice == "10");
+        }
+        // Customer History
+        "11" => {
+            handle_customer_history(conn);
+        }
+        // Price Tiers
+        "12" => {
+            handle_price_tiers(conn);
+        }
+        // Exit Orders Menu
+        "100" => {
+            println!("👋 Exiting. Goodbye!");
+            std::process::exit(0);
+        }
+        &_ => {
+            println!("Error--Invalid option\n Returning to Main Menu...");
+        }
+    }
+}
 
-                    let transactions = transaction_filter(conn, query.trim()).expect("Error fetching transactions");
-                    println!("Transactions:");
+// Today's date as YYYY-MM-DD
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+// Prompt for invoice date, terms and tax, then save the invoice
+// lines: (recipe_id, description, quantity, unit_price)
+fn create_invoice(
+    conn: &Connection,
+    customer: &Customer,
+    order_id: Option<i32>,
+    sale_transaction_id: Option<i32>,
+    deposit_applied: f32,
+    notes: Option<String>,
+    lines: &[(Option<i32>, String, i32, f32)],
+) {
+    let mut issue_date = prompt("Issue date (YYYY-MM-DD, default today): ");
+    if issue_date.is_empty() {
+        issue_date = today();
+    }
+    let issue = match NaiveDate::parse_from_str(&issue_date, "%Y-%m-%d") {
+        Ok(date) => date,
+        Err(_) => {
+            println!("❌ Invalid date.");
+            return;
+        }
+    };
+
+    let terms_days: i64 = prompt("Payment terms in days (default 30, 0 = due on receipt): ").parse().unwrap_or(30);
+    let terms = if terms_days == 0 { "Due on receipt".to_string() } else { format!("Net {}", terms_days) };
+    let due_date = (issue + Duration::days(terms_days)).format("%Y-%m-%d").to_string();
+
+    // Bill the same tax the sale records in the tax liability, stored as an effective rate.
+    // A sale (or fulfilled order) already recorded its tax; an open order uses what fulfilment will record.
+    let order = order_id.and_then(|id| get_order(conn, id).expect("Error fetching order"));
+    let tax = match order.as_ref().and_then(|o| o.sale_transaction_id).or(sale_transaction_id) {
+        Some(transaction_id) => get_sale_tax_total(conn, transaction_id).expect("Error fetching sales tax"),
+        None => order.as_ref().map(|o| order_total(conn, o).total_tax()).unwrap_or(0.0),
+    };
+    let subtotal: f32 = lines.iter().map(|(_, _, quantity, unit_price)| *quantity as f32 * unit_price).sum();
+    let tax_rate = if subtotal > 0.0 { tax / subtotal * 100.0 } else { 0.0 };
+    println!("Sales tax: ${:.2}", tax);
+
+    let invoice = Invoice {
+        id: 0,
+        number: next_invoice_number(conn).unwrap_or(0),
+        customer_id: customer.id,
+        order_id,
+        sale_transaction_id,
+        issue_date,
+        due_date,
+        terms,
+        tax_rate,
+        deposit_applied,
+        notes,
+        voided: false,
+    };
+
+    match add_invoice(conn, &invoice, lines) {
+        Ok(id) => {
+            let details = get_invoice_details(conn, id)
+                .expect("Error fetching invoice")
+                .expect("Invoice not found");
+            println!("✅ Created {} for {} — balance ${:.2} due {}",
+                format_invoice_number(details.invoice.number), customer.name, details.balance, details.invoice.due_date);
+        }
+        Err(e) => println!("❌ Failed to create invoice: {}", e),
+    }
+}
+
+// Build an invoice from a customer order
+fn handle_invoice_from_order(conn: &Connection) {
+    let order = match prompt_order(conn) {
+        Some(order) => order,
+        None => return,
+    };
+
+    if order.status == "cancelled" {
+        println!("⚠️ Order #{} was cancelled.", order.id);
+        return;
+    }
+    if let Ok(Some(_)) = find_invoice_for_source(conn, Some(order.id), None) {
+        println!("⚠️ Order #{} has already been invoiced.", order.id);
+        return;
+    }
+
+    let customer = match order.customer_id.and_then(|id| get_customer(conn, id).ok().flatten()) {
+        Some(customer) => customer,
+        None => {
+            println!("Order #{} isn't linked to a customer in the directory.", order.id);
+            match prompt_customer(conn) {
+                Some(customer) => customer,
+                None => {
+                    println!("❌ Invoices need a customer. Add one under Customers & Orders.");
+                    return;
+                }
+            }
+        }
+    };
+
+    let lines: Vec<(Option<i32>, String, i32, f32)> = get_order_items(conn, order.id)
+        .expect("Error fetching order items")
+        .into_iter()
+        .map(|item| (Some(item.recipe_id), item.recipe_name, item.quantity, item.unit_price))
+        .collect();
+
+    create_invoice(conn, &customer, Some(order.id), None, order.deposit_paid, order.notes.clone(), &lines);
+}
+
+// Build an invoice from a recorded sale
+fn handle_invoice_from_sale(conn: &Connection) {
+    let transaction_id: i32 = prompt("Sale transaction ID: ").parse().unwrap_or(0);
+    let transaction = match get_transaction(conn, transaction_id) {
+        Ok(Some(tx)) if tx.transaction_type == "sale" => tx,
+        _ => {
+            println!("❌ Sale not found!");
+            return;
+        }
+    };
+
+    if let Ok(Some(_)) = find_invoice_for_source(conn, None, Some(transaction.id)) {
+        println!("⚠️ Sale {} has already been invoiced.", transaction.id);
+        return;
+    }
+
+    let customer = match transaction.customer_id.and_then(|id| get_customer(conn, id).ok().flatten()) {
+        Some(customer) => customer,
+        None => match prompt_customer(conn) {
+            Some(customer) => customer,
+            None => {
+                println!("❌ Invoices need a customer. Add one under Customers & Orders.");
+                return;
+            }
+        },
+    };
+
+    // Itemized sales bill per product; lump-sum sales bill as a single line
+    let recipes = get_recipe_collection(conn).expect("Error fetching recipes");
+    let items = get_sale_items(conn, transaction.id).expect("Error fetching sale items");
+    let lines: Vec<(Option<i32>, String, i32, f32)> = if items.is_empty() {
+        vec![(None, transaction.description.clone(), 1, transaction.amount)]
+    } else {
+        items
+            .iter()
+            .map(|item| {
+                let name = recipes
+                    .iter()
+                    .find(|r| r.id == item.recipe_id)
+                    .map(|r| r.name.clone())
+                    .unwrap_or_else(|| "(deleted recipe)".to_string());
+                (Some(item.recipe_id), name, item.quantity, item.unit_price)
+            })
+            .collect()
+    };
+
+    create_invoice(conn, &customer, None, Some(transaction.id), 0.0, None, &lines);
+}
+
+// Invoicing Menu
+pub fn handle_invoice_menu(conn: &Connection) {
+    println!("🧾 Invoices");
+    println!("1. View Invoices");
+    println!("2. Create Invoice from Order");
+    println!("3. Create Invoice from Sale");
+    println!("4. Record Invoice Payment");
+    println!("5. Print Invoice (HTML + Text)");
+    println!("6. A/R Aging Report");
+    println!("7. Void Invoice");
+    println!("100. Exit");
+
+    let choice = prompt("Choose an option: ");
+    let today = today();
+
+    match choice.as_str() {
+        // View Invoices
+        "1" => {
+            let invoices = get_all_invoice_details(conn).expect("Error fetching invoices");
+            if invoices.is_empty() {
+                println!("⚠️ No invoices found.");
+            } else {
+                println!(
+                    "\n{:<4} | {:<9} | {:<20} | {:<10} | {:>9} | {:>9} | Status",
+                    "ID", "Number", "Customer", "Due", "Total", "Balance"
+                );
+                println!("{}", "-".repeat(85));
+                for details in &invoices {
                     println!(
-                        "\n{:<4} | {:<12} | {:<10} | {:>8} | {}",
-                        "ID", "Date", "Type", "Amount", "Description"
+                        "{:<4} | {:<9} | {:<20} | {:<10} | ${:>8.2} | ${:>8.2} | {}",
+                        details.invoice.id,
+                        format_invoice_number(details.invoice.number),
+                        details.customer.name,
+                        details.invoice.due_date,
+                        details.total,
+                        details.balance,
+                        details.status(&today)
                     );
-                    println!("{}", "-".repeat(60));
-                    for transaction in transactions {
-                        println!(
-                            "{:<4} | {:<12} | {:<10} | ${:>7.2} | {}",
-                            transaction.id, transaction.date, transaction.transaction_type, transaction.amount, transaction.description
-                        )
+                }
+            }
+        }
+        // Create Invoice from Order
+        "2" => {
+            handle_invoice_from_order(conn);
+        }
+        // Create Invoice from Sale
+        "3" => {
+            handle_invoice_from_sale(conn);
+        }
+        // Record Invoice Payment
+        "4" => {
+            let invoice_id: i32 = prompt("Invoice ID: ").parse().unwrap_or(0);
+            match get_invoice_details(conn, invoice_id) {
+                Ok(Some(details)) if !details.invoice.voided => {
+                    println!("Balance due: ${:.2}", details.balance);
+                    let amount: f32 = prompt(&format!("Payment amount (default {:.2}): ", details.balance))
+                        .parse()
+                        .unwrap_or(details.balance);
+                    let mut date = prompt("Payment date (YYYY-MM-DD, default today): ");
+                    if date.is_empty() {
+                        date = today.clone();
+                    }
+                    let mut account = prompt("Account (default Operating): ");
+                    if account.is_empty() {
+                        account = "Operating".to_string();
+                    }
+
+                    match record_invoice_payment(conn, invoice_id, &date, amount, &account) {
+                        Ok(_) => println!("✅ Recorded ${:.2} payment; balance now ${:.2}", amount, details.balance - amount),
+                        Err(e) => println!("❌ Failed to record payment: {}", e),
                     }
-                    // Pause app and wait for user input
-                    wait_for_enter();
                 }
-                "2" => {
-                        let mut date = String::new();
-                        print!("Date (YYYY-MM-DD): ");
-                        io::stdout().flush().unwrap();
-                        io::stdin().read_line(&mut date).unwrap();
-                        
-                        let transactions = filter_by_date(conn, date.trim()).expect("Error fetching transactions");
-                        println!("Transactions:");
-                        println!(
-                            "\n{:<4} | {:<12} | {:<10} | {:>8} | {}",
-                            "ID", "Date", "Type", "Amount", "Description"
-                        );
-                        println!("{}", "-".repeat(60));
-                        for transaction in transactions {
-                            println!(
-                                "{:<4} | {:<12} | {:<10} | ${:>7.2} | {}",
-                                transaction.id, transaction.date, transaction.transaction_type, transaction.amount, transaction.description
-                            )
-                        }
-                        // Pause app and wait for user input
-                        wait_for_enter();
+                Ok(Some(_)) => println!("⚠️ That invoice has been voided."),
+                _ => println!("❌ Invoice not found!"),
+            }
+        }
+        // Print Invoice
+        "5" => {
+            let invoice_id: i32 = prompt("Invoice ID: ").parse().unwrap_or(0);
+            match get_invoice_details(conn, invoice_id) {
+                Ok(Some(details)) => {
+                    println!("\n{}", render_invoice_text(&details, &today));
+                    match write_invoice_documents(&details, &today) {
+                        Ok((html_path, text_path)) => println!(
+                            "✅ Saved {} and {}", html_path.display(), text_path.display()
+                        ),
+                        Err(e) => println!("❌ Failed to write invoice files: {}", e),
                     }
-                &_ => {
-                    println!("Error--input not accepted")
                 }
-                
-
+                _ => println!("❌ Invoice not found!"),
             }
-            // Pause app and wait for user input
-            wait_for_enter();
         }
-        // Print CSV Transaction Report
-        "4" => {
-            write_csv_transaction_report(conn).expect("Error: Failed to create report");
-
-            println!("✅ Report created successfully");
-
-            // Pause app and wait for user input
-            wait_for_enter();
+        // A/R Aging Report
+        "6" => {
+            let invoices = get_all_invoice_details(conn).expect("Error fetching invoices");
+            println!("\n{}", render_ar_aging(&invoices, &today));
         }
-        // Exit Transaction Menu
+        // Void Invoice
+        "7" => {
+            let invoice_id: i32 = prompt("Invoice ID to void: ").parse().unwrap_or(0);
+            if prompt("Type 'YES' to confirm: ") == "YES" {
+                match void_invoice(conn, invoice_id) {
+                    Ok(true) => println!("✅ Invoice voided."),
+                    Ok(false) => println!("⚠️ Invoice not found or already void."),
+                    Err(e) => println!("❌ Failed to void invoice: {}", e),
+                }
+            } else {
+                println!("❌ Void cancelled.");
+            }
+        }
+        // Exit Invoices Menu
         "100" => {
             println!("👋 Exiting. Goodbye!");
             std::process::exit(0);
         }
         &_ => {
             println!("Error--Invalid option\n Returning to Main Menu...");
+            return;
+        }
+    }
+    // Pause app and wait for user input
+    wait_for_enter();
+}
+
+// Writes one CSV report to a path
+type CsvReportWriter = fn(&Connection, &Path) -> io::Result<()>;
+
+// CSV exports for inventory, recipes and costing
+fn handle_csv_exports(conn: &Connection) {
+    println!("\n📤 Export CSV Reports");
+    println!("1. Transactions");
+    println!("2. Inventory");
+    println!("3. Recipes");
+    println!("4. Recipe Ingredient Lines (with unit cost)");
+    println!("5. Recipe Costing Summary");
+    println!("(blank to return)");
+
+    let choice = prompt("Choose an option: ");
+    let (default_name, write): (&str, CsvReportWriter) = match choice.as_str() {
+        "1" => ("transaction-report.csv", write_csv_transaction_report),
+        "2" => ("inventory.csv", write_csv_inventory_report),
+        "3" => ("recipes.csv", write_csv_recipe_report),
+        "4" => ("recipe-ingredients.csv", write_csv_recipe_ingredients_report),
+        "5" => ("recipe-costing.csv", write_csv_recipe_costing_report),
+        "" => return,
+        _ => {
+            println!("Error--Invalid option\n Returning to Main Menu...");
+            return;
         }
+    };
+
+    let path = prompt_export_path(default_name);
+    match write(conn, &path) {
+        Ok(()) => println!("✅ Report written to {}", path.display()),
+        Err(e) => println!("❌ Error: Failed to create report: {}", e),
+    }
+    wait_for_enter();
+}
+
+// Bulk-load inventory, recipes or recipe ingredient lines from CSV
+// Every row is validated first; the file is applied in one transaction or not at all
+fn handle_csv_import(conn: &Connection) {
+    println!("\n📥 Import CSV Data");
+    println!("1. Inventory (name, unit, quantity, cost_per_unit[, category])");
+    println!("2. Recipes (name, instructions, yield_quantity, category[, prep_time, bake_time, total_time, msrp_per_unit])");
+    println!("3. Recipe Ingredients (recipe_name, ingredient_name, quantity_required)");
+    println!("(blank to return)");
+
+    let choice = prompt("Choose an option: ");
+    if choice.is_empty() {
+        return;
+    }
+    if !["1", "2", "3"].contains(&choice.as_str()) {
+        println!("Error--Invalid option\n Returning to Main Menu...");
+        return;
+    }
+
+    let path = prompt("CSV file path: ");
+    let result = match choice.as_str() {
+        "1" => import_inventory_csv(conn, Path::new(&path)),
+        "2" => import_recipes_csv(conn, Path::new(&path)),
+        _ => import_recipe_ingredients_csv(conn, Path::new(&path)),
+    };
+
+    match result {
+        Ok(summary) => println!("✅ Imported {}: {} added, {} updated", path, summary.inserted, summary.updated),
+        Err(errors) => {
+            println!("❌ {} problem(s) found; nothing was imported:", errors.len());
+            for error in &errors {
+                println!("   {}", error);
+            }
+        }
+    }
+    wait_for_enter();
+}
+
+// Load a JSON archive, merging by natural key or replacing everything
+fn handle_json_import(conn: &Connection) {
+    let path = prompt("JSON file path: ");
+    println!("1. Merge (match by name / transaction details, add or update)");
+    println!("2. Replace all (delete everything, then load the archive)");
+    let mode = match prompt("Import mode: ").as_str() {
+        "1" => ImportMode::Merge,
+        "2" => ImportMode::ReplaceAll,
+        _ => {
+            println!("Error--Invalid option\n Returning to Main Menu...");
+            return;
+        }
+    };
+
+    if mode == ImportMode::ReplaceAll
+        && !prompt("⚠️ This deletes all current data. Type 'yes' to continue: ").eq_ignore_ascii_case("yes")
+    {
+        println!("❌ Import cancelled.");
+        wait_for_enter();
+        return;
+    }
+
+    match import_json(conn, Path::new(&path), mode) {
+        Ok(summary) => {
+            println!("✅ Imported {}", path);
+            for (section, count) in &summary.sections {
+                println!("   {:<28} {}", section, count);
+            }
+            for note in &summary.notes {
+                println!("⚠️ {}", note);
+            }
+        }
+        Err(e) => println!("❌ Import failed, nothing was changed: {}", e),
+    }
+    wait_for_enter();
+}
+
+// One XLSX workbook for the accountant covering a month
+fn handle_bookkeeping_workbook(conn: &Connection) {
+    let current_month = Local::now().format("%Y-%m").to_string();
+    let mut month = prompt(&format!("Month (YYYY-MM, default {}): ", current_month));
+    if month.is_empty() {
+        month = current_month;
+    }
+    let start = match NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d") {
+        Ok(start) => start,
+        Err(_) => {
+            println!("❌ Month must be in YYYY-MM format.");
+            wait_for_enter();
+            return;
+        }
+    };
+    let end = (start + Duration::days(32)).with_day(1).unwrap_or(start) - Duration::days(1);
+
+    let transactions = read_transactions_between(conn, &start.to_string(), &end.to_string()).expect("Error fetching transactions");
+    let profit_and_loss = get_budget_vs_actual(conn, &month).expect("Error building P&L");
+    let inventory = get_inventory_valuation(conn).expect("Error fetching inventory");
+    let costing = get_recipe_costing(conn).expect("Error fetching recipe costing");
+
+    let path = prompt_export_path(&format!("bookkeeping-{}.xlsx", month));
+    match write_bookkeeping_workbook(&path, &month, &transactions, &profit_and_loss, &inventory, &costing) {
+        Ok(()) => println!("✅ Bookkeeping workbook written to {} ({} transactions)", path.display(), transactions.len()),
+        Err(e) => println!("❌ Failed to write workbook: {}", e),
+    }
+    wait_for_enter();
+}
+
+// Offline HTML dashboard for a chosen reporting period
+fn handle_dashboard(conn: &Connection) {
+    let now = Local::now().date_naive();
+    let default_start = (now - Duration::days(89)).to_string();
+
+    let mut start_date = prompt(&format!("Start date (YYYY-MM-DD, default {}): ", default_start));
+    if start_date.is_empty() {
+        start_date = default_start;
+    }
+    let mut end_date = prompt(&format!("End date (YYYY-MM-DD, default {}): ", now));
+    if end_date.is_empty() {
+        end_date = now.to_string();
+    }
+    let (start, end) = match (
+        NaiveDate::parse_from_str(&start_date, "%Y-%m-%d"),
+        NaiveDate::parse_from_str(&end_date, "%Y-%m-%d"),
+    ) {
+        (Ok(start), Ok(end)) if start <= end => (start, end),
+        _ => {
+            println!("❌ Enter a valid start and end date (start before end).");
+            wait_for_enter();
+            return;
+        }
+    };
+    let min_batches: f32 = prompt("Low-stock threshold in batches (default 2): ").parse().unwrap_or(2.0);
+
+    // Daily bars for up to two months, monthly beyond that
+    let by_month = (end - start).num_days() > 62;
+    let dashboard = Dashboard {
+        revenue_expenses: get_revenue_expense_series(conn, &start_date, &end_date, by_month).expect("Error fetching transactions"),
+        product_sales: get_product_sales(conn, &start_date, &end_date, false).expect("Error fetching product sales"),
+        inventory: get_inventory_valuation(conn).expect("Error fetching inventory"),
+        low_stock: get_low_stock_items(conn, min_batches).expect("Error fetching low stock"),
+        min_batches,
+        costing: get_recipe_costing(conn).expect("Error fetching recipe costing"),
+        start_date,
+        end_date,
+        generated_at: Local::now().format("%Y-%m-%d %H:%M").to_string(),
+    };
+
+    let path = prompt_export_path("dashboard.html");
+    let written = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, render_dashboard_html(&dashboard)));
+    match written {
+        Ok(()) => println!("✅ Dashboard written to {} — open it in any browser", path.display()),
+        Err(e) => println!("❌ Failed to write dashboard: {}", e),
     }
+    wait_for_enter();
 }
 
 // Utilities Menu
@@ -877,6 +4610,12 @@ pub fn handle_utilities_menu(conn: &Connection) {
     println!("3. View System Info");
     println!("4. Run Data Integrity Check");
     println!("5. Compact (VACUUM) Database");
+    println!("6. Export CSV Reports");
+    println!("7. Import CSV Data");
+    println!("8. Export Database to JSON");
+    println!("9. Import Database from JSON");
+    println!("10. Export Monthly Bookkeeping Workbook (XLSX)");
+    println!("11. Generate HTML Dashboard");
     println!("100. Return to Main Menu");
 
     print!("Choose an option: ");
@@ -901,6 +4640,29 @@ pub fn handle_utilities_menu(conn: &Connection) {
         "5" => {
            handle_vacuum(conn);
         }
+        "6" => {
+            handle_csv_exports(conn);
+        }
+        "7" => {
+            handle_csv_import(conn);
+        }
+        "8" => {
+            let path = prompt_export_path("bakery-export.json");
+            match export_json(conn, &path) {
+                Ok(()) => println!("✅ Database exported to {} (schema version {})", path.display(), ARCHIVE_SCHEMA_VERSION),
+                Err(e) => println!("❌ Export failed: {}", e),
+            }
+            wait_for_enter();
+        }
+        "9" => {
+            handle_json_import(conn);
+        }
+        "10" => {
+            handle_bookkeeping_workbook(conn);
+        }
+        "11" => {
+            handle_dashboard(conn);
+        }
         "100" => {
             println!("Returning to Main Menu...");
             std::process::exit(0);
@@ -912,11 +4674,24 @@ pub fn handle_utilities_menu(conn: &Connection) {
 // function to display main CLI menu via main.rs
 pub fn show_main_menu(conn: &Connection) {
     println!("\n🍞 Welcome to Bakery Manager CLI 🍞");
-    
+
+    // Flag expense categories already over this month's budget
+    let month = Local::now().format("%Y-%m").to_string();
+    if let Ok(over) = get_over_budget_categories(conn, &month) {
+        for line in over {
+            println!(
+                "⚠️  {} is over budget for {}: ${:.2} spent of ${:.2}",
+                line.category, month, line.actual, line.budgeted
+            );
+        }
+    }
+
     println!("🍞 1. Inventory Management");
     println!("📖 2. Recipe Management");
     println!("💰 3. Transaction Management");
     println!("🛠 4. Utilities");
+    println!("🧁 5. Customers & Orders");
+    println!("🧾 6. Invoices");
     println!("100. Exit");
 
     print!("Choose a category: ");
@@ -930,6 +4705,8 @@ pub fn show_main_menu(conn: &Connection) {
         "2" => handle_recipe_menu(conn),
         "3" => handle_transaction_menu(conn),
         "4" => handle_utilities_menu(conn),
+        "5" => handle_order_menu(conn),
+        "6" => handle_invoice_menu(conn),
         // Exit Menu
         "100" => {
             println!("👋 Exiting. Goodbye!");
@@ -1,17 +1,44 @@
 // src/db.rs
 use rusqlite::{Connection, Result, Row, params};
 use crate::models::InventoryItem;
-use crate::models::RecipeCollection;
+use crate::models::{RecipeCollection, RecipeIngredient};
 use crate::models::Transaction;
+use crate::models::Reconciliation;
+use crate::models::{SaleItem, ProductSales};
+use crate::models::{Order, OrderItem};
+use crate::models::{Customer, PriceTier};
+use crate::models::{format_invoice_number, Invoice, InvoiceLine, InvoicePayment, InvoiceDetails};
+use crate::models::{TaxJurisdiction, SaleTax, TaxLiability};
+use crate::models::{RecurringOccurrences, RecurringTransaction};
+use crate::models::BudgetLine;
+use crate::models::{CogsLine, CogsReport};
+use crate::models::InventorySnapshot;
+use crate::models::{RecipeIngredientLine, RecipeCosting};
+use crate::models::Pan;
+use crate::models::RecipeStep;
+use crate::steps::parse_instructions;
+use crate::models::Resource;
+use crate::models::{ProductionPlan, ProductionPlanItem};
+use crate::models::ForecastEvent;
+use crate::bakers_math::{FormulaIngredient, IngredientRole, Preferment, PrefermentKind};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io;
+use std::path::Path;
 use csv::Writer;
+use serde::Serialize;
 
 pub fn connect() -> Result<Connection> {
     Connection::open("bakery.db")
 }
 
 pub fn init_db(conn: &Connection) -> Result<()> {
+    // Recipes written before structured steps existed get their instructions parsed once
+    let steps_existed: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'recipe_steps'",
+        [],
+        |row| row.get(0),
+    )?;
     conn.execute_batch(
         "
         CREATE TABLE IF NOT EXISTS inventory (
@@ -48,12 +75,369 @@ pub fn init_db(conn: &Connection) -> Result<()> {
             amount REAL NOT NULL,
             description TEXT
         );
+
+        CREATE TABLE IF NOT EXISTS reconciliations (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            account TEXT NOT NULL,
+            period_start TEXT NOT NULL,
+            period_end TEXT NOT NULL,
+            opening_balance REAL NOT NULL,
+            statement_balance REAL NOT NULL,
+            status TEXT NOT NULL DEFAULT 'open',
+            completed_at TEXT,
+            report TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS sale_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            transaction_id INTEGER NOT NULL,
+            recipe_id INTEGER NOT NULL,
+            quantity INTEGER NOT NULL,
+            unit_price REAL NOT NULL,
+            FOREIGN KEY(transaction_id) REFERENCES transactions(id),
+            FOREIGN KEY(recipe_id) REFERENCES recipes(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS production_runs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            date TEXT NOT NULL,
+            recipe_id INTEGER NOT NULL,
+            batches REAL NOT NULL,
+            units_produced INTEGER NOT NULL,
+            FOREIGN KEY(recipe_id) REFERENCES recipes(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS finished_goods (
+            recipe_id INTEGER PRIMARY KEY,
+            quantity INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY(recipe_id) REFERENCES recipes(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS orders (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            customer_name TEXT NOT NULL,
+            customer_contact TEXT,
+            due_date TEXT NOT NULL,
+            due_time TEXT,
+            fulfilment TEXT NOT NULL DEFAULT 'pickup',
+            deposit_paid REAL NOT NULL DEFAULT 0,
+            status TEXT NOT NULL DEFAULT 'quoted',
+            notes TEXT,
+            created_date TEXT NOT NULL,
+            sale_transaction_id INTEGER,
+            FOREIGN KEY(sale_transaction_id) REFERENCES transactions(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS order_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            order_id INTEGER NOT NULL,
+            recipe_id INTEGER NOT NULL,
+            quantity INTEGER NOT NULL,
+            unit_price REAL NOT NULL,
+            FOREIGN KEY(order_id) REFERENCES orders(id),
+            FOREIGN KEY(recipe_id) REFERENCES recipes(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS price_tiers (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            discount_percent REAL
+        );
+
+        CREATE TABLE IF NOT EXISTS price_tier_prices (
+            tier_id INTEGER NOT NULL,
+            recipe_id INTEGER NOT NULL,
+            unit_price REAL NOT NULL,
+            PRIMARY KEY(tier_id, recipe_id),
+            FOREIGN KEY(tier_id) REFERENCES price_tiers(id),
+            FOREIGN KEY(recipe_id) REFERENCES recipes(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS customers (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            email TEXT,
+            phone TEXT,
+            address TEXT,
+            customer_type TEXT NOT NULL DEFAULT 'retail',
+            price_tier_id INTEGER,
+            tax_exempt INTEGER NOT NULL DEFAULT 0,
+            notes TEXT,
+            FOREIGN KEY(price_tier_id) REFERENCES price_tiers(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS invoices (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            number INTEGER NOT NULL UNIQUE,
+            customer_id INTEGER NOT NULL,
+            order_id INTEGER,
+            sale_transaction_id INTEGER,
+            issue_date TEXT NOT NULL,
+            due_date TEXT NOT NULL,
+            terms TEXT NOT NULL,
+            tax_rate REAL NOT NULL DEFAULT 0,
+            deposit_applied REAL NOT NULL DEFAULT 0,
+            notes TEXT,
+            voided INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY(customer_id) REFERENCES customers(id),
+            FOREIGN KEY(order_id) REFERENCES orders(id),
+            FOREIGN KEY(sale_transaction_id) REFERENCES transactions(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS invoice_lines (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            invoice_id INTEGER NOT NULL,
+            recipe_id INTEGER,
+            description TEXT NOT NULL,
+            quantity INTEGER NOT NULL,
+            unit_price REAL NOT NULL,
+            FOREIGN KEY(invoice_id) REFERENCES invoices(id),
+            FOREIGN KEY(recipe_id) REFERENCES recipes(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS tax_jurisdictions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            level TEXT NOT NULL,
+            rate REAL NOT NULL,
+            active INTEGER NOT NULL DEFAULT 1
+        );
+
+        CREATE TABLE IF NOT EXISTS category_tax_rules (
+            category TEXT PRIMARY KEY,
+            taxable INTEGER NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS sale_taxes (
+            transaction_id INTEGER NOT NULL,
+            jurisdiction_id INTEGER NOT NULL,
+            gross_amount REAL NOT NULL,
+            taxable_amount REAL NOT NULL,
+            tax_amount REAL NOT NULL,
+            PRIMARY KEY(transaction_id, jurisdiction_id),
+            FOREIGN KEY(transaction_id) REFERENCES transactions(id),
+            FOREIGN KEY(jurisdiction_id) REFERENCES tax_jurisdictions(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS recurring_transactions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            description TEXT NOT NULL,
+            transaction_type TEXT NOT NULL,
+            amount REAL NOT NULL,
+            is_estimate INTEGER NOT NULL DEFAULT 0,
+            account TEXT NOT NULL DEFAULT 'Operating',
+            frequency TEXT NOT NULL,
+            interval INTEGER NOT NULL DEFAULT 1,
+            day_of_month INTEGER,
+            start_date TEXT NOT NULL,
+            end_date TEXT,
+            last_posted_date TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS budgets (
+            month TEXT NOT NULL,
+            category TEXT NOT NULL,
+            budget_type TEXT NOT NULL,
+            amount REAL NOT NULL,
+            PRIMARY KEY(month, category, budget_type)
+        );
+
+        CREATE TABLE IF NOT EXISTS inventory_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            snapshot_date TEXT NOT NULL,
+            label TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS inventory_snapshot_items (
+            snapshot_id INTEGER NOT NULL,
+            inventory_id INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            unit TEXT NOT NULL,
+            category TEXT,
+            quantity REAL NOT NULL,
+            cost_per_unit REAL NOT NULL,
+            PRIMARY KEY(snapshot_id, inventory_id),
+            FOREIGN KEY(snapshot_id) REFERENCES inventory_snapshots(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS pans (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            shape TEXT NOT NULL,
+            length_in REAL,
+            width_in REAL,
+            diameter_in REAL,
+            tube_diameter_in REAL,
+            depth_in REAL,
+            volume_cups REAL
+        );
+
+        CREATE TABLE IF NOT EXISTS preferments (
+            inventory_id INTEGER PRIMARY KEY,
+            kind TEXT NOT NULL,
+            hydration REAL NOT NULL,
+            inoculation REAL NOT NULL DEFAULT 0,
+            FOREIGN KEY(inventory_id) REFERENCES inventory(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS production_plans (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            plan_date TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'draft',
+            notes TEXT,
+            posted_date TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS production_plan_items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            plan_id INTEGER NOT NULL,
+            recipe_id INTEGER NOT NULL,
+            batches REAL NOT NULL,
+            FOREIGN KEY(plan_id) REFERENCES production_plans(id),
+            FOREIGN KEY(recipe_id) REFERENCES recipes(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS forecast_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            event_date TEXT NOT NULL,
+            name TEXT NOT NULL,
+            recipe_id INTEGER,
+            adjust_percent REAL NOT NULL,
+            FOREIGN KEY(recipe_id) REFERENCES recipes(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS resources (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            step_type TEXT NOT NULL,
+            capacity INTEGER NOT NULL DEFAULT 1
+        );
+
+        CREATE TABLE IF NOT EXISTS recipe_steps (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            recipe_id INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            step_type TEXT NOT NULL,
+            description TEXT NOT NULL,
+            min_minutes INTEGER,
+            max_minutes INTEGER,
+            temperature_f REAL,
+            notes TEXT,
+            FOREIGN KEY(recipe_id) REFERENCES recipes(id)
+        );
         "
     )?;
     conn.execute(
         "ALTER TABLE recipes ADD COLUMN msrp_per_unit REAL",
         [],
     ).ok();
+    // Per-recipe dough temperature settings (°C)
+    conn.execute(
+        "ALTER TABLE recipes ADD COLUMN target_ddt_c REAL",
+        [],
+    ).ok();
+    conn.execute(
+        "ALTER TABLE recipes ADD COLUMN friction_factor_c REAL",
+        [],
+    ).ok();
+    // Pan each recipe was written for
+    conn.execute(
+        "ALTER TABLE recipes ADD COLUMN pan_id INTEGER REFERENCES pans(id)",
+        [],
+    ).ok();
+    // Reconciliation columns on transactions
+    conn.execute(
+        "ALTER TABLE transactions ADD COLUMN account TEXT NOT NULL DEFAULT 'Operating'",
+        [],
+    ).ok();
+    conn.execute(
+        "ALTER TABLE transactions ADD COLUMN cleared INTEGER NOT NULL DEFAULT 0",
+        [],
+    ).ok();
+    conn.execute(
+        "ALTER TABLE transactions ADD COLUMN locked INTEGER NOT NULL DEFAULT 0",
+        [],
+    ).ok();
+    conn.execute(
+        "ALTER TABLE transactions ADD COLUMN reconciliation_id INTEGER REFERENCES reconciliations(id)",
+        [],
+    ).ok();
+    // Customer links
+    conn.execute(
+        "ALTER TABLE transactions ADD COLUMN customer_id INTEGER REFERENCES customers(id)",
+        [],
+    ).ok();
+    // Deposits are posted to the ledger against their order
+    conn.execute(
+        "ALTER TABLE transactions ADD COLUMN order_id INTEGER REFERENCES orders(id)",
+        [],
+    ).ok();
+    conn.execute(
+        "ALTER TABLE orders ADD COLUMN customer_id INTEGER REFERENCES customers(id)",
+        [],
+    ).ok();
+    // Payments received against invoices
+    conn.execute(
+        "ALTER TABLE transactions ADD COLUMN invoice_id INTEGER REFERENCES invoices(id)",
+        [],
+    ).ok();
+    // Transactions posted from a recurring template
+    conn.execute(
+        "ALTER TABLE transactions ADD COLUMN recurring_id INTEGER REFERENCES recurring_transactions(id)",
+        [],
+    ).ok();
+    // Budget categories
+    conn.execute(
+        "ALTER TABLE transactions ADD COLUMN category TEXT",
+        [],
+    ).ok();
+    conn.execute(
+        "ALTER TABLE recurring_transactions ADD COLUMN category TEXT",
+        [],
+    ).ok();
+    // Ingredient cost per batch at the time of production, for COGS
+    conn.execute(
+        "ALTER TABLE production_runs ADD COLUMN batch_cost REAL",
+        [],
+    ).ok();
+    // Production plan a run was posted from
+    conn.execute(
+        "ALTER TABLE production_runs ADD COLUMN plan_id INTEGER REFERENCES production_plans(id)",
+        [],
+    ).ok();
+    // Inventory categories for valuation
+    conn.execute(
+        "ALTER TABLE inventory ADD COLUMN category TEXT",
+        [],
+    ).ok();
+    // Ingredient roles for baker's math; the first time the column is added,
+    // existing items get a starting guess from their names
+    let role_added = conn.execute(
+        "ALTER TABLE inventory ADD COLUMN role TEXT",
+        [],
+    ).is_ok();
+    if role_added {
+        conn.execute(
+            "UPDATE inventory SET role = CASE
+                WHEN lower(name) LIKE '%flour%' THEN 'flour'
+                WHEN lower(name) LIKE '%starter%' OR lower(name) LIKE '%levain%' THEN 'preferment'
+                WHEN lower(name) LIKE '%water%' OR lower(name) LIKE '%juice%' OR lower(name) LIKE 'egg%'
+                    OR (lower(name) LIKE '%milk%' AND lower(name) NOT LIKE '%powder%') THEN 'liquid'
+                WHEN lower(name) LIKE '%butter%' OR lower(name) LIKE '%oil%' THEN 'fat'
+                WHEN lower(name) LIKE '%salt%' THEN 'salt'
+                WHEN lower(name) LIKE '%yeast%' OR lower(name) LIKE '%baking powder%'
+                    OR lower(name) LIKE '%baking soda%' THEN 'leavener'
+                WHEN lower(name) LIKE '%sugar%' OR lower(name) LIKE '%honey%' THEN 'sugar'
+             END
+             WHERE role IS NULL",
+            [],
+        )?;
+    }
+    if !steps_existed {
+        for recipe in get_recipe_collection(conn)? {
+            replace_recipe_steps(conn, recipe.id, &recipe_steps_from_instructions(&recipe))?;
+        }
+    }
 
     Ok(())    
     
@@ -62,75 +446,83 @@ pub fn init_db(conn: &Connection) -> Result<()> {
 // seed inventory
 pub fn seed_inventory(conn: &Connection) -> Result<()> {
     let sample_inventory = vec![
-        ("Flour, Bread", "kg", 15.8, 1.87),
-        ("Sugar, Organic Granulated", "kg", 9.5, 1.98),
-        ("Butter", "grams", 20.0, 3.25),
-        ("Eggs", "each", 15.0, 1.20),
-        ("Vanilla", "ml", 118.0, 0.09),
-        ("Butter, Unsalted", "grams", 907.18, 0.0138),
-        ("Sugar, Light Brown", "kg", 3.175, 2.20),
-        ("Flour, Whole Wheat", "kg", 2.268, 2.55),
-        ("Flour, Organic Rye", "kg", 1.361, 8.81),
-        ("Salt, Maldon Flaked Sea", "grams", 567.0, 0.0123),
-        ("Olive Oil, Organic Extra Virgin", "ml", 1998.0, 0.0075),
-        ("Salt, Kosher", "grams", 1360.78, 0.0022),
-        ("Flour, Unbleached All Purpose", "kg", 5.443, 1.98),
-        ("Water", "grams", 10000.0, 0.0),
-        ("Sourdough Starter", "grams", 1000.0, 0.00107),
-        ("Raisins", "grams", 500.0, 0.0077),
-        ("Cinnamon", "grams", 100.0, 0.033),
-        ("Milk Powder, Nonfat", "grams", 623.7, 0.02403),
-        ("Yeast, Instant", "grams", 113.4, 0.05811),
-        ("Honey", "grams", 566.99, 0.02646),
-        ("Vegetable Oil", "grams", 2721.55, 0.00202),
-        ("Chocolate Chips, Dark", "grams", 283.0, 0.02823),
-        ("Cocoa Powder", "grams", 226.8, 0.03082),
-        ("Espresso Powder", "grams", 100.0, 0.09490),
-        ("Confectioners' Sugar", "grams", 907.18, 0.00440),
-        ("Sesame Seeds", "grams", 60.1, 0.04975),
-        ("Orange Zest", "grams", 130.0, 0.00769),
-        ("Rosemary", "grams", 100.0, 0.0),
-        ("Cream Cheese", "grams", 170.0, 0.0),
-        ("Sour Cream", "grams", 90.0, 0.0),
-        ("Ricotta Cheese", "grams", 454.0, 0.0),
-        ("Lemon Zest", "grams", 5.0, 0.0),
-        ("Lemon Juice", "grams", 60.0, 0.0),
-        ("Turbinado Sugar", "grams", 34.0, 0.0),
-        ("Sparkling Sugar", "grams", 43.0, 0.0),
-        ("Cream of Tartar", "grams", 10.0, 0.0),
-        ("Potato Flour", "grams", 46.0, 0.0),
-        ("Dill Pickle Juice", "grams", 170.0, 0.0),
-        ("Caraway Seeds", "grams", 10.0, 0.0),
-        ("Dill Seeds", "grams", 10.0, 0.0),
-        ("Mustard Seeds", "grams", 10.0, 0.0),
-        ("Walnuts", "grams", 113.0, 0.0),
-        ("Dried Cranberries", "grams", 85.0, 0.0),
-        ("Fiori di Sicilia", "ml", 5.0, 0.0),
-        ("Vanilla Bean Paste", "ml", 5.0, 0.0),
-        ("Pizza Flour, 00", "grams", 180.0, 0.0),
-        ("Cake Flour", "grams", 180.0, 0.0),
-        ("Unbleached Cake Flour", "grams", 120.0, 0.0),
-        ("Egg Whites", "grams", 420.0, 0.0),
-        ("Baking Powder", "grams", 10.0, 0.0),
-        ("Pecans", "grams", 113.0, 0.0),
-        ("Apricot Preserves", "grams", 64.0, 0.0),
-        ("Apple", "grams", 113.0, 0.0),
-        ("Cornstarch", "grams", 14.0, 0.0),
-        ("Lime Juice", "grams", 5.0, 0.0),
-        ("Orange Juice", "grams", 15.0, 0.0),
-        ("Dried Apricots", "grams", 64.0, 0.0),
-        ("Currants", "grams", 85.0, 0.0),
-        ("Potato Flakes, Dried", "grams", 21.0, 0.0),
-        ("Egg White", "each", 1.0, 0.0)
+        ("Flour, Bread", "kg", 15.8, 1.87, "Flour & Grains", Some("flour")),
+        ("Sugar, Organic Granulated", "kg", 9.5, 1.98, "Sweeteners", Some("sugar")),
+        ("Butter", "grams", 20.0, 3.25, "Dairy & Eggs", Some("fat")),
+        ("Eggs", "each", 15.0, 1.20, "Dairy & Eggs", Some("liquid")),
+        ("Vanilla", "ml", 118.0, 0.09, "Flavorings", None),
+        ("Butter, Unsalted", "grams", 907.18, 0.0138, "Dairy & Eggs", Some("fat")),
+        ("Sugar, Light Brown", "kg", 3.175, 2.20, "Sweeteners", Some("sugar")),
+        ("Flour, Whole Wheat", "kg", 2.268, 2.55, "Flour & Grains", Some("flour")),
+        ("Flour, Organic Rye", "kg", 1.361, 8.81, "Flour & Grains", Some("flour")),
+        ("Salt, Maldon Flaked Sea", "grams", 567.0, 0.0123, "Salt & Spices", Some("salt")),
+        ("Olive Oil, Organic Extra Virgin", "ml", 1998.0, 0.0075, "Fats & Oils", Some("fat")),
+        ("Salt, Kosher", "grams", 1360.78, 0.0022, "Salt & Spices", Some("salt")),
+        ("Flour, Unbleached All Purpose", "kg", 5.443, 1.98, "Flour & Grains", Some("flour")),
+        ("Water", "grams", 10000.0, 0.0, "Pantry", Some("liquid")),
+        ("Sourdough Starter", "grams", 1000.0, 0.00107, "Leaveners", Some("preferment")),
+        ("Raisins", "grams", 500.0, 0.0077, "Fruit & Nuts", None),
+        ("Cinnamon", "grams", 100.0, 0.033, "Salt & Spices", None),
+        ("Milk Powder, Nonfat", "grams", 623.7, 0.02403, "Dairy & Eggs", None),
+        ("Yeast, Instant", "grams", 113.4, 0.05811, "Leaveners", Some("leavener")),
+        ("Honey", "grams", 566.99, 0.02646, "Sweeteners", Some("sugar")),
+        ("Vegetable Oil", "grams", 2721.55, 0.00202, "Fats & Oils", Some("fat")),
+        ("Chocolate Chips, Dark", "grams", 283.0, 0.02823, "Fruit & Nuts", None),
+        ("Cocoa Powder", "grams", 226.8, 0.03082, "Salt & Spices", None),
+        ("Espresso Powder", "grams", 100.0, 0.09490, "Salt & Spices", None),
+        ("Confectioners' Sugar", "grams", 907.18, 0.00440, "Sweeteners", Some("sugar")),
+        ("Sesame Seeds", "grams", 60.1, 0.04975, "Salt & Spices", None),
+        ("Orange Zest", "grams", 130.0, 0.00769, "Fruit & Nuts", None),
+        ("Rosemary", "grams", 100.0, 0.0, "Salt & Spices", None),
+        ("Cream Cheese", "grams", 170.0, 0.0, "Dairy & Eggs", None),
+        ("Sour Cream", "grams", 90.0, 0.0, "Dairy & Eggs", None),
+        ("Ricotta Cheese", "grams", 454.0, 0.0, "Dairy & Eggs", None),
+        ("Lemon Zest", "grams", 5.0, 0.0, "Fruit & Nuts", None),
+        ("Lemon Juice", "grams", 60.0, 0.0, "Fruit & Nuts", Some("liquid")),
+        ("Turbinado Sugar", "grams", 34.0, 0.0, "Sweeteners", Some("sugar")),
+        ("Sparkling Sugar", "grams", 43.0, 0.0, "Sweeteners", Some("sugar")),
+        ("Cream of Tartar", "grams", 10.0, 0.0, "Leaveners", None),
+        ("Potato Flour", "grams", 46.0, 0.0, "Flour & Grains", Some("flour")),
+        ("Dill Pickle Juice", "grams", 170.0, 0.0, "Pantry", Some("liquid")),
+        ("Caraway Seeds", "grams", 10.0, 0.0, "Salt & Spices", None),
+        ("Dill Seeds", "grams", 10.0, 0.0, "Salt & Spices", None),
+        ("Mustard Seeds", "grams", 10.0, 0.0, "Salt & Spices", None),
+        ("Walnuts", "grams", 113.0, 0.0, "Fruit & Nuts", None),
+        ("Dried Cranberries", "grams", 85.0, 0.0, "Fruit & Nuts", None),
+        ("Fiori di Sicilia", "ml", 5.0, 0.0, "Flavorings", None),
+        ("Vanilla Bean Paste", "ml", 5.0, 0.0, "Flavorings", None),
+        ("Pizza Flour, 00", "grams", 180.0, 0.0, "Flour & Grains", Some("flour")),
+        ("Cake Flour", "grams", 180.0, 0.0, "Flour & Grains", Some("flour")),
+        ("Unbleached Cake Flour", "grams", 120.0, 0.0, "Flour & Grains", Some("flour")),
+        ("Egg Whites", "grams", 420.0, 0.0, "Dairy & Eggs", Some("liquid")),
+        ("Baking Powder", "grams", 10.0, 0.0, "Leaveners", Some("leavener")),
+        ("Pecans", "grams", 113.0, 0.0, "Fruit & Nuts", None),
+        ("Apricot Preserves", "grams", 64.0, 0.0, "Sweeteners", None),
+        ("Apple", "grams", 113.0, 0.0, "Fruit & Nuts", None),
+        ("Cornstarch", "grams", 14.0, 0.0, "Flour & Grains", None),
+        ("Lime Juice", "grams", 5.0, 0.0, "Fruit & Nuts", Some("liquid")),
+        ("Orange Juice", "grams", 15.0, 0.0, "Fruit & Nuts", Some("liquid")),
+        ("Dried Apricots", "grams", 64.0, 0.0, "Fruit & Nuts", None),
+        ("Currants", "grams", 85.0, 0.0, "Fruit & Nuts", None),
+        ("Potato Flakes, Dried", "grams", 21.0, 0.0, "Flour & Grains", None),
+        ("Egg White", "each", 1.0, 0.0, "Dairy & Eggs", Some("liquid")),
+        ("Yeast, Active Dry", "grams", 0.0, 0.0, "Leaveners", Some("leavener")),
+        ("Yeast, Fresh", "grams", 0.0, 0.0, "Leaveners", Some("leavener"))
     ];
 
-    for (name, unit, quantity, cost_per_unit) in sample_inventory {
+    for (name, unit, quantity, cost_per_unit, category, role) in sample_inventory {
         conn.execute(
-            "INSERT INTO inventory (name, unit, quantity, cost_per_unit)
-             VALUES (?1, ?2, ?3, ?4)",
-            params![name, unit, quantity, cost_per_unit],
+            "INSERT INTO inventory (name, unit, quantity, cost_per_unit, category, role)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![name, unit, quantity, cost_per_unit, category, role],
         )?;
     }
+    // The house starter is a 100% hydration levain
+    conn.execute(
+        "INSERT INTO preferments (inventory_id, kind, hydration, inoculation)
+         SELECT id, 'levain', 100.0, 20.0 FROM inventory WHERE name = 'Sourdough Starter'",
+        [],
+    )?;
 
     println!("✅ Sample inventory seeded");
     Ok(())
@@ -162,6 +554,8 @@ pub fn seed_recipes(conn: &Connection) -> Result<()> {
              VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?)",
             params![name, instructions, yield_quantity, category, prep_time, bake_time, total_time],
         )?;
+        let steps = parse_instructions(instructions, Some(prep_time), Some(bake_time), Some(total_time));
+        replace_recipe_steps(conn, conn.last_insert_rowid() as i32, &steps)?;
     }
 
     println!("✅ Sample recipes seeded");
@@ -171,22 +565,22 @@ pub fn seed_recipes(conn: &Connection) -> Result<()> {
 // Seed transaction table
 pub fn seed_transactions(conn: &Connection) -> Result<()> {
     let transactions = vec![
-        ("2025-04-01", "sale", 125.50, "Morning pastry sales"),
-        ("2025-04-01", "expense", 42.00, "Purchased 50 lbs of flour"),
-        ("2025-04-02", "sale", 98.25, "Coffee + croissant combo special"),
-        ("2025-04-02", "expense", 28.75, "Eggs and butter from supplier"),
-        ("2025-04-03", "sale", 145.00, "Custom catering order for local office"),
-        ("2025-04-03", "expense", 12.99, "Vanilla bean restock"),
-        ("2025-04-04", "sale", 162.30, "Saturday morning rush sales"),
-        ("2025-04-05", "expense", 80.00, "Marketing design for new packaging"),
-        ("2025-04-05", "sale", 73.40, "Farmer's Market pastries"),
-        ("2025-04-06", "sale", 84.15, "Sunday brunch box orders"),
+        ("2025-04-01", "sale", 125.50, "Morning pastry sales", "Retail Sales"),
+        ("2025-04-01", "expense", 42.00, "Purchased 50 lbs of flour", "Ingredients"),
+        ("2025-04-02", "sale", 98.25, "Coffee + croissant combo special", "Retail Sales"),
+        ("2025-04-02", "expense", 28.75, "Eggs and butter from supplier", "Ingredients"),
+        ("2025-04-03", "sale", 145.00, "Custom catering order for local office", "Catering"),
+        ("2025-04-03", "expense", 12.99, "Vanilla bean restock", "Ingredients"),
+        ("2025-04-04", "sale", 162.30, "Saturday morning rush sales", "Retail Sales"),
+        ("2025-04-05", "expense", 80.00, "Marketing design for new packaging", "Marketing"),
+        ("2025-04-05", "sale", 73.40, "Farmer's Market pastries", "Market Sales"),
+        ("2025-04-06", "sale", 84.15, "Sunday brunch box orders", "Retail Sales"),
     ];
 
-    for (date, tx_type, amount, description) in transactions {
+    for (date, tx_type, amount, description, category) in transactions {
         conn.execute(
-            "INSERT INTO transactions (date, transaction_type, amount, description) VALUES (?1, ?2, ?3, ?4)",
-            rusqlite::params![date, tx_type, amount, description],
+            "INSERT INTO transactions (date, transaction_type, amount, description, category) VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![date, tx_type, amount, description, category],
         )?;
     }
 
@@ -247,7 +641,7 @@ pub fn should_seed(conn: &Connection) -> bool {
 // Read inventory
 pub fn get_all_inventory(conn: &Connection) -> Result<Vec<InventoryItem>> {
     let mut stmt = conn.prepare(
-        "SELECT id, name, unit, quantity, cost_per_unit FROM inventory"
+        "SELECT id, name, unit, quantity, cost_per_unit, category, role FROM inventory"
     )?;
 
     let inventory_iter = stmt.query_map([], |row: &Row| {
@@ -257,6 +651,8 @@ pub fn get_all_inventory(conn: &Connection) -> Result<Vec<InventoryItem>> {
             unit: row.get(2)?,
             quantity: row.get(3)?,
             cost_per_unit: row.get(4)?,
+            category: row.get(5)?,
+            role: row.get(6)?,
         })
     })?;
 
@@ -271,7 +667,9 @@ pub fn get_all_inventory(conn: &Connection) -> Result<Vec<InventoryItem>> {
 // Read recipes
 pub fn get_recipe_collection(conn: &Connection) -> Result<Vec<RecipeCollection>> {
     let mut stmt = conn.prepare(
-        "SELECT id, name, instructions, yield_quantity, category, prep_time, bake_time, total_time, msrp_per_unit FROM recipes"
+        "SELECT id, name, instructions, yield_quantity, category, prep_time, bake_time, total_time, msrp_per_unit,
+                target_ddt_c, friction_factor_c, pan_id
+         FROM recipes"
     )?;
 
     let recipe_iter = stmt.query_map([], |row: &Row| {
@@ -285,8 +683,9 @@ pub fn get_recipe_collection(conn: &Connection) -> Result<Vec<RecipeCollection>>
             bake_time: row.get(6)?,
             total_time: row.get(7)?,
             msrp_per_unit: row.get(8)?,
-
-
+            target_ddt_c: row.get(9)?,
+            friction_factor_c: row.get(10)?,
+            pan_id: row.get(11)?,
         })
     })?;
 
@@ -298,19 +697,36 @@ pub fn get_recipe_collection(conn: &Connection) -> Result<Vec<RecipeCollection>>
     Ok(recipes)
 }
 
+// Columns selected for every Transaction query
+const TRANSACTION_COLUMNS: &str =
+    "id, date, transaction_type, amount, description, account, cleared, locked, customer_id, invoice_id, category,
+     reconciliation_id, recurring_id, order_id";
+
+// Map a transactions row (selected with TRANSACTION_COLUMNS) into a Transaction
+fn transaction_from_row(row: &Row) -> Result<Transaction> {
+    Ok(Transaction {
+        id: row.get(0)?,
+        date: row.get(1)?,
+        transaction_type: row.get(2)?,
+        amount: row.get(3)?,
+        description: row.get(4)?,
+        account: row.get(5)?,
+        cleared: row.get(6)?,
+        locked: row.get(7)?,
+        customer_id: row.get(8)?,
+        invoice_id: row.get(9)?,
+        category: row.get(10)?,
+        reconciliation_id: row.get(11)?,
+        recurring_id: row.get(12)?,
+        order_id: row.get(13)?,
+    })
+}
+
 // Function returns all transactions
 pub fn read_transactions(conn: &Connection) -> Result<Vec<Transaction>> {
-    let mut stmt = conn.prepare("SELECT id, date, transaction_type, amount, description FROM transactions")?;
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM transactions", TRANSACTION_COLUMNS))?;
 
-    let transaction_iter = stmt.query_map([], |row: &Row| {
-        Ok(Transaction {
-            id: row.get(0)?,
-            date: row.get(1)?,
-            transaction_type: row.get(2)?,
-            amount: row.get(3)?,
-            description: row.get(4)?,
-          })
-    })?;
+    let transaction_iter = stmt.query_map([], transaction_from_row)?;
 
     let mut transactions: Vec<Transaction> = Vec::new();
     for transaction in transaction_iter{
@@ -323,20 +739,12 @@ pub fn read_transactions(conn: &Connection) -> Result<Vec<Transaction>> {
 // Function to filter transactions and return 
 pub fn transaction_filter(conn: &Connection, query: &str) ->Result<Vec<Transaction>> {
     
-    let mut stmt = conn.prepare(
-        "SELECT id, date, transaction_type, amount, description FROM transactions 
-        WHERE transaction_type = ?1")?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM transactions 
+        WHERE transaction_type = ?1", TRANSACTION_COLUMNS))?;
     
 
-    let transaction_iter = stmt.query_map([query], |row: &Row| {
-        Ok(Transaction {
-            id: row.get(0)?,
-            date: row.get(1)?,
-            transaction_type: row.get(2)?,
-            amount: row.get(3)?,
-            description: row.get(4)?,
-            })
-    })?;
+    let transaction_iter = stmt.query_map([query], transaction_from_row)?;
 
     let mut transactions: Vec<Transaction> = Vec::new();
     for transaction in transaction_iter{
@@ -349,20 +757,12 @@ pub fn transaction_filter(conn: &Connection, query: &str) ->Result<Vec<Transacti
 // Filter transactions by date
 pub fn filter_by_date(conn: &Connection, query: &str) ->Result<Vec<Transaction>> {
     
-    let mut stmt = conn.prepare(
-        "SELECT id, date, transaction_type, amount, description FROM transactions 
-        WHERE date = ?1")?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM transactions 
+        WHERE date = ?1", TRANSACTION_COLUMNS))?;
     
 
-    let transaction_iter = stmt.query_map([query], |row: &Row| {
-        Ok(Transaction {
-            id: row.get(0)?,
-            date: row.get(1)?,
-            transaction_type: row.get(2)?,
-            amount: row.get(3)?,
-            description: row.get(4)?,
-            })
-    })?;
+    let transaction_iter = stmt.query_map([query], transaction_from_row)?;
 
     let mut transactions: Vec<Transaction> = Vec::new();
     for transaction in transaction_iter{
@@ -372,6 +772,38 @@ pub fn filter_by_date(conn: &Connection, query: &str) ->Result<Vec<Transaction>>
     Ok(transactions)
 }
 
+// Transactions between two dates (inclusive), oldest first
+pub fn read_transactions_between(conn: &Connection, start_date: &str, end_date: &str) -> Result<Vec<Transaction>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM transactions
+        WHERE date BETWEEN ?1 AND ?2
+        ORDER BY date, id", TRANSACTION_COLUMNS))?;
+
+    let transaction_iter = stmt.query_map([start_date, end_date], transaction_from_row)?;
+
+    let mut transactions: Vec<Transaction> = Vec::new();
+    for transaction in transaction_iter {
+        transactions.push(transaction?);
+    }
+
+    Ok(transactions)
+}
+
+// Fetch a single transaction by id
+pub fn get_transaction(conn: &Connection, transaction_id: i32) -> Result<Option<Transaction>> {
+    let result = conn.query_row(
+        &format!("SELECT {} FROM transactions WHERE id = ?1", TRANSACTION_COLUMNS),
+        [transaction_id],
+        transaction_from_row,
+    );
+
+    match result {
+        Ok(tx) => Ok(Some(tx)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
 // Function to add an item to inventory
 pub fn add_inventory_item(
         conn: &Connection,
@@ -379,148 +811,632 @@ pub fn add_inventory_item(
         unit: &str,
         quantity: f32,
         cost_per_unit: f32,
+        category: Option<&str>,
+        role: Option<&str>,
     ) -> Result<()> {
-        conn.execute("INSERT INTO inventory (name, unit, quantity, cost_per_unit) VALUES (?1, ?2, ?3, ?4)",
-        params![name, unit, quantity, cost_per_unit],
+        conn.execute("INSERT INTO inventory (name, unit, quantity, cost_per_unit, category, role) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![name, unit, quantity, cost_per_unit, category, role],
     )?;
     
     Ok(())
 }
 
-// Function to update an inventory quantity
-pub fn update_inventory_quantity(conn: &Connection, item_id: i32, updated_quantity: f32) -> Result<()> {
-    conn.execute("UPDATE inventory SET quantity = ?1 WHERE id = ?2",
-    params![updated_quantity, item_id],
+// Function to update an inventory item's category
+pub fn update_inventory_category(conn: &Connection, item_id: i32, category: Option<&str>) -> Result<()> {
+    conn.execute("UPDATE inventory SET category = ?1 WHERE id = ?2",
+        params![category, item_id],
     )?;
 
     Ok(())
 }
 
-// Function to update inventory cost_per_unit
-pub fn update_inventory_cost(conn: &Connection, item_id: i32, updated_cost: f32) -> Result<()> {
-    conn.execute("UPDATE inventory SET cost_per_unit = ?1 WHERE id = ?2",
-    params![updated_cost, item_id],
+// Function to update an inventory item's baker's math role
+pub fn update_inventory_role(conn: &Connection, item_id: i32, role: Option<&str>) -> Result<()> {
+    conn.execute("UPDATE inventory SET role = ?1 WHERE id = ?2",
+        params![role, item_id],
     )?;
 
     Ok(())
-
 }
 
-// Function to add transaction to database
-pub fn add_transaction(
-    conn: &Connection,
-    date: &str,
-    transaction_type: &str,
-    amount: f32,
-    description: &str,
-    ) -> Result<()> {
-    conn.execute("INSERT INTO transactions (date, transaction_type, amount, description) VALUES (?1, ?2, ?3, ?4)",
-    params![date, transaction_type, amount, description],
+// Record how a preferment item is built; also tags the item with the preferment role
+pub fn set_preferment(conn: &Connection, item_id: i32, preferment: &Preferment) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "INSERT INTO preferments (inventory_id, kind, hydration, inoculation) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(inventory_id) DO UPDATE SET kind = ?2, hydration = ?3, inoculation = ?4",
+        params![item_id, preferment.kind.as_str(), preferment.hydration, preferment.inoculation],
     )?;
-    
+    tx.execute("UPDATE inventory SET role = 'preferment' WHERE id = ?1", [item_id])?;
+    tx.commit()?;
+
     Ok(())
 }
 
-pub fn get_ingredients_for_recipe(conn: &Connection, recipe_id: i32) -> Result<Vec<(String, f32, String)>> {
-    let mut stmt = conn.prepare(
-        "SELECT i.name, ri.quantity_required, i.unit
-         FROM recipe_ingredients ri
-         JOIN inventory i ON ri.ingredient_id = i.id
-         WHERE ri.recipe_id = ?1"
-    )?;
+// Recorded preferment details for an inventory item
+pub fn get_preferment(conn: &Connection, item_id: i32) -> Result<Option<Preferment>> {
+    let result = conn.query_row(
+        "SELECT kind, hydration, inoculation FROM preferments WHERE inventory_id = ?1",
+        [item_id],
+        |row| Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?)),
+    );
+
+    match result {
+        Ok((kind, hydration, inoculation)) => Ok(PrefermentKind::parse(&kind)
+            .map(|kind| Preferment { kind, hydration, inoculation })),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
 
-    let rows = stmt.query_map([recipe_id], |row| {
-        Ok((
-            row.get(0)?, // name
-            row.get(1)?, // quantity_required
-            row.get(2)?, // unit
-        ))
-    })?;
+// Standard pan library; existing pans (matched by name) are left alone
+pub fn seed_pans(conn: &Connection) -> Result<()> {
+    let pans = vec![
+        ("9x5 Loaf Pan", "loaf", Some(9.0), Some(5.0), None, None, Some(2.75), Some(8.0)),
+        ("8.5x4.5 Loaf Pan", "loaf", Some(8.5), Some(4.5), None, None, Some(2.75), Some(6.0)),
+        ("8-inch Round", "round", None, None, Some(8.0), None, Some(2.0), None),
+        ("9-inch Round", "round", None, None, Some(9.0), None, Some(2.0), None),
+        ("8-inch Square", "square", Some(8.0), Some(8.0), None, None, Some(2.0), None),
+        ("9x13 Rectangular", "rectangular", Some(13.0), Some(9.0), None, None, Some(2.0), None),
+        ("10-inch Tube", "tube", None, None, Some(10.0), Some(3.5), Some(4.0), Some(16.0)),
+        ("10-cup Bundt", "bundt", None, None, None, None, Some(3.5), Some(10.0)),
+    ];
 
-    let mut ingredients = Vec::new();
-    for row in rows {
-        ingredients.push(row?);
+    let mut added = 0;
+    for (name, shape, length, width, diameter, tube_diameter, depth, volume) in pans {
+        added += conn.execute(
+            "INSERT OR IGNORE INTO pans (name, shape, length_in, width_in, diameter_in, tube_diameter_in, depth_in, volume_cups)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![name, shape, length, width, diameter, tube_diameter, depth, volume],
+        )?;
     }
 
-    Ok(ingredients)
+    if added > 0 {
+        println!("✅ Standard pan library seeded");
+    }
+    Ok(())
 }
 
-pub fn reset_database(conn: &Connection) -> Result<()> {
-
-    conn.execute("DELETE FROM recipe_ingredients", [])?;
-    conn.execute("DELETE FROM transactions", [])?;
-    conn.execute("DELETE FROM recipes", [])?;
-    conn.execute("DELETE FROM inventory", [])?;
-    Ok(())
+const PAN_COLUMNS: &str =
+    "id, name, shape, length_in, width_in, diameter_in, tube_diameter_in, depth_in, volume_cups";
+
+fn pan_from_row(row: &Row) -> Result<Pan> {
+    Ok(Pan {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        shape: row.get(2)?,
+        length_in: row.get(3)?,
+        width_in: row.get(4)?,
+        diameter_in: row.get(5)?,
+        tube_diameter_in: row.get(6)?,
+        depth_in: row.get(7)?,
+        volume_cups: row.get(8)?,
+    })
 }
 
-pub fn calculate_recipe_cost(conn: &Connection, recipe_id: i32) -> Result<f32> {
-    // you'll write SQL here and sum cost_per_unit * quantity_required
-    let mut stmt = conn.prepare(
-        "SELECT SUM(i.cost_per_unit * ri.quantity_required)
-        FROM recipe_ingredients ri
-        JOIN inventory i ON ri.ingredient_id = i.id
-        WHERE ri.recipe_id = ?1"
+pub fn add_pan(conn: &Connection, pan: &Pan) -> Result<i32> {
+    conn.execute(
+        "INSERT INTO pans (name, shape, length_in, width_in, diameter_in, tube_diameter_in, depth_in, volume_cups)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            pan.name, pan.shape, pan.length_in, pan.width_in, pan.diameter_in, pan.tube_diameter_in,
+            pan.depth_in, pan.volume_cups
+        ],
     )?;
 
-    let total_cost: f32 = stmt.query_row([recipe_id], |row| {row.get(0)}).unwrap_or(0.0);
+    Ok(conn.last_insert_rowid() as i32)
+}
 
+pub fn get_pans(conn: &Connection) -> Result<Vec<Pan>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM pans ORDER BY shape, name", PAN_COLUMNS))?;
+    let rows = stmt.query_map([], pan_from_row)?;
 
-    Ok(total_cost)
+    let mut pans = Vec::new();
+    for pan in rows {
+        pans.push(pan?);
+    }
+
+    Ok(pans)
 }
 
-pub fn deduct_recipe_from_inventory(conn: &Connection, recipe_id: i32) -> Result<()> {
-    let mut stmt = conn.prepare(
-        "SELECT ingredient_id, quantity_required
-        FROM recipe_ingredients
-        WHERE recipe_id = ?1"
+// Record which pan a recipe was written for (None to clear)
+pub fn set_recipe_pan(conn: &Connection, recipe_id: i32, pan_id: Option<i32>) -> Result<()> {
+    conn.execute(
+        "UPDATE recipes SET pan_id = ?1 WHERE id = ?2",
+        params![pan_id, recipe_id],
     )?;
 
-    let rows = stmt.query_map([recipe_id], |row| {
-        Ok((row.get::<_, i32>(0)?, row.get::<_, f32>(1)?))  // (ingredient_id, quantity_required)
-    })?;
+    Ok(())
+}
 
-    for row in rows {
-        let (ingredient_id, qty_required) = row?;
-    
+// Save a recipe's desired dough temperature and friction factor (°C)
+pub fn update_recipe_dough_temp(conn: &Connection, recipe_id: i32, target_ddt_c: f32, friction_factor_c: f32) -> Result<()> {
+    conn.execute(
+        "UPDATE recipes SET target_ddt_c = ?1, friction_factor_c = ?2 WHERE id = ?3",
+        params![target_ddt_c, friction_factor_c, recipe_id],
+    )?;
 
-        let current_qty: f32 = conn.query_row(
-            "SELECT quantity FROM inventory WHERE id = ?1",
-            [ingredient_id],
-            |row| row.get(0)
-        )?;
+    Ok(())
+}
 
-        let new_qty = current_qty - qty_required;
-        conn.execute(
-            "UPDATE inventory SET quantity = ?1 WHERE id = ?2",
-            params![new_qty, ingredient_id]
-        )?;
+// Seed the shared equipment the scheduler checks for conflicts
+pub fn seed_resources(conn: &Connection) -> Result<()> {
+    let resources = vec![
+        ("Mixer", "mix", 1),
+        ("Proofer", "proof", 4),
+        ("Oven", "bake", 1),
+    ];
 
-        println!("🧾 Ingredient {}: {:.2} → {:.2}", ingredient_id, current_qty, new_qty);
+    let mut added = 0;
+    for (name, step_type, capacity) in resources {
+        added += conn.execute(
+            "INSERT OR IGNORE INTO resources (name, step_type, capacity) VALUES (?1, ?2, ?3)",
+            params![name, step_type, capacity],
+        )?;
+    }
+
+    if added > 0 {
+        println!("✅ Bakery equipment seeded");
+    }
+    Ok(())
+}
+
+pub fn get_resources(conn: &Connection) -> Result<Vec<Resource>> {
+    let mut stmt = conn.prepare("SELECT id, name, step_type, capacity FROM resources ORDER BY name")?;
+    let rows = stmt.query_map([], |row| {
+        Ok(Resource {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            step_type: row.get(2)?,
+            capacity: row.get(3)?,
+        })
+    })?;
+
+    let mut resources = Vec::new();
+    for resource in rows {
+        resources.push(resource?);
+    }
+
+    Ok(resources)
+}
+
+pub fn update_resource_capacity(conn: &Connection, resource_id: i32, capacity: i32) -> Result<()> {
+    conn.execute(
+        "UPDATE resources SET capacity = ?1 WHERE id = ?2",
+        params![capacity, resource_id],
+    )?;
+    Ok(())
+}
+
+// Columns selected for every RecipeStep query
+const RECIPE_STEP_COLUMNS: &str =
+    "id, recipe_id, position, step_type, description, min_minutes, max_minutes, temperature_f, notes";
+
+// Map a recipe_steps row (selected with RECIPE_STEP_COLUMNS) into a RecipeStep
+fn recipe_step_from_row(row: &Row) -> Result<RecipeStep> {
+    Ok(RecipeStep {
+        id: row.get(0)?,
+        recipe_id: row.get(1)?,
+        position: row.get(2)?,
+        step_type: row.get(3)?,
+        description: row.get(4)?,
+        min_minutes: row.get(5)?,
+        max_minutes: row.get(6)?,
+        temperature_f: row.get(7)?,
+        notes: row.get(8)?,
+    })
+}
+
+// Parse a recipe's free-text instructions and times into steps
+pub fn recipe_steps_from_instructions(recipe: &RecipeCollection) -> Vec<RecipeStep> {
+    parse_instructions(
+        &recipe.instructions,
+        recipe.prep_time.as_deref(),
+        recipe.bake_time.as_deref(),
+        recipe.total_time.as_deref(),
+    )
+}
+
+// Steps for a recipe in order
+pub fn get_recipe_steps(conn: &Connection, recipe_id: i32) -> Result<Vec<RecipeStep>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM recipe_steps WHERE recipe_id = ?1 ORDER BY position, id", RECIPE_STEP_COLUMNS))?;
+    let rows = stmt.query_map([recipe_id], recipe_step_from_row)?;
+
+    let mut steps = Vec::new();
+    for step in rows {
+        steps.push(step?);
+    }
+
+    Ok(steps)
+}
+
+// Replace all of a recipe's steps, numbering them in the order given
+pub fn replace_recipe_steps(conn: &Connection, recipe_id: i32, steps: &[RecipeStep]) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM recipe_steps WHERE recipe_id = ?1", [recipe_id])?;
+    insert_recipe_steps(&tx, recipe_id, steps)?;
+    tx.commit()
+}
+
+// Append steps to a recipe without opening a transaction, for callers already inside one
+pub fn insert_recipe_steps(conn: &Connection, recipe_id: i32, steps: &[RecipeStep]) -> Result<()> {
+    for (i, step) in steps.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO recipe_steps (recipe_id, position, step_type, description, min_minutes, max_minutes, temperature_f, notes)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                recipe_id, i as i32 + 1, step.step_type, step.description, step.min_minutes, step.max_minutes,
+                step.temperature_f, step.notes
+            ],
+        )?;
     }
+    Ok(())
+}
+
+// Insert a step at a position (1-based), shifting later steps down
+// Returns the new step id
+pub fn add_recipe_step(conn: &Connection, recipe_id: i32, step: &RecipeStep) -> Result<i32> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "UPDATE recipe_steps SET position = position + 1 WHERE recipe_id = ?1 AND position >= ?2",
+        params![recipe_id, step.position],
+    )?;
+    tx.execute(
+        "INSERT INTO recipe_steps (recipe_id, position, step_type, description, min_minutes, max_minutes, temperature_f, notes)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            recipe_id, step.position, step.step_type, step.description, step.min_minutes, step.max_minutes,
+            step.temperature_f, step.notes
+        ],
+    )?;
+    let step_id = tx.last_insert_rowid() as i32;
+    tx.commit()?;
+
+    Ok(step_id)
+}
+
+// Update a step's details; its position is left alone
+pub fn update_recipe_step(conn: &Connection, step: &RecipeStep) -> Result<()> {
+    conn.execute(
+        "UPDATE recipe_steps SET step_type = ?1, description = ?2, min_minutes = ?3, max_minutes = ?4,
+                temperature_f = ?5, notes = ?6
+         WHERE id = ?7",
+        params![
+            step.step_type, step.description, step.min_minutes, step.max_minutes, step.temperature_f,
+            step.notes, step.id
+        ],
+    )?;
+    Ok(())
+}
+
+// Delete a step and close the gap in the numbering
+pub fn delete_recipe_step(conn: &Connection, step: &RecipeStep) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM recipe_steps WHERE id = ?1", [step.id])?;
+    tx.execute(
+        "UPDATE recipe_steps SET position = position - 1 WHERE recipe_id = ?1 AND position > ?2",
+        params![step.recipe_id, step.position],
+    )?;
+    tx.commit()
+}
+
+// Function to update an inventory quantity
+pub fn update_inventory_quantity(conn: &Connection, item_id: i32, updated_quantity: f32) -> Result<()> {
+    conn.execute("UPDATE inventory SET quantity = ?1 WHERE id = ?2",
+    params![updated_quantity, item_id],
+    )?;
+
+    Ok(())
+}
+
+// Function to update inventory cost_per_unit
+pub fn update_inventory_cost(conn: &Connection, item_id: i32, updated_cost: f32) -> Result<()> {
+    conn.execute("UPDATE inventory SET cost_per_unit = ?1 WHERE id = ?2",
+    params![updated_cost, item_id],
+    )?;
 
     Ok(())
+
+}
+
+// Function to add transaction to database
+pub fn add_transaction(conn: &Connection, transaction: &Transaction) -> Result<()> {
+    conn.execute("INSERT INTO transactions (date, transaction_type, amount, description, account, customer_id, category)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+    params![
+        transaction.date, transaction.transaction_type, transaction.amount, transaction.description,
+        transaction.account, transaction.customer_id, transaction.category
+    ],
+    )?;
     
+    Ok(())
+}
+
+// Edit a transaction; reconciled (locked) rows are left untouched
+// Returns false if the transaction is locked or doesn't exist
+pub fn update_transaction(
+    conn: &Connection,
+    transaction_id: i32,
+    date: &str,
+    transaction_type: &str,
+    amount: f32,
+    description: &str,
+    category: Option<&str>,
+    ) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE transactions SET date = ?1, transaction_type = ?2, amount = ?3, description = ?4, category = ?5
+         WHERE id = ?6 AND locked = 0",
+        params![date, transaction_type, amount, description, category, transaction_id],
+    )?;
+
+    Ok(updated > 0)
+}
+
+// Explicitly unlock a reconciled transaction so it can be edited again
+pub fn unlock_transaction(conn: &Connection, transaction_id: i32) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE transactions SET locked = 0 WHERE id = ?1 AND locked = 1",
+        [transaction_id],
+    )?;
+
+    Ok(updated > 0)
+}
+
+pub fn get_ingredients_for_recipe(conn: &Connection, recipe_id: i32) -> Result<Vec<(String, f32, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT i.name, ri.quantity_required, i.unit
+         FROM recipe_ingredients ri
+         JOIN inventory i ON ri.ingredient_id = i.id
+         WHERE ri.recipe_id = ?1"
+    )?;
+
+    let rows = stmt.query_map([recipe_id], |row| {
+        Ok((
+            row.get(0)?, // name
+            row.get(1)?, // quantity_required
+            row.get(2)?, // unit
+        ))
+    })?;
+
+    let mut ingredients = Vec::new();
+    for row in rows {
+        ingredients.push(row?);
+    }
+
+    Ok(ingredients)
+}
+
+// Recipe ingredients with their role tags, as input for baker's math
+pub fn get_formula_for_recipe(conn: &Connection, recipe_id: i32) -> Result<Vec<FormulaIngredient>> {
+    let lines = get_formula_lines_for_recipe(conn, recipe_id)?;
+    Ok(lines.into_iter().map(|(_, ingredient)| ingredient).collect())
+}
+
+// Recipe ingredients for baker's math, paired with their inventory ids
+pub fn get_formula_lines_for_recipe(conn: &Connection, recipe_id: i32) -> Result<Vec<(i32, FormulaIngredient)>> {
+    let mut stmt = conn.prepare(
+        "SELECT i.name, ri.quantity_required, i.unit, i.role, p.kind, p.hydration, p.inoculation, i.id
+         FROM recipe_ingredients ri
+         JOIN inventory i ON ri.ingredient_id = i.id
+         LEFT JOIN preferments p ON p.inventory_id = i.id
+         WHERE ri.recipe_id = ?1"
+    )?;
+
+    let rows = stmt.query_map([recipe_id], |row| {
+        let role: Option<String> = row.get(3)?;
+        let kind: Option<String> = row.get(4)?;
+        let preferment = match kind.as_deref().and_then(PrefermentKind::parse) {
+            Some(kind) => Some(Preferment { kind, hydration: row.get(5)?, inoculation: row.get(6)? }),
+            None => None,
+        };
+        Ok((row.get(7)?, FormulaIngredient {
+            name: row.get(0)?,
+            quantity: row.get(1)?,
+            unit: row.get(2)?,
+            role: role.as_deref().and_then(IngredientRole::parse),
+            preferment,
+        }))
+    })?;
+
+    let mut ingredients = Vec::new();
+    for row in rows {
+        ingredients.push(row?);
+    }
+
+    Ok(ingredients)
 }
 
-pub fn write_csv_transaction_report(conn: &Connection) -> io::Result<()> {
+pub fn reset_database(conn: &Connection) -> Result<()> {
+
+    conn.execute("DELETE FROM recipe_ingredients", [])?;
+    conn.execute("DELETE FROM recipe_steps", [])?;
+    conn.execute("DELETE FROM resources", [])?;
+    conn.execute("DELETE FROM sale_items", [])?;
+    conn.execute("DELETE FROM sale_taxes", [])?;
+    conn.execute("DELETE FROM order_items", [])?;
+    conn.execute("DELETE FROM orders", [])?;
+    conn.execute("DELETE FROM invoice_lines", [])?;
+    conn.execute("DELETE FROM invoices", [])?;
+    conn.execute("DELETE FROM customers", [])?;
+    conn.execute("DELETE FROM price_tier_prices", [])?;
+    conn.execute("DELETE FROM price_tiers", [])?;
+    conn.execute("DELETE FROM finished_goods", [])?;
+    conn.execute("DELETE FROM production_runs", [])?;
+    conn.execute("DELETE FROM production_plan_items", [])?;
+    conn.execute("DELETE FROM forecast_events", [])?;
+    conn.execute("DELETE FROM production_plans", [])?;
+    conn.execute("DELETE FROM transactions", [])?;
+    conn.execute("DELETE FROM recurring_transactions", [])?;
+    conn.execute("DELETE FROM budgets", [])?;
+    conn.execute("DELETE FROM inventory_snapshot_items", [])?;
+    conn.execute("DELETE FROM inventory_snapshots", [])?;
+    conn.execute("DELETE FROM preferments", [])?;
+    conn.execute("UPDATE recipes SET pan_id = NULL", [])?;
+    conn.execute("DELETE FROM pans", [])?;
+    conn.execute("DELETE FROM reconciliations", [])?;
+    conn.execute("DELETE FROM recipes", [])?;
+    conn.execute("DELETE FROM inventory", [])?;
+    Ok(())
+}
+
+pub fn calculate_recipe_cost(conn: &Connection, recipe_id: i32) -> Result<f32> {
+    // you'll write SQL here and sum cost_per_unit * quantity_required
+    let mut stmt = conn.prepare(
+        "SELECT SUM(i.cost_per_unit * ri.quantity_required)
+        FROM recipe_ingredients ri
+        JOIN inventory i ON ri.ingredient_id = i.id
+        WHERE ri.recipe_id = ?1"
+    )?;
+
+    let total_cost: f32 = stmt.query_row([recipe_id], |row| {row.get(0)}).unwrap_or(0.0);
+
+
+    Ok(total_cost)
+}
+
+pub fn deduct_recipe_from_inventory(conn: &Connection, recipe_id: i32, batches: f32) -> Result<()> {
+    let mut stmt = conn.prepare(
+        "SELECT ingredient_id, quantity_required
+        FROM recipe_ingredients
+        WHERE recipe_id = ?1"
+    )?;
+
+    let rows = stmt.query_map([recipe_id], |row| {
+        Ok((row.get::<_, i32>(0)?, row.get::<_, f32>(1)?))  // (ingredient_id, quantity_required)
+    })?;
+
+    for row in rows {
+        let (ingredient_id, qty_required) = row?;
+    
+
+        let current_qty: f32 = conn.query_row(
+            "SELECT quantity FROM inventory WHERE id = ?1",
+            [ingredient_id],
+            |row| row.get(0)
+        )?;
+
+        let new_qty = current_qty - qty_required * batches;
+        conn.execute(
+            "UPDATE inventory SET quantity = ?1 WHERE id = ?2",
+            params![new_qty, ingredient_id]
+        )?;
 
-    let transactions: Vec<Transaction> = read_transactions(conn).expect("Failed to retrieve transactions.");
+        println!("🧾 Ingredient {}: {:.2} → {:.2}", ingredient_id, current_qty, new_qty);
+    }
+
+    Ok(())
+    
+}
 
-    std::fs::create_dir_all("reports")?;
-    let file = File::create("reports/transaction-report.csv")?;
+// Serialize rows to a CSV file, creating its directory if needed
+fn write_csv_rows<T: Serialize>(path: &Path, rows: &[T]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = File::create(path)?;
 
     let mut writer = Writer::from_writer(file);
 
-    for tx in transactions {
-        writer.serialize(tx).expect("Failed to write row");
+    for row in rows {
+        writer.serialize(row)?;
     }
 
     writer.flush()?;
-    println!("Transactions printed to transaction-report.csv");
     Ok(())
 }
 
+pub fn write_csv_transaction_report(conn: &Connection, path: &Path) -> io::Result<()> {
+    let transactions: Vec<Transaction> = read_transactions(conn)
+        .map_err(|e| io::Error::other(format!("Failed to retrieve transactions: {}", e)))?;
+    write_csv_rows(path, &transactions)
+}
+
+pub fn write_csv_inventory_report(conn: &Connection, path: &Path) -> io::Result<()> {
+    let inventory = get_all_inventory(conn)
+        .map_err(|e| io::Error::other(format!("Failed to retrieve inventory: {}", e)))?;
+    write_csv_rows(path, &inventory)
+}
+
+pub fn write_csv_recipe_report(conn: &Connection, path: &Path) -> io::Result<()> {
+    let recipes = get_recipe_collection(conn)
+        .map_err(|e| io::Error::other(format!("Failed to retrieve recipes: {}", e)))?;
+    write_csv_rows(path, &recipes)
+}
+
+pub fn write_csv_recipe_ingredients_report(conn: &Connection, path: &Path) -> io::Result<()> {
+    let lines = get_recipe_ingredient_lines(conn)
+        .map_err(|e| io::Error::other(format!("Failed to retrieve recipe ingredients: {}", e)))?;
+    write_csv_rows(path, &lines)
+}
+
+pub fn write_csv_recipe_costing_report(conn: &Connection, path: &Path) -> io::Result<()> {
+    let costing = get_recipe_costing(conn)
+        .map_err(|e| io::Error::other(format!("Failed to retrieve recipe costing: {}", e)))?;
+    write_csv_rows(path, &costing)
+}
+
+// Every recipe ingredient line with its unit and line cost
+pub fn get_recipe_ingredient_lines(conn: &Connection) -> Result<Vec<RecipeIngredientLine>> {
+    let mut stmt = conn.prepare(
+        "SELECT r.id, r.name, i.id, i.name, ri.quantity_required, i.unit, i.cost_per_unit
+         FROM recipe_ingredients ri
+         JOIN recipes r ON ri.recipe_id = r.id
+         JOIN inventory i ON ri.ingredient_id = i.id
+         ORDER BY r.name, i.name"
+    )?;
+
+    let rows = stmt.query_map([], |row: &Row| {
+        let quantity_required: f32 = row.get(4)?;
+        let cost_per_unit: f32 = row.get(6)?;
+        Ok(RecipeIngredientLine {
+            recipe_id: row.get(0)?,
+            recipe_name: row.get(1)?,
+            ingredient_id: row.get(2)?,
+            ingredient_name: row.get(3)?,
+            quantity_required,
+            unit: row.get(5)?,
+            cost_per_unit,
+            line_cost: quantity_required * cost_per_unit,
+        })
+    })?;
+
+    let mut lines = Vec::new();
+    for row in rows {
+        lines.push(row?);
+    }
+
+    Ok(lines)
+}
+
+// Batch cost, unit cost and margin at MSRP for every recipe
+pub fn get_recipe_costing(conn: &Connection) -> Result<Vec<RecipeCosting>> {
+    let mut costing = Vec::new();
+    for recipe in get_recipe_collection(conn)? {
+        let batch_cost = calculate_recipe_cost(conn, recipe.id)?;
+        let unit_cost = if recipe.yield_quantity > 0 { batch_cost / recipe.yield_quantity as f32 } else { 0.0 };
+        let unit_margin = recipe.msrp_per_unit.map(|msrp| msrp - unit_cost);
+        let margin_percent = match (recipe.msrp_per_unit, unit_margin) {
+            (Some(msrp), Some(margin)) if msrp > 0.0 => Some(margin / msrp * 100.0),
+            _ => None,
+        };
+
+        costing.push(RecipeCosting {
+            recipe_id: recipe.id,
+            recipe_name: recipe.name,
+            category: recipe.category,
+            yield_quantity: recipe.yield_quantity,
+            batch_cost,
+            unit_cost,
+            msrp_per_unit: recipe.msrp_per_unit,
+            unit_margin,
+            margin_percent,
+        });
+    }
+
+    Ok(costing)
+}
+
 // Update RecipeCollection table with unit MSRP once generated
 pub fn update_msrp_for_recipe(conn: &Connection, recipe_id: i32, msrp_per_unit: f32) -> Result<()> {
     conn.execute(
@@ -557,6 +1473,18 @@ pub fn run_integrity_check(conn: &Connection) -> Result<Vec<String>> {
         issues.push(format!("Found {} recipes without any ingredients.", no_ingredients));
     }
 
+    // Check for sale line items pointing at missing sales or recipes
+    let orphan_sale_items_query = "
+        SELECT COUNT(*) FROM sale_items si
+        LEFT JOIN transactions t ON si.transaction_id = t.id
+        LEFT JOIN recipes r ON si.recipe_id = r.id
+        WHERE t.id IS NULL OR r.id IS NULL;
+    ";
+    let orphan_sale_items: i32 = conn.query_row(orphan_sale_items_query, [], |row| row.get(0))?;
+    if orphan_sale_items > 0 {
+        issues.push(format!("Found {} orphaned sale_items entries.", orphan_sale_items));
+    }
+
     Ok(issues)
 }
 
@@ -566,3 +1494,2502 @@ pub fn vacuum_database(conn: &Connection) -> Result<()> {
     Ok(())
 }
 
+
+// Map a reconciliations row into a Reconciliation
+fn reconciliation_from_row(row: &Row) -> Result<Reconciliation> {
+    Ok(Reconciliation {
+        id: row.get(0)?,
+        account: row.get(1)?,
+        period_start: row.get(2)?,
+        period_end: row.get(3)?,
+        opening_balance: row.get(4)?,
+        statement_balance: row.get(5)?,
+        status: row.get(6)?,
+        completed_at: row.get(7)?,
+        report: row.get(8)?,
+    })
+}
+
+const RECONCILIATION_COLUMNS: &str =
+    "id, account, period_start, period_end, opening_balance, statement_balance, status, completed_at, report";
+
+// Statement balance of the account's last completed reconciliation, which opens the next one
+pub fn last_reconciled_balance(conn: &Connection, account: &str) -> Result<Option<f32>> {
+    let result = conn.query_row(
+        "SELECT statement_balance FROM reconciliations
+         WHERE account = ?1 AND status = 'completed'
+         ORDER BY period_end DESC, id DESC LIMIT 1",
+        [account],
+        |row| row.get(0),
+    );
+
+    match result {
+        Ok(balance) => Ok(Some(balance)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// Start a reconciliation for an account and statement period
+pub fn start_reconciliation(
+    conn: &Connection,
+    account: &str,
+    period_start: &str,
+    period_end: &str,
+    opening_balance: f32,
+    statement_balance: f32,
+) -> Result<i32> {
+    conn.execute(
+        "INSERT INTO reconciliations (account, period_start, period_end, opening_balance, statement_balance)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![account, period_start, period_end, opening_balance, statement_balance],
+    )?;
+
+    Ok(conn.last_insert_rowid() as i32)
+}
+
+// Find the in-progress reconciliation for an account, if there is one
+pub fn get_open_reconciliation(conn: &Connection, account: &str) -> Result<Option<Reconciliation>> {
+    let result = conn.query_row(
+        &format!("SELECT {} FROM reconciliations WHERE account = ?1 AND status = 'open'", RECONCILIATION_COLUMNS),
+        [account],
+        reconciliation_from_row,
+    );
+
+    match result {
+        Ok(rec) => Ok(Some(rec)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// Fetch a single reconciliation by id
+pub fn get_reconciliation(conn: &Connection, reconciliation_id: i32) -> Result<Option<Reconciliation>> {
+    let result = conn.query_row(
+        &format!("SELECT {} FROM reconciliations WHERE id = ?1", RECONCILIATION_COLUMNS),
+        [reconciliation_id],
+        reconciliation_from_row,
+    );
+
+    match result {
+        Ok(rec) => Ok(Some(rec)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// List all reconciliations, newest first
+pub fn get_reconciliations(conn: &Connection) -> Result<Vec<Reconciliation>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM reconciliations ORDER BY period_end DESC, id DESC", RECONCILIATION_COLUMNS))?;
+
+    let rec_iter = stmt.query_map([], reconciliation_from_row)?;
+
+    let mut reconciliations = Vec::new();
+    for rec in rec_iter {
+        reconciliations.push(rec?);
+    }
+
+    Ok(reconciliations)
+}
+
+// Unreconciled transactions on the account up to the statement end date
+// Earlier uncleared items are included so outstanding items carry forward
+pub fn get_reconciliation_transactions(conn: &Connection, reconciliation: &Reconciliation) -> Result<Vec<Transaction>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM transactions
+         WHERE account = ?1 AND date <= ?2 AND reconciliation_id IS NULL
+         ORDER BY date, id", TRANSACTION_COLUMNS))?;
+
+    let transaction_iter = stmt.query_map(
+        params![reconciliation.account, reconciliation.period_end],
+        transaction_from_row,
+    )?;
+
+    let mut transactions = Vec::new();
+    for transaction in transaction_iter {
+        transactions.push(transaction?);
+    }
+
+    Ok(transactions)
+}
+
+// Tick or untick a transaction as cleared; locked rows are left untouched
+pub fn set_transaction_cleared(conn: &Connection, transaction_id: i32, cleared: bool) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE transactions SET cleared = ?1 WHERE id = ?2 AND locked = 0",
+        params![cleared, transaction_id],
+    )?;
+
+    Ok(updated > 0)
+}
+
+// Opening balance plus every cleared transaction
+pub fn reconciliation_cleared_balance(reconciliation: &Reconciliation, transactions: &[Transaction]) -> f32 {
+    reconciliation.opening_balance
+        + transactions
+            .iter()
+            .filter(|tx| tx.cleared)
+            .map(|tx| tx.signed_amount())
+            .sum::<f32>()
+}
+
+// Build the printable reconciliation report
+fn build_reconciliation_report(reconciliation: &Reconciliation, transactions: &[Transaction], completed_at: &str) -> String {
+    let mut report = String::new();
+    let cleared: Vec<&Transaction> = transactions.iter().filter(|tx| tx.cleared).collect();
+    let outstanding: Vec<&Transaction> = transactions.iter().filter(|tx| !tx.cleared).collect();
+
+    let deposits: f32 = cleared.iter().filter(|tx| tx.signed_amount() >= 0.0).map(|tx| tx.amount).sum();
+    let payments: f32 = cleared.iter().filter(|tx| tx.signed_amount() < 0.0).map(|tx| tx.amount).sum();
+    let outstanding_total: f32 = outstanding.iter().fold(0.0, |total, tx| total + tx.signed_amount());
+    let cleared_balance = reconciliation_cleared_balance(reconciliation, transactions);
+
+    report.push_str("Bank Reconciliation Report\n");
+    report.push_str(&format!("Account: {}\n", reconciliation.account));
+    report.push_str(&format!("Statement period: {} to {}\n", reconciliation.period_start, reconciliation.period_end));
+    report.push_str(&format!("Completed: {}\n", completed_at));
+    report.push_str(&format!("{}\n", "-".repeat(60)));
+    report.push_str("Cleared transactions:\n");
+    for tx in &cleared {
+        report.push_str(&format!(
+            "{:<4} | {:<12} | {:<10} | ${:>9.2} | {}\n",
+            tx.id, tx.date, tx.transaction_type, tx.amount, tx.description
+        ));
+    }
+    report.push_str(&format!("{}\n", "-".repeat(60)));
+    report.push_str(&format!("Opening balance:     ${:>10.2}\n", reconciliation.opening_balance));
+    report.push_str(&format!("Cleared deposits:    ${:>10.2}\n", deposits));
+    report.push_str(&format!("Cleared payments:    ${:>10.2}\n", payments));
+    report.push_str(&format!("Cleared balance:     ${:>10.2}\n", cleared_balance));
+    report.push_str(&format!("Statement balance:   ${:>10.2}\n", reconciliation.statement_balance));
+    report.push_str(&format!("Difference:          ${:>10.2}\n", reconciliation.statement_balance - cleared_balance));
+    report.push_str(&format!(
+        "Outstanding items:   {} (net ${:.2}, carried forward)\n",
+        outstanding.len(), outstanding_total
+    ));
+
+    report
+}
+
+// Finish a reconciliation: lock cleared rows, store the report and close it out
+pub fn complete_reconciliation(conn: &Connection, reconciliation_id: i32) -> Result<String> {
+    let reconciliation = get_reconciliation(conn, reconciliation_id)?
+        .ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+    let transactions = get_reconciliation_transactions(conn, &reconciliation)?;
+    let completed_at = chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let report = build_reconciliation_report(&reconciliation, &transactions, &completed_at);
+
+    let db_tx = conn.unchecked_transaction()?;
+    for tx in transactions.iter().filter(|tx| tx.cleared) {
+        db_tx.execute(
+            "UPDATE transactions SET reconciliation_id = ?1, locked = 1 WHERE id = ?2",
+            params![reconciliation_id, tx.id],
+        )?;
+    }
+    db_tx.execute(
+        "UPDATE reconciliations SET status = 'completed', completed_at = ?1, report = ?2 WHERE id = ?3",
+        params![completed_at, report, reconciliation_id],
+    )?;
+    db_tx.commit()?;
+
+    Ok(report)
+}
+
+// Record a sale made up of line items; the transaction amount is the sum of the lines
+// items: (recipe_id, quantity, unit_price)
+pub fn add_itemized_sale(
+    conn: &Connection,
+    date: &str,
+    description: &str,
+    account: &str,
+    category: Option<&str>,
+    customer_id: Option<i32>,
+    items: &[(i32, i32, f32)],
+) -> Result<i32> {
+    let db_tx = conn.unchecked_transaction()?;
+    let transaction_id = insert_itemized_sale(&db_tx, date, description, account, category, customer_id, items)?;
+    db_tx.commit()?;
+
+    Ok(transaction_id)
+}
+
+// Insert the sale transaction and its lines; callers own the surrounding db transaction
+fn insert_itemized_sale(
+    conn: &Connection,
+    date: &str,
+    description: &str,
+    account: &str,
+    category: Option<&str>,
+    customer_id: Option<i32>,
+    items: &[(i32, i32, f32)],
+) -> Result<i32> {
+    // The transaction records what was collected: line items plus sales tax
+    let sale_tax = compute_sale_tax(conn, customer_id, items)?;
+    let total = sale_tax.total();
+
+    conn.execute(
+        "INSERT INTO transactions (date, transaction_type, amount, description, account, customer_id, category)
+         VALUES (?1, 'sale', ?2, ?3, ?4, ?5, ?6)",
+        params![date, total, description, account, customer_id, category],
+    )?;
+    let transaction_id = conn.last_insert_rowid() as i32;
+    insert_sale_lines(conn, transaction_id, items, &sale_tax)?;
+
+    Ok(transaction_id)
+}
+
+// Record a sale's line items and the tax charged on them against an existing sale transaction
+fn insert_sale_lines(conn: &Connection, transaction_id: i32, items: &[(i32, i32, f32)], sale_tax: &SaleTax) -> Result<()> {
+    for (recipe_id, quantity, unit_price) in items {
+        conn.execute(
+            "INSERT INTO sale_items (transaction_id, recipe_id, quantity, unit_price) VALUES (?1, ?2, ?3, ?4)",
+            params![transaction_id, recipe_id, quantity, unit_price],
+        )?;
+    }
+
+    for (jurisdiction_id, tax_amount) in &sale_tax.taxes {
+        conn.execute(
+            "INSERT INTO sale_taxes (transaction_id, jurisdiction_id, gross_amount, taxable_amount, tax_amount)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![transaction_id, jurisdiction_id, sale_tax.gross, sale_tax.taxable, tax_amount],
+        )?;
+    }
+
+    Ok(())
+}
+
+// Line items recorded against a sale transaction
+pub fn get_sale_items(conn: &Connection, transaction_id: i32) -> Result<Vec<SaleItem>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, transaction_id, recipe_id, quantity, unit_price FROM sale_items WHERE transaction_id = ?1"
+    )?;
+
+    let item_iter = stmt.query_map([transaction_id], |row: &Row| {
+        Ok(SaleItem {
+            id: row.get(0)?,
+            transaction_id: row.get(1)?,
+            recipe_id: row.get(2)?,
+            quantity: row.get(3)?,
+            unit_price: row.get(4)?,
+        })
+    })?;
+
+    let mut items = Vec::new();
+    for item in item_iter {
+        items.push(item?);
+    }
+
+    Ok(items)
+}
+
+// Theoretical cost of one unit of a recipe (batch cost / yield)
+pub fn recipe_unit_cost(conn: &Connection, recipe_id: i32) -> Result<f32> {
+    let yield_quantity: i32 = conn.query_row(
+        "SELECT yield_quantity FROM recipes WHERE id = ?1",
+        [recipe_id],
+        |row| row.get(0),
+    )?;
+    if yield_quantity <= 0 {
+        return Ok(0.0);
+    }
+
+    Ok(calculate_recipe_cost(conn, recipe_id)? / yield_quantity as f32)
+}
+
+// Units sold, revenue, cost and margin per product between two dates (inclusive)
+// With by_month the figures are broken out per YYYY-MM period
+pub fn get_product_sales(conn: &Connection, start_date: &str, end_date: &str, by_month: bool) -> Result<Vec<ProductSales>> {
+    let mut stmt = conn.prepare(
+        "SELECT CASE WHEN ?3 THEN substr(t.date, 1, 7) ELSE ?1 || ' to ' || ?2 END AS period,
+                r.id, r.name, SUM(si.quantity), SUM(si.quantity * si.unit_price)
+         FROM sale_items si
+         JOIN transactions t ON si.transaction_id = t.id
+         JOIN recipes r ON si.recipe_id = r.id
+         WHERE t.date BETWEEN ?1 AND ?2
+         GROUP BY period, r.id
+         ORDER BY period, r.name"
+    )?;
+
+    let rows = stmt.query_map(params![start_date, end_date, by_month], |row| {
+        Ok((
+            row.get::<_, String>(0)?, // period
+            row.get::<_, i32>(1)?,    // recipe_id
+            row.get::<_, String>(2)?, // recipe name
+            row.get::<_, i32>(3)?,    // units sold
+            row.get::<_, f32>(4)?,    // revenue
+        ))
+    })?;
+
+    let mut unit_costs: HashMap<i32, f32> = HashMap::new();
+    let mut sales = Vec::new();
+    for row in rows {
+        let (period, recipe_id, recipe_name, units_sold, revenue) = row?;
+
+        let unit_cost = match unit_costs.get(&recipe_id) {
+            Some(cost) => *cost,
+            None => {
+                let cost = recipe_unit_cost(conn, recipe_id)?;
+                unit_costs.insert(recipe_id, cost);
+                cost
+            }
+        };
+        let cost = unit_cost * units_sold as f32;
+
+        sales.push(ProductSales {
+            period,
+            recipe_id,
+            recipe_name,
+            units_sold,
+            revenue,
+            cost,
+            gross_margin: revenue - cost,
+        });
+    }
+
+    Ok(sales)
+}
+
+// Bake a number of batches: deduct ingredients and add the yield to finished goods
+// Returns the number of units produced
+pub fn record_production_run(conn: &Connection, recipe_id: i32, batches: f32, date: &str) -> Result<i32> {
+    let db_tx = conn.unchecked_transaction()?;
+    let units_produced = insert_production_run(&db_tx, recipe_id, batches, date, None)?;
+    db_tx.commit()?;
+
+    Ok(units_produced)
+}
+
+// Deduct ingredients, log the run and add finished goods without opening a transaction
+// Returns units produced
+fn insert_production_run(conn: &Connection, recipe_id: i32, batches: f32, date: &str, plan_id: Option<i32>) -> Result<i32> {
+    let yield_quantity: i32 = conn.query_row(
+        "SELECT yield_quantity FROM recipes WHERE id = ?1",
+        [recipe_id],
+        |row| row.get(0),
+    )?;
+    let units_produced = (yield_quantity as f32 * batches).round() as i32;
+    let batch_cost = calculate_recipe_cost(conn, recipe_id)?;
+
+    deduct_recipe_from_inventory(conn, recipe_id, batches)?;
+    conn.execute(
+        "INSERT INTO production_runs (date, recipe_id, batches, units_produced, batch_cost, plan_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![date, recipe_id, batches, units_produced, batch_cost, plan_id],
+    )?;
+    adjust_finished_goods(conn, recipe_id, units_produced)?;
+
+    Ok(units_produced)
+}
+
+// Units sold per recipe per day between two dates (inclusive), as (recipe id, date, units)
+pub fn get_daily_unit_sales(conn: &Connection, start_date: &str, end_date: &str) -> Result<Vec<(i32, String, i32)>> {
+    let mut stmt = conn.prepare(
+        "SELECT si.recipe_id, t.date, SUM(si.quantity)
+         FROM sale_items si
+         JOIN transactions t ON si.transaction_id = t.id
+         WHERE t.transaction_type = 'sale' AND t.date BETWEEN ?1 AND ?2
+         GROUP BY si.recipe_id, t.date
+         ORDER BY t.date"
+    )?;
+
+    let rows = stmt.query_map([start_date, end_date], |row| {
+        Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+    })?;
+
+    let mut sales = Vec::new();
+    for row in rows {
+        sales.push(row?);
+    }
+
+    Ok(sales)
+}
+
+pub fn add_forecast_event(conn: &Connection, event: &ForecastEvent) -> Result<i32> {
+    conn.execute(
+        "INSERT INTO forecast_events (event_date, name, recipe_id, adjust_percent) VALUES (?1, ?2, ?3, ?4)",
+        params![event.event_date, event.name, event.recipe_id, event.adjust_percent],
+    )?;
+
+    Ok(conn.last_insert_rowid() as i32)
+}
+
+// Events on or after a date, soonest first
+pub fn get_forecast_events(conn: &Connection, from_date: &str) -> Result<Vec<ForecastEvent>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, event_date, name, recipe_id, adjust_percent
+         FROM forecast_events
+         WHERE event_date >= ?1
+         ORDER BY event_date, id"
+    )?;
+
+    let rows = stmt.query_map([from_date], |row: &Row| {
+        Ok(ForecastEvent {
+            id: row.get(0)?,
+            event_date: row.get(1)?,
+            name: row.get(2)?,
+            recipe_id: row.get(3)?,
+            adjust_percent: row.get(4)?,
+        })
+    })?;
+
+    let mut events = Vec::new();
+    for event in rows {
+        events.push(event?);
+    }
+
+    Ok(events)
+}
+
+pub fn delete_forecast_event(conn: &Connection, event_id: i32) -> Result<bool> {
+    let deleted = conn.execute("DELETE FROM forecast_events WHERE id = ?1", [event_id])?;
+    Ok(deleted > 0)
+}
+
+// Create a draft production plan with its (recipe id, batches) lines
+// Returns the new plan id
+pub fn add_production_plan(conn: &Connection, plan_date: &str, notes: Option<&str>, items: &[(i32, f32)]) -> Result<i32> {
+    let db_tx = conn.unchecked_transaction()?;
+    db_tx.execute(
+        "INSERT INTO production_plans (plan_date, status, notes) VALUES (?1, 'draft', ?2)",
+        params![plan_date, notes],
+    )?;
+    let plan_id = db_tx.last_insert_rowid() as i32;
+    for (recipe_id, batches) in items {
+        db_tx.execute(
+            "INSERT INTO production_plan_items (plan_id, recipe_id, batches) VALUES (?1, ?2, ?3)",
+            params![plan_id, recipe_id, batches],
+        )?;
+    }
+    db_tx.commit()?;
+
+    Ok(plan_id)
+}
+
+const PRODUCTION_PLAN_COLUMNS: &str = "id, plan_date, status, notes, posted_date";
+
+fn production_plan_from_row(row: &Row) -> Result<ProductionPlan> {
+    Ok(ProductionPlan {
+        id: row.get(0)?,
+        plan_date: row.get(1)?,
+        status: row.get(2)?,
+        notes: row.get(3)?,
+        posted_date: row.get(4)?,
+    })
+}
+
+// Plans newest date first
+pub fn get_production_plans(conn: &Connection) -> Result<Vec<ProductionPlan>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM production_plans ORDER BY plan_date DESC, id DESC", PRODUCTION_PLAN_COLUMNS))?;
+    let rows = stmt.query_map([], production_plan_from_row)?;
+
+    let mut plans = Vec::new();
+    for plan in rows {
+        plans.push(plan?);
+    }
+
+    Ok(plans)
+}
+
+pub fn get_production_plan(conn: &Connection, plan_id: i32) -> Result<Option<ProductionPlan>> {
+    let result = conn.query_row(
+        &format!("SELECT {} FROM production_plans WHERE id = ?1", PRODUCTION_PLAN_COLUMNS),
+        [plan_id],
+        production_plan_from_row,
+    );
+
+    match result {
+        Ok(plan) => Ok(Some(plan)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn get_production_plan_items(conn: &Connection, plan_id: i32) -> Result<Vec<ProductionPlanItem>> {
+    let mut stmt = conn.prepare(
+        "SELECT pi.recipe_id, r.name, pi.batches
+         FROM production_plan_items pi
+         JOIN recipes r ON pi.recipe_id = r.id
+         WHERE pi.plan_id = ?1
+         ORDER BY pi.id"
+    )?;
+
+    let rows = stmt.query_map([plan_id], |row: &Row| {
+        Ok(ProductionPlanItem {
+            recipe_id: row.get(0)?,
+            recipe_name: row.get(1)?,
+            batches: row.get(2)?,
+        })
+    })?;
+
+    let mut items = Vec::new();
+    for item in rows {
+        items.push(item?);
+    }
+
+    Ok(items)
+}
+
+// Delete a plan that hasn't been posted
+// Returns false when the plan doesn't exist or was already posted
+pub fn delete_production_plan(conn: &Connection, plan_id: i32) -> Result<bool> {
+    let db_tx = conn.unchecked_transaction()?;
+    let deleted = db_tx.execute(
+        "DELETE FROM production_plans WHERE id = ?1 AND status = 'draft'",
+        [plan_id],
+    )?;
+    if deleted > 0 {
+        db_tx.execute("DELETE FROM production_plan_items WHERE plan_id = ?1", [plan_id])?;
+    }
+    db_tx.commit()?;
+
+    Ok(deleted > 0)
+}
+
+// Post every line of a draft plan as a production run in one transaction
+// Returns (recipe name, units produced) per line, or None when the plan isn't a draft
+pub fn post_production_plan(conn: &Connection, plan_id: i32, date: &str) -> Result<Option<Vec<(String, i32)>>> {
+    let db_tx = conn.unchecked_transaction()?;
+    let claimed = db_tx.execute(
+        "UPDATE production_plans SET status = 'posted', posted_date = ?1 WHERE id = ?2 AND status = 'draft'",
+        params![date, plan_id],
+    )?;
+    if claimed == 0 {
+        return Ok(None);
+    }
+
+    let mut produced = Vec::new();
+    for item in get_production_plan_items(&db_tx, plan_id)? {
+        let units = insert_production_run(&db_tx, item.recipe_id, item.batches, date, Some(plan_id))?;
+        produced.push((item.recipe_name, units));
+    }
+    db_tx.commit()?;
+
+    Ok(Some(produced))
+}
+
+// Add (or with a negative change, remove) finished units of a recipe
+fn adjust_finished_goods(conn: &Connection, recipe_id: i32, change: i32) -> Result<()> {
+    conn.execute(
+        "INSERT INTO finished_goods (recipe_id, quantity) VALUES (?1, ?2)
+         ON CONFLICT(recipe_id) DO UPDATE SET quantity = quantity + excluded.quantity",
+        params![recipe_id, change],
+    )?;
+    Ok(())
+}
+
+// Take sold units out of finished goods; stock never drops below zero
+fn consume_finished_goods(conn: &Connection, recipe_id: i32, quantity: i32) -> Result<()> {
+    conn.execute(
+        "UPDATE finished_goods SET quantity = MAX(quantity - ?2, 0) WHERE recipe_id = ?1",
+        params![recipe_id, quantity],
+    )?;
+    Ok(())
+}
+
+// Finished goods on hand: (recipe_id, recipe name, quantity)
+pub fn get_finished_goods(conn: &Connection) -> Result<Vec<(i32, String, i32)>> {
+    let mut stmt = conn.prepare(
+        "SELECT r.id, r.name, COALESCE(fg.quantity, 0)
+         FROM recipes r
+         LEFT JOIN finished_goods fg ON fg.recipe_id = r.id
+         ORDER BY r.name"
+    )?;
+
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get(0)?, // recipe_id
+            row.get(1)?, // name
+            row.get(2)?, // quantity
+        ))
+    })?;
+
+    let mut goods = Vec::new();
+    for row in rows {
+        goods.push(row?);
+    }
+
+    Ok(goods)
+}
+
+const ORDER_COLUMNS: &str =
+    "id, customer_name, customer_contact, due_date, due_time, fulfilment, deposit_paid, status, notes, created_date, sale_transaction_id, customer_id";
+
+// Map an orders row (selected with ORDER_COLUMNS) into an Order
+fn order_from_row(row: &Row) -> Result<Order> {
+    Ok(Order {
+        id: row.get(0)?,
+        customer_name: row.get(1)?,
+        customer_contact: row.get(2)?,
+        due_date: row.get(3)?,
+        due_time: row.get(4)?,
+        fulfilment: row.get(5)?,
+        deposit_paid: row.get(6)?,
+        status: row.get(7)?,
+        notes: row.get(8)?,
+        created_date: row.get(9)?,
+        sale_transaction_id: row.get(10)?,
+        customer_id: row.get(11)?,
+    })
+}
+
+// Create an order with its requested products
+// items: (recipe_id, quantity, unit_price)
+pub fn add_order(conn: &Connection, order: &Order, items: &[(i32, i32, f32)]) -> Result<i32> {
+    let db_tx = conn.unchecked_transaction()?;
+    db_tx.execute(
+        "INSERT INTO orders (customer_name, customer_contact, due_date, due_time, fulfilment, deposit_paid, status, notes, created_date, customer_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            order.customer_name, order.customer_contact, order.due_date, order.due_time, order.fulfilment,
+            order.deposit_paid, order.status, order.notes, order.created_date, order.customer_id
+        ],
+    )?;
+    let order_id = db_tx.last_insert_rowid() as i32;
+
+    for (recipe_id, quantity, unit_price) in items {
+        db_tx.execute(
+            "INSERT INTO order_items (order_id, recipe_id, quantity, unit_price) VALUES (?1, ?2, ?3, ?4)",
+            params![order_id, recipe_id, quantity, unit_price],
+        )?;
+    }
+    db_tx.commit()?;
+
+    Ok(order_id)
+}
+
+// All orders by due date; open_only hides fulfilled and cancelled orders
+pub fn get_orders(conn: &Connection, open_only: bool) -> Result<Vec<Order>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM orders
+         WHERE NOT ?1 OR status NOT IN ('fulfilled', 'cancelled')
+         ORDER BY due_date, due_time, id", ORDER_COLUMNS))?;
+
+    let order_iter = stmt.query_map([open_only], order_from_row)?;
+
+    let mut orders = Vec::new();
+    for order in order_iter {
+        orders.push(order?);
+    }
+
+    Ok(orders)
+}
+
+// Fetch a single order by id
+pub fn get_order(conn: &Connection, order_id: i32) -> Result<Option<Order>> {
+    let result = conn.query_row(
+        &format!("SELECT {} FROM orders WHERE id = ?1", ORDER_COLUMNS),
+        [order_id],
+        order_from_row,
+    );
+
+    match result {
+        Ok(order) => Ok(Some(order)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// Products requested on an order
+pub fn get_order_items(conn: &Connection, order_id: i32) -> Result<Vec<OrderItem>> {
+    let mut stmt = conn.prepare(
+        "SELECT oi.id, oi.order_id, oi.recipe_id, r.name, oi.quantity, oi.unit_price
+         FROM order_items oi
+         JOIN recipes r ON oi.recipe_id = r.id
+         WHERE oi.order_id = ?1"
+    )?;
+
+    let item_iter = stmt.query_map([order_id], |row: &Row| {
+        Ok(OrderItem {
+            id: row.get(0)?,
+            order_id: row.get(1)?,
+            recipe_id: row.get(2)?,
+            recipe_name: row.get(3)?,
+            quantity: row.get(4)?,
+            unit_price: row.get(5)?,
+        })
+    })?;
+
+    let mut items = Vec::new();
+    for item in item_iter {
+        items.push(item?);
+    }
+
+    Ok(items)
+}
+
+// Move an order to a new status; fulfilled and cancelled orders can't be changed
+pub fn update_order_status(conn: &Connection, order_id: i32, status: &str) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE orders SET status = ?1 WHERE id = ?2 AND status NOT IN ('fulfilled', 'cancelled')",
+        params![status, order_id],
+    )?;
+
+    Ok(updated > 0)
+}
+
+// What fulfilling an order will charge: its line items plus sales tax
+pub fn get_order_sale_tax(conn: &Connection, order: &Order) -> Result<SaleTax> {
+    let lines: Vec<(i32, i32, f32)> = get_order_items(conn, order.id)?
+        .iter()
+        .map(|item| (item.recipe_id, item.quantity, item.unit_price))
+        .collect();
+    compute_sale_tax(conn, order.customer_id, &lines)
+}
+
+// Record a deposit against an order (added to any deposit already paid) and post the money
+// received as a sale linked to the order; fulfilment nets it out of the final sale.
+// Returns false if the order is closed or the deposit is more than the balance still due.
+pub fn add_order_deposit(
+    conn: &Connection,
+    order_id: i32,
+    amount: f32,
+    date: &str,
+    account: &str,
+    category: Option<&str>,
+) -> Result<bool> {
+    let order = match get_order(conn, order_id)? {
+        Some(order) => order,
+        None => return Ok(false),
+    };
+    let total = get_order_sale_tax(conn, &order)?.total();
+
+    let db_tx = conn.unchecked_transaction()?;
+    // Half a cent of slack so paying the exact balance isn't refused over float rounding
+    let updated = db_tx.execute(
+        "UPDATE orders SET deposit_paid = deposit_paid + ?1
+         WHERE id = ?2 AND status NOT IN ('fulfilled', 'cancelled') AND deposit_paid + ?1 <= ?3 + 0.005",
+        params![amount, order_id, total],
+    )?;
+    if updated == 0 {
+        return Ok(false);
+    }
+
+    let (customer_name, customer_id): (String, Option<i32>) = db_tx.query_row(
+        "SELECT customer_name, customer_id FROM orders WHERE id = ?1",
+        [order_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    db_tx.execute(
+        "INSERT INTO transactions (date, transaction_type, amount, description, account, customer_id, order_id, category)
+         VALUES (?1, 'sale', ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            date, amount, format!("Deposit on order #{} — {}", order_id, customer_name), account, customer_id, order_id,
+            category
+        ],
+    )?;
+    db_tx.commit()?;
+
+    Ok(true)
+}
+
+// Fulfil an order: post the sale, consume finished goods and mark it fulfilled
+// Deposits already posted for the order are netted out of the sale so the order is only counted once.
+// When the deposits already cover the order, no sale is posted: the line items and tax are recorded
+// against the latest deposit, and anything collected beyond the total is posted as a refund.
+// Returns the id of the sale transaction holding the line items, or None if the order was already fulfilled or cancelled
+pub fn fulfil_order(conn: &Connection, order_id: i32, date: &str, account: &str, category: Option<&str>) -> Result<Option<i32>> {
+    let order = get_order(conn, order_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+    let items = get_order_items(conn, order_id)?;
+    let lines: Vec<(i32, i32, f32)> = items
+        .iter()
+        .map(|item| (item.recipe_id, item.quantity, item.unit_price))
+        .collect();
+
+    let db_tx = conn.unchecked_transaction()?;
+    let updated = db_tx.execute(
+        "UPDATE orders SET status = 'fulfilled' WHERE id = ?1 AND status NOT IN ('fulfilled', 'cancelled')",
+        [order_id],
+    )?;
+    if updated == 0 {
+        return Ok(None);
+    }
+
+    let description = format!("Order #{} — {}", order.id, order.customer_name);
+    let (deposits, last_deposit): (f32, Option<i32>) = db_tx.query_row(
+        "SELECT COALESCE(SUM(amount), 0), MAX(id) FROM transactions WHERE order_id = ?1 AND transaction_type = 'sale'",
+        [order_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let sale_tax = compute_sale_tax(&db_tx, order.customer_id, &lines)?;
+    let balance = sale_tax.total() - deposits;
+
+    let transaction_id = match last_deposit {
+        Some(deposit_id) if balance < 0.005 => {
+            insert_sale_lines(&db_tx, deposit_id, &lines, &sale_tax)?;
+            if balance <= -0.005 {
+                db_tx.execute(
+                    "INSERT INTO transactions (date, transaction_type, amount, description, account, customer_id, order_id, category)
+                     VALUES (?1, 'expense', ?2, ?3, ?4, ?5, ?6, 'Refunds')",
+                    params![
+                        date, -balance, format!("Refund of overpaid deposit on order #{} — {}", order.id, order.customer_name),
+                        account, order.customer_id, order_id
+                    ],
+                )?;
+            }
+            deposit_id
+        }
+        _ => {
+            let transaction_id = insert_itemized_sale(&db_tx, date, &description, account, category, order.customer_id, &lines)?;
+            db_tx.execute(
+                "UPDATE transactions SET order_id = ?1, amount = ?2 WHERE id = ?3",
+                params![order_id, balance, transaction_id],
+            )?;
+            transaction_id
+        }
+    };
+    for item in &items {
+        consume_finished_goods(&db_tx, item.recipe_id, item.quantity)?;
+    }
+    db_tx.execute(
+        "UPDATE orders SET sale_transaction_id = ?1 WHERE id = ?2",
+        params![transaction_id, order_id],
+    )?;
+    db_tx.commit()?;
+
+    Ok(Some(transaction_id))
+}
+
+const CUSTOMER_COLUMNS: &str =
+    "id, name, email, phone, address, customer_type, price_tier_id, tax_exempt, notes";
+
+// Map a customers row (selected with CUSTOMER_COLUMNS) into a Customer
+fn customer_from_row(row: &Row) -> Result<Customer> {
+    Ok(Customer {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        email: row.get(2)?,
+        phone: row.get(3)?,
+        address: row.get(4)?,
+        customer_type: row.get(5)?,
+        price_tier_id: row.get(6)?,
+        tax_exempt: row.get(7)?,
+        notes: row.get(8)?,
+    })
+}
+
+// Add a customer to the directory
+pub fn add_customer(conn: &Connection, customer: &Customer) -> Result<i32> {
+    conn.execute(
+        "INSERT INTO customers (name, email, phone, address, customer_type, price_tier_id, tax_exempt, notes)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            customer.name, customer.email, customer.phone, customer.address, customer.customer_type,
+            customer.price_tier_id, customer.tax_exempt, customer.notes
+        ],
+    )?;
+
+    Ok(conn.last_insert_rowid() as i32)
+}
+
+// Save changes to an existing customer
+pub fn update_customer(conn: &Connection, customer: &Customer) -> Result<()> {
+    conn.execute(
+        "UPDATE customers SET name = ?1, email = ?2, phone = ?3, address = ?4, customer_type = ?5,
+         price_tier_id = ?6, tax_exempt = ?7, notes = ?8
+         WHERE id = ?9",
+        params![
+            customer.name, customer.email, customer.phone, customer.address, customer.customer_type,
+            customer.price_tier_id, customer.tax_exempt, customer.notes, customer.id
+        ],
+    )?;
+
+    Ok(())
+}
+
+// All customers by name
+pub fn get_customers(conn: &Connection) -> Result<Vec<Customer>> {
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM customers ORDER BY name", CUSTOMER_COLUMNS))?;
+
+    let customer_iter = stmt.query_map([], customer_from_row)?;
+
+    let mut customers = Vec::new();
+    for customer in customer_iter {
+        customers.push(customer?);
+    }
+
+    Ok(customers)
+}
+
+// Fetch a single customer by id
+pub fn get_customer(conn: &Connection, customer_id: i32) -> Result<Option<Customer>> {
+    let result = conn.query_row(
+        &format!("SELECT {} FROM customers WHERE id = ?1", CUSTOMER_COLUMNS),
+        [customer_id],
+        customer_from_row,
+    );
+
+    match result {
+        Ok(customer) => Ok(Some(customer)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// Orders placed by a customer, newest first
+pub fn get_orders_for_customer(conn: &Connection, customer_id: i32) -> Result<Vec<Order>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM orders WHERE customer_id = ?1 ORDER BY due_date DESC, id DESC", ORDER_COLUMNS))?;
+
+    let order_iter = stmt.query_map([customer_id], order_from_row)?;
+
+    let mut orders = Vec::new();
+    for order in order_iter {
+        orders.push(order?);
+    }
+
+    Ok(orders)
+}
+
+// Transactions linked to a customer, newest first
+pub fn get_transactions_for_customer(conn: &Connection, customer_id: i32) -> Result<Vec<Transaction>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM transactions WHERE customer_id = ?1 ORDER BY date DESC, id DESC", TRANSACTION_COLUMNS))?;
+
+    let transaction_iter = stmt.query_map([customer_id], transaction_from_row)?;
+
+    let mut transactions = Vec::new();
+    for transaction in transaction_iter {
+        transactions.push(transaction?);
+    }
+
+    Ok(transactions)
+}
+
+// Create a price tier; discount_percent is taken off MSRP for products without a fixed tier price
+pub fn add_price_tier(conn: &Connection, name: &str, discount_percent: Option<f32>) -> Result<i32> {
+    conn.execute(
+        "INSERT INTO price_tiers (name, discount_percent) VALUES (?1, ?2)",
+        params![name, discount_percent],
+    )?;
+
+    Ok(conn.last_insert_rowid() as i32)
+}
+
+// All price tiers by name
+pub fn get_price_tiers(conn: &Connection) -> Result<Vec<PriceTier>> {
+    let mut stmt = conn.prepare("SELECT id, name, discount_percent FROM price_tiers ORDER BY name")?;
+
+    let tier_iter = stmt.query_map([], |row: &Row| {
+        Ok(PriceTier {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            discount_percent: row.get(2)?,
+        })
+    })?;
+
+    let mut tiers = Vec::new();
+    for tier in tier_iter {
+        tiers.push(tier?);
+    }
+
+    Ok(tiers)
+}
+
+// Set (or replace) a fixed per-product price for a tier
+pub fn set_tier_price(conn: &Connection, tier_id: i32, recipe_id: i32, unit_price: f32) -> Result<()> {
+    conn.execute(
+        "INSERT INTO price_tier_prices (tier_id, recipe_id, unit_price) VALUES (?1, ?2, ?3)
+         ON CONFLICT(tier_id, recipe_id) DO UPDATE SET unit_price = excluded.unit_price",
+        params![tier_id, recipe_id, unit_price],
+    )?;
+
+    Ok(())
+}
+
+// Fixed prices defined for a tier: (recipe_id, recipe name, unit_price)
+pub fn get_tier_prices(conn: &Connection, tier_id: i32) -> Result<Vec<(i32, String, f32)>> {
+    let mut stmt = conn.prepare(
+        "SELECT r.id, r.name, tp.unit_price
+         FROM price_tier_prices tp
+         JOIN recipes r ON tp.recipe_id = r.id
+         WHERE tp.tier_id = ?1
+         ORDER BY r.name"
+    )?;
+
+    let rows = stmt.query_map([tier_id], |row| {
+        Ok((
+            row.get(0)?, // recipe_id
+            row.get(1)?, // name
+            row.get(2)?, // unit_price
+        ))
+    })?;
+
+    let mut prices = Vec::new();
+    for row in rows {
+        prices.push(row?);
+    }
+
+    Ok(prices)
+}
+
+// Unit price for a recipe under a price tier
+// Fixed tier price wins, then the tier discount off MSRP, then plain MSRP
+pub fn resolve_unit_price(conn: &Connection, price_tier_id: Option<i32>, recipe_id: i32) -> Result<Option<f32>> {
+    let msrp: Option<f32> = conn.query_row(
+        "SELECT msrp_per_unit FROM recipes WHERE id = ?1",
+        [recipe_id],
+        |row| row.get(0),
+    )?;
+
+    let tier_id = match price_tier_id {
+        Some(id) => id,
+        None => return Ok(msrp),
+    };
+
+    let fixed_price = conn.query_row(
+        "SELECT unit_price FROM price_tier_prices WHERE tier_id = ?1 AND recipe_id = ?2",
+        [tier_id, recipe_id],
+        |row| row.get::<_, f32>(0),
+    );
+    match fixed_price {
+        Ok(price) => return Ok(Some(price)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => {}
+        Err(e) => return Err(e),
+    }
+
+    // A tier that no longer exists prices at plain MSRP
+    let discount: Option<f32> = match conn.query_row(
+        "SELECT discount_percent FROM price_tiers WHERE id = ?1",
+        [tier_id],
+        |row| row.get(0),
+    ) {
+        Ok(discount) => discount,
+        Err(rusqlite::Error::QueryReturnedNoRows) => None,
+        Err(e) => return Err(e),
+    };
+
+    Ok(match (msrp, discount) {
+        (Some(msrp), Some(discount)) => Some(msrp * (1.0 - discount / 100.0)),
+        (msrp, _) => msrp,
+    })
+}
+
+const INVOICE_COLUMNS: &str =
+    "id, number, customer_id, order_id, sale_transaction_id, issue_date, due_date, terms, tax_rate, deposit_applied, notes, voided";
+
+// Map an invoices row (selected with INVOICE_COLUMNS) into an Invoice
+fn invoice_from_row(row: &Row) -> Result<Invoice> {
+    Ok(Invoice {
+        id: row.get(0)?,
+        number: row.get(1)?,
+        customer_id: row.get(2)?,
+        order_id: row.get(3)?,
+        sale_transaction_id: row.get(4)?,
+        issue_date: row.get(5)?,
+        due_date: row.get(6)?,
+        terms: row.get(7)?,
+        tax_rate: row.get(8)?,
+        deposit_applied: row.get(9)?,
+        notes: row.get(10)?,
+        voided: row.get(11)?,
+    })
+}
+
+// Next sequential invoice number
+pub fn next_invoice_number(conn: &Connection) -> Result<i32> {
+    conn.query_row("SELECT COALESCE(MAX(number), 0) + 1 FROM invoices", [], |row| row.get(0))
+}
+
+// Create an invoice with its lines; the invoice number is assigned here
+// lines: (recipe_id, description, quantity, unit_price)
+pub fn add_invoice(conn: &Connection, invoice: &Invoice, lines: &[(Option<i32>, String, i32, f32)]) -> Result<i32> {
+    let db_tx = conn.unchecked_transaction()?;
+    let number = next_invoice_number(&db_tx)?;
+    db_tx.execute(
+        "INSERT INTO invoices (number, customer_id, order_id, sale_transaction_id, issue_date, due_date, terms, tax_rate, deposit_applied, notes)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            number, invoice.customer_id, invoice.order_id, invoice.sale_transaction_id, invoice.issue_date,
+            invoice.due_date, invoice.terms, invoice.tax_rate, invoice.deposit_applied, invoice.notes
+        ],
+    )?;
+    let invoice_id = db_tx.last_insert_rowid() as i32;
+
+    for (recipe_id, description, quantity, unit_price) in lines {
+        db_tx.execute(
+            "INSERT INTO invoice_lines (invoice_id, recipe_id, description, quantity, unit_price) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![invoice_id, recipe_id, description, quantity, unit_price],
+        )?;
+    }
+    db_tx.commit()?;
+
+    Ok(invoice_id)
+}
+
+// Invoice ids that already exist for an order or sale, so they aren't billed twice
+pub fn find_invoice_for_source(conn: &Connection, order_id: Option<i32>, sale_transaction_id: Option<i32>) -> Result<Option<i32>> {
+    let result = conn.query_row(
+        "SELECT id FROM invoices WHERE voided = 0 AND (order_id = ?1 OR sale_transaction_id = ?2)",
+        params![order_id, sale_transaction_id],
+        |row| row.get(0),
+    );
+
+    match result {
+        Ok(id) => Ok(Some(id)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// Load an invoice with its customer, lines, payments and totals
+pub fn get_invoice_details(conn: &Connection, invoice_id: i32) -> Result<Option<InvoiceDetails>> {
+    let invoice = match conn.query_row(
+        &format!("SELECT {} FROM invoices WHERE id = ?1", INVOICE_COLUMNS),
+        [invoice_id],
+        invoice_from_row,
+    ) {
+        Ok(invoice) => invoice,
+        Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    let customer = get_customer(conn, invoice.customer_id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, invoice_id, recipe_id, description, quantity, unit_price FROM invoice_lines WHERE invoice_id = ?1 ORDER BY id"
+    )?;
+    let line_iter = stmt.query_map([invoice_id], |row: &Row| {
+        Ok(InvoiceLine {
+            id: row.get(0)?,
+            invoice_id: row.get(1)?,
+            recipe_id: row.get(2)?,
+            description: row.get(3)?,
+            quantity: row.get(4)?,
+            unit_price: row.get(5)?,
+        })
+    })?;
+    let mut lines = Vec::new();
+    for line in line_iter {
+        lines.push(line?);
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT date, amount FROM transactions WHERE invoice_id = ?1 AND transaction_type = 'receipt' ORDER BY date, id"
+    )?;
+    let payment_iter = stmt.query_map([invoice_id], |row: &Row| {
+        Ok(InvoicePayment {
+            date: row.get(0)?,
+            amount: row.get(1)?,
+        })
+    })?;
+    let mut payments = Vec::new();
+    for payment in payment_iter {
+        payments.push(payment?);
+    }
+
+    Ok(Some(InvoiceDetails::new(invoice, customer, lines, payments)))
+}
+
+// Every invoice with its details, newest first
+pub fn get_all_invoice_details(conn: &Connection) -> Result<Vec<InvoiceDetails>> {
+    let mut stmt = conn.prepare("SELECT id FROM invoices ORDER BY number DESC")?;
+    let ids = stmt.query_map([], |row| row.get::<_, i32>(0))?;
+
+    let mut invoices = Vec::new();
+    for id in ids {
+        if let Some(details) = get_invoice_details(conn, id?)? {
+            invoices.push(details);
+        }
+    }
+
+    Ok(invoices)
+}
+
+// Record a payment received against an invoice as a 'receipt' on the ledger, so it can be
+// cleared in a reconciliation. The sale behind the invoice already posted the income, so
+// receipts settle the receivable and stay out of revenue, budgets and the P&L.
+pub fn record_invoice_payment(conn: &Connection, invoice_id: i32, date: &str, amount: f32, account: &str) -> Result<i32> {
+    let (number, customer_id): (i32, i32) = conn.query_row(
+        "SELECT number, customer_id FROM invoices WHERE id = ?1",
+        [invoice_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    conn.execute(
+        "INSERT INTO transactions (date, transaction_type, amount, description, account, customer_id, invoice_id)
+         VALUES (?1, 'receipt', ?2, ?3, ?4, ?5, ?6)",
+        params![date, amount, format!("Payment on {}", format_invoice_number(number)), account, customer_id, invoice_id],
+    )?;
+
+    Ok(conn.last_insert_rowid() as i32)
+}
+
+// Void an invoice; it stays on file but drops out of receivables
+pub fn void_invoice(conn: &Connection, invoice_id: i32) -> Result<bool> {
+    let updated = conn.execute("UPDATE invoices SET voided = 1 WHERE id = ?1 AND voided = 0", [invoice_id])?;
+    Ok(updated > 0)
+}
+
+// Add a state, county or city tax rate (percent)
+pub fn add_tax_jurisdiction(conn: &Connection, name: &str, level: &str, rate: f32) -> Result<i32> {
+    conn.execute(
+        "INSERT INTO tax_jurisdictions (name, level, rate) VALUES (?1, ?2, ?3)",
+        params![name, level, rate],
+    )?;
+
+    Ok(conn.last_insert_rowid() as i32)
+}
+
+// Change a jurisdiction's rate or switch it on/off
+pub fn update_tax_jurisdiction(conn: &Connection, jurisdiction_id: i32, rate: f32, active: bool) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE tax_jurisdictions SET rate = ?1, active = ?2 WHERE id = ?3",
+        params![rate, active, jurisdiction_id],
+    )?;
+
+    Ok(updated > 0)
+}
+
+// All tax jurisdictions, state first then county then city
+pub fn get_tax_jurisdictions(conn: &Connection) -> Result<Vec<TaxJurisdiction>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, level, rate, active FROM tax_jurisdictions
+         ORDER BY CASE level WHEN 'state' THEN 0 WHEN 'county' THEN 1 ELSE 2 END, name"
+    )?;
+
+    let jurisdiction_iter = stmt.query_map([], |row: &Row| {
+        Ok(TaxJurisdiction {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            level: row.get(2)?,
+            rate: row.get(3)?,
+            active: row.get(4)?,
+        })
+    })?;
+
+    let mut jurisdictions = Vec::new();
+    for jurisdiction in jurisdiction_iter {
+        jurisdictions.push(jurisdiction?);
+    }
+
+    Ok(jurisdictions)
+}
+
+// Combined rate of every active jurisdiction (percent)
+pub fn combined_tax_rate(conn: &Connection) -> Result<f32> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(rate), 0) FROM tax_jurisdictions WHERE active = 1",
+        [],
+        |row| row.get(0),
+    )
+}
+
+// Mark a recipe category as taxable or exempt
+pub fn set_category_taxable(conn: &Connection, category: &str, taxable: bool) -> Result<()> {
+    conn.execute(
+        "INSERT INTO category_tax_rules (category, taxable) VALUES (?1, ?2)
+         ON CONFLICT(category) DO UPDATE SET taxable = excluded.taxable",
+        params![category, taxable],
+    )?;
+
+    Ok(())
+}
+
+// Every recipe category with its taxability; categories without a rule are taxable
+pub fn get_category_tax_rules(conn: &Connection) -> Result<Vec<(String, bool)>> {
+    let mut stmt = conn.prepare(
+        "SELECT c.category, COALESCE(ctr.taxable, 1)
+         FROM (SELECT DISTINCT category FROM recipes
+               UNION SELECT category FROM category_tax_rules) c
+         LEFT JOIN category_tax_rules ctr ON ctr.category = c.category
+         ORDER BY c.category"
+    )?;
+
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+    let mut rules = Vec::new();
+    for row in rows {
+        rules.push(row?);
+    }
+
+    Ok(rules)
+}
+
+// Work out sales tax for a set of line items
+// Tax-exempt customers and exempt categories count towards exempt sales
+pub fn compute_sale_tax(conn: &Connection, customer_id: Option<i32>, items: &[(i32, i32, f32)]) -> Result<SaleTax> {
+    let customer_exempt: bool = match customer_id {
+        Some(id) => match conn.query_row("SELECT tax_exempt FROM customers WHERE id = ?1", [id], |row| row.get(0)) {
+            Ok(exempt) => exempt,
+            Err(rusqlite::Error::QueryReturnedNoRows) => false,
+            Err(e) => return Err(e),
+        },
+        None => false,
+    };
+
+    let mut gross = 0.0;
+    let mut taxable = 0.0;
+    for (recipe_id, quantity, unit_price) in items {
+        let line_total = *quantity as f32 * unit_price;
+        gross += line_total;
+
+        let category_taxable: bool = match conn.query_row(
+            "SELECT COALESCE(ctr.taxable, 1)
+             FROM recipes r
+             LEFT JOIN category_tax_rules ctr ON ctr.category = r.category
+             WHERE r.id = ?1",
+            [recipe_id],
+            |row| row.get(0),
+        ) {
+            Ok(taxable) => taxable,
+            Err(rusqlite::Error::QueryReturnedNoRows) => true,
+            Err(e) => return Err(e),
+        };
+
+        if category_taxable && !customer_exempt {
+            taxable += line_total;
+        }
+    }
+
+    let mut taxes = Vec::new();
+    for jurisdiction in get_tax_jurisdictions(conn)?.iter().filter(|j| j.active) {
+        // Round each jurisdiction's tax to the cent
+        let tax = (taxable * jurisdiction.rate / 100.0 * 100.0).round() / 100.0;
+        taxes.push((jurisdiction.id, tax));
+    }
+
+    Ok(SaleTax { gross, taxable, taxes })
+}
+
+// Sales tax recorded against a sale transaction (0 for lump-sum sales)
+pub fn get_sale_tax_total(conn: &Connection, transaction_id: i32) -> Result<f32> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(tax_amount), 0) FROM sale_taxes WHERE transaction_id = ?1",
+        [transaction_id],
+        |row| row.get(0),
+    )
+}
+
+// Sales-tax liability per jurisdiction for a filing period (dates inclusive)
+pub fn get_sales_tax_liability(conn: &Connection, start_date: &str, end_date: &str) -> Result<Vec<TaxLiability>> {
+    let mut stmt = conn.prepare(
+        "SELECT j.name, j.level, j.rate,
+                COALESCE(SUM(st.gross_amount), 0),
+                COALESCE(SUM(st.taxable_amount), 0),
+                COALESCE(SUM(st.tax_amount), 0)
+         FROM tax_jurisdictions j
+         LEFT JOIN sale_taxes st ON st.jurisdiction_id = j.id
+              AND st.transaction_id IN (SELECT id FROM transactions WHERE date BETWEEN ?1 AND ?2)
+         GROUP BY j.id
+         ORDER BY CASE j.level WHEN 'state' THEN 0 WHEN 'county' THEN 1 ELSE 2 END, j.name"
+    )?;
+
+    let rows = stmt.query_map(params![start_date, end_date], |row| {
+        let gross_sales: f32 = row.get(3)?;
+        let taxable_sales: f32 = row.get(4)?;
+        Ok(TaxLiability {
+            jurisdiction: row.get(0)?,
+            level: row.get(1)?,
+            rate: row.get(2)?,
+            gross_sales,
+            exempt_sales: gross_sales - taxable_sales,
+            taxable_sales,
+            tax_collected: row.get(5)?,
+        })
+    })?;
+
+    let mut liabilities = Vec::new();
+    for row in rows {
+        liabilities.push(row?);
+    }
+
+    Ok(liabilities)
+}
+
+// Sales in the period recorded without tax detail (lump-sum sales): (count, total)
+// Order deposits are left out; the order's itemized sale carries their tax detail
+pub fn get_unclassified_sales(conn: &Connection, start_date: &str, end_date: &str) -> Result<(i32, f32)> {
+    conn.query_row(
+        "SELECT COUNT(*), COALESCE(SUM(amount), 0) FROM transactions t
+         WHERE t.transaction_type = 'sale' AND t.date BETWEEN ?1 AND ?2 AND t.order_id IS NULL
+           AND NOT EXISTS (SELECT 1 FROM sale_items si WHERE si.transaction_id = t.id)",
+        params![start_date, end_date],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+}
+
+const RECURRING_COLUMNS: &str =
+    "id, description, transaction_type, amount, is_estimate, account, frequency, interval, day_of_month, start_date, end_date, last_posted_date, category";
+
+// Map a recurring_transactions row (selected with RECURRING_COLUMNS) into a RecurringTransaction
+fn recurring_from_row(row: &Row) -> Result<RecurringTransaction> {
+    Ok(RecurringTransaction {
+        id: row.get(0)?,
+        description: row.get(1)?,
+        transaction_type: row.get(2)?,
+        amount: row.get(3)?,
+        is_estimate: row.get(4)?,
+        account: row.get(5)?,
+        frequency: row.get(6)?,
+        interval: row.get(7)?,
+        day_of_month: row.get(8)?,
+        start_date: row.get(9)?,
+        end_date: row.get(10)?,
+        last_posted_date: row.get(11)?,
+        category: row.get(12)?,
+    })
+}
+
+// Save a recurring transaction template
+pub fn add_recurring_transaction(conn: &Connection, recurring: &RecurringTransaction) -> Result<i32> {
+    conn.execute(
+        "INSERT INTO recurring_transactions
+         (description, transaction_type, amount, is_estimate, account, frequency, interval, day_of_month, start_date, end_date, category)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            recurring.description, recurring.transaction_type, recurring.amount, recurring.is_estimate,
+            recurring.account, recurring.frequency, recurring.interval, recurring.day_of_month,
+            recurring.start_date, recurring.end_date, recurring.category
+        ],
+    )?;
+
+    Ok(conn.last_insert_rowid() as i32)
+}
+
+// All recurring transaction templates
+pub fn get_recurring_transactions(conn: &Connection) -> Result<Vec<RecurringTransaction>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM recurring_transactions ORDER BY description", RECURRING_COLUMNS))?;
+
+    let recurring_iter = stmt.query_map([], recurring_from_row)?;
+
+    let mut recurring = Vec::new();
+    for item in recurring_iter {
+        recurring.push(item?);
+    }
+
+    Ok(recurring)
+}
+
+// Stop a recurring transaction from posting after the given date
+pub fn end_recurring_transaction(conn: &Connection, recurring_id: i32, end_date: &str) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE recurring_transactions SET end_date = ?1 WHERE id = ?2",
+        params![end_date, recurring_id],
+    )?;
+
+    Ok(updated > 0)
+}
+
+// Post due occurrences as transactions and advance each template's last posted date
+pub fn post_recurring_occurrences(conn: &Connection, occurrences: &[RecurringOccurrences]) -> Result<usize> {
+    let db_tx = conn.unchecked_transaction()?;
+    let mut posted = 0;
+
+    for (recurring, dates) in occurrences {
+        for (date, actual) in dates {
+            // Only an estimate posted as-is is marked as one
+            let description = if recurring.is_estimate && actual.is_none() {
+                format!("{} (estimated)", recurring.description)
+            } else {
+                recurring.description.clone()
+            };
+            db_tx.execute(
+                "INSERT INTO transactions (date, transaction_type, amount, description, account, recurring_id, category)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    date.format("%Y-%m-%d").to_string(), recurring.transaction_type, actual.unwrap_or(recurring.amount),
+                    description, recurring.account, recurring.id, recurring.category
+                ],
+            )?;
+            posted += 1;
+        }
+
+        if let Some((last, _)) = dates.iter().max_by_key(|(date, _)| *date) {
+            db_tx.execute(
+                "UPDATE recurring_transactions SET last_posted_date = ?1 WHERE id = ?2",
+                params![last.format("%Y-%m-%d").to_string(), recurring.id],
+            )?;
+        }
+    }
+    db_tx.commit()?;
+
+    Ok(posted)
+}
+
+// Set (or replace) the budget for a category in a YYYY-MM month
+pub fn set_budget(conn: &Connection, month: &str, category: &str, budget_type: &str, amount: f32) -> Result<()> {
+    conn.execute(
+        "INSERT INTO budgets (month, category, budget_type, amount) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(month, category, budget_type) DO UPDATE SET amount = excluded.amount",
+        params![month, category, budget_type, amount],
+    )?;
+
+    Ok(())
+}
+
+// A transaction's amount less any sales tax collected on it; the tax is owed to the
+// jurisdictions, so revenue figures leave it out. Expects the transactions alias `t`.
+const NET_OF_TAX_AMOUNT: &str =
+    "(t.amount - COALESCE((SELECT SUM(st.tax_amount) FROM sale_taxes st WHERE st.transaction_id = t.id), 0))";
+
+// Budget vs actual for every budgeted (or active) category in a YYYY-MM month
+// Sales count as revenue (net of sales tax), expenses as expense; uncategorized transactions show as "Uncategorized"
+pub fn get_budget_vs_actual(conn: &Connection, month: &str) -> Result<Vec<BudgetLine>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT c.category, c.budget_type, COALESCE(b.amount, 0),
+                (SELECT COALESCE(SUM({}), 0) FROM transactions t
+                 WHERE substr(t.date, 1, 7) = ?1
+                   AND COALESCE(t.category, 'Uncategorized') = c.category
+                   AND t.transaction_type = CASE c.budget_type WHEN 'revenue' THEN 'sale' ELSE 'expense' END)
+         FROM (SELECT category, budget_type FROM budgets WHERE month = ?1
+               UNION
+               SELECT COALESCE(category, 'Uncategorized'),
+                      CASE transaction_type WHEN 'sale' THEN 'revenue' ELSE 'expense' END
+               FROM transactions
+               WHERE substr(date, 1, 7) = ?1 AND transaction_type IN ('sale', 'expense')) c
+         LEFT JOIN budgets b ON b.month = ?1 AND b.category = c.category AND b.budget_type = c.budget_type
+         ORDER BY c.budget_type DESC, c.category", NET_OF_TAX_AMOUNT))?;
+
+    let rows = stmt.query_map([month], |row: &Row| {
+        Ok(BudgetLine {
+            category: row.get(0)?,
+            budget_type: row.get(1)?,
+            budgeted: row.get(2)?,
+            actual: row.get(3)?,
+        })
+    })?;
+
+    let mut lines = Vec::new();
+    for row in rows {
+        lines.push(row?);
+    }
+
+    Ok(lines)
+}
+
+// Expense categories whose actual spend has already passed the month's budget
+pub fn get_over_budget_categories(conn: &Connection, month: &str) -> Result<Vec<BudgetLine>> {
+    Ok(get_budget_vs_actual(conn, month)?
+        .into_iter()
+        .filter(|line| line.budget_type == "expense" && line.budgeted > 0.0 && line.actual > line.budgeted)
+        .collect())
+}
+
+// Copy one year's monthly actuals into next year's budgets with a percentage uplift
+// Returns the number of budget lines written
+pub fn copy_actuals_to_budget(conn: &Connection, from_year: i32, to_year: i32, uplift_percent: f32) -> Result<usize> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT substr(t.date, 6, 2), COALESCE(t.category, 'Uncategorized'),
+                CASE t.transaction_type WHEN 'sale' THEN 'revenue' ELSE 'expense' END, SUM({})
+         FROM transactions t
+         WHERE substr(t.date, 1, 4) = ?1 AND t.transaction_type IN ('sale', 'expense')
+         GROUP BY 1, 2, 3", NET_OF_TAX_AMOUNT))?;
+
+    let rows = stmt.query_map([from_year.to_string()], |row| {
+        Ok((
+            row.get::<_, String>(0)?, // month number
+            row.get::<_, String>(1)?, // category
+            row.get::<_, String>(2)?, // budget type
+            row.get::<_, f32>(3)?,    // actual
+        ))
+    })?;
+
+    let mut actuals = Vec::new();
+    for row in rows {
+        actuals.push(row?);
+    }
+
+    let db_tx = conn.unchecked_transaction()?;
+    for (month, category, budget_type, actual) in &actuals {
+        let amount = (actual * (1.0 + uplift_percent / 100.0) * 100.0).round() / 100.0;
+        set_budget(&db_tx, &format!("{}-{}", to_year, month), category, budget_type, amount)?;
+    }
+    db_tx.commit()?;
+
+    Ok(actuals.len())
+}
+
+// Distinct transaction categories in use, for prompts
+pub fn get_transaction_categories(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT category FROM transactions WHERE category IS NOT NULL
+         UNION SELECT category FROM budgets
+         ORDER BY 1"
+    )?;
+
+    let rows = stmt.query_map([], |row| row.get(0))?;
+
+    let mut categories = Vec::new();
+    for row in rows {
+        categories.push(row?);
+    }
+
+    Ok(categories)
+}
+
+// Current value of stock on hand (quantity * cost_per_unit)
+pub fn inventory_value(conn: &Connection) -> Result<f32> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(quantity * cost_per_unit), 0) FROM inventory",
+        [],
+        |row| row.get(0),
+    )
+}
+
+// Total of expense transactions in a purchase category between two dates (inclusive)
+pub fn get_purchases_total(conn: &Connection, start_date: &str, end_date: &str, category: &str) -> Result<f32> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(amount), 0) FROM transactions
+         WHERE transaction_type = 'expense' AND date BETWEEN ?1 AND ?2 AND category = ?3 COLLATE NOCASE",
+        params![start_date, end_date, category],
+        |row| row.get(0),
+    )
+}
+
+// Theoretical COGS per recipe from production runs between two dates (inclusive)
+// Runs recorded before batch costs were stored fall back to today's recipe cost
+pub fn get_theoretical_cogs(conn: &Connection, start_date: &str, end_date: &str) -> Result<Vec<CogsLine>> {
+    let mut stmt = conn.prepare(
+        "SELECT r.id, r.name, SUM(p.batches), SUM(p.units_produced),
+                COALESCE(SUM(p.batches * p.batch_cost), 0),
+                SUM(CASE WHEN p.batch_cost IS NULL THEN p.batches ELSE 0 END)
+         FROM production_runs p
+         JOIN recipes r ON p.recipe_id = r.id
+         WHERE p.date BETWEEN ?1 AND ?2
+         GROUP BY r.id
+         ORDER BY r.name"
+    )?;
+
+    let rows = stmt.query_map([start_date, end_date], |row| {
+        Ok((
+            row.get::<_, i32>(0)?,    // recipe_id
+            row.get::<_, String>(1)?, // recipe_name
+            row.get::<_, f32>(2)?,    // batches
+            row.get::<_, i32>(3)?,    // units_produced
+            row.get::<_, f32>(4)?,    // cost of runs with a stored batch cost
+            row.get::<_, f32>(5)?,    // batches without a stored batch cost
+        ))
+    })?;
+
+    let mut lines = Vec::new();
+    for row in rows {
+        let (recipe_id, recipe_name, batches, units_produced, priced_cost, unpriced_batches) = row?;
+        let mut theoretical_cost = priced_cost;
+        if unpriced_batches > 0.0 {
+            theoretical_cost += unpriced_batches * calculate_recipe_cost(conn, recipe_id)?;
+        }
+
+        lines.push(CogsLine { recipe_id, recipe_name, batches, units_produced, theoretical_cost });
+    }
+
+    Ok(lines)
+}
+
+// Theoretical vs actual COGS for a period
+// Actual COGS = opening inventory value + purchases - closing inventory value
+pub fn get_cogs_report(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+    purchase_category: &str,
+    opening_value: f32,
+    closing_value: f32,
+    ) -> Result<CogsReport> {
+    Ok(CogsReport {
+        start_date: start_date.to_string(),
+        end_date: end_date.to_string(),
+        lines: get_theoretical_cogs(conn, start_date, end_date)?,
+        opening_value,
+        purchases: get_purchases_total(conn, start_date, end_date, purchase_category)?,
+        closing_value,
+    })
+}
+
+// Inventory items ordered for a valuation report: by category, then name
+pub fn get_inventory_valuation(conn: &Connection) -> Result<Vec<InventoryItem>> {
+    let mut items = get_all_inventory(conn)?;
+    items.sort_by(|a, b| a.category_label().cmp(b.category_label()).then_with(|| a.name.cmp(&b.name)));
+
+    Ok(items)
+}
+
+// Store a dated copy of every inventory row; returns the snapshot id
+pub fn take_inventory_snapshot(conn: &Connection, snapshot_date: &str, label: Option<&str>) -> Result<i32> {
+    let db_tx = conn.unchecked_transaction()?;
+    db_tx.execute(
+        "INSERT INTO inventory_snapshots (snapshot_date, label) VALUES (?1, ?2)",
+        params![snapshot_date, label],
+    )?;
+    let snapshot_id = db_tx.last_insert_rowid() as i32;
+
+    db_tx.execute(
+        "INSERT INTO inventory_snapshot_items (snapshot_id, inventory_id, name, unit, category, quantity, cost_per_unit)
+         SELECT ?1, id, name, unit, category, quantity, cost_per_unit FROM inventory",
+        [snapshot_id],
+    )?;
+    db_tx.commit()?;
+
+    Ok(snapshot_id)
+}
+
+const SNAPSHOT_COLUMNS: &str =
+    "s.id, s.snapshot_date, s.label, COALESCE(SUM(i.quantity * i.cost_per_unit), 0), COUNT(i.inventory_id)";
+
+fn snapshot_from_row(row: &Row) -> Result<InventorySnapshot> {
+    Ok(InventorySnapshot {
+        id: row.get(0)?,
+        snapshot_date: row.get(1)?,
+        label: row.get(2)?,
+        total_value: row.get(3)?,
+        item_count: row.get(4)?,
+    })
+}
+
+// All snapshots, newest first, with their total value
+pub fn get_inventory_snapshots(conn: &Connection) -> Result<Vec<InventorySnapshot>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM inventory_snapshots s
+         LEFT JOIN inventory_snapshot_items i ON i.snapshot_id = s.id
+         GROUP BY s.id
+         ORDER BY s.snapshot_date DESC, s.id DESC",
+        SNAPSHOT_COLUMNS
+    ))?;
+
+    let rows = stmt.query_map([], snapshot_from_row)?;
+
+    let mut snapshots = Vec::new();
+    for row in rows {
+        snapshots.push(row?);
+    }
+
+    Ok(snapshots)
+}
+
+// Most recent snapshot taken on or before a date
+pub fn get_snapshot_on_or_before(conn: &Connection, date: &str) -> Result<Option<InventorySnapshot>> {
+    match conn.query_row(
+        &format!(
+            "SELECT {} FROM inventory_snapshots s
+             LEFT JOIN inventory_snapshot_items i ON i.snapshot_id = s.id
+             WHERE s.snapshot_date <= ?1
+             GROUP BY s.id
+             ORDER BY s.snapshot_date DESC, s.id DESC
+             LIMIT 1",
+            SNAPSHOT_COLUMNS
+        ),
+        [date],
+        snapshot_from_row,
+    ) {
+        Ok(snapshot) => Ok(Some(snapshot)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// Inventory rows as they were recorded in a snapshot, ordered for valuation
+pub fn get_snapshot_items(conn: &Connection, snapshot_id: i32) -> Result<Vec<InventoryItem>> {
+    let mut stmt = conn.prepare(
+        "SELECT inventory_id, name, unit, quantity, cost_per_unit, category
+         FROM inventory_snapshot_items
+         WHERE snapshot_id = ?1
+         ORDER BY COALESCE(category, 'Uncategorized'), name"
+    )?;
+
+    let rows = stmt.query_map([snapshot_id], |row: &Row| {
+        Ok(InventoryItem {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            unit: row.get(2)?,
+            quantity: row.get(3)?,
+            cost_per_unit: row.get(4)?,
+            category: row.get(5)?,
+            role: None,
+        })
+    })?;
+
+    let mut items = Vec::new();
+    for row in rows {
+        items.push(row?);
+    }
+
+    Ok(items)
+}
+
+// Look up an inventory item id by name, ignoring case
+pub fn find_inventory_id_by_name(conn: &Connection, name: &str) -> Result<Option<i32>> {
+    match conn.query_row(
+        "SELECT id FROM inventory WHERE name = ?1 COLLATE NOCASE ORDER BY id LIMIT 1",
+        [name],
+        |row| row.get(0),
+    ) {
+        Ok(id) => Ok(Some(id)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// Look up a recipe id by name, ignoring case
+pub fn find_recipe_id_by_name(conn: &Connection, name: &str) -> Result<Option<i32>> {
+    match conn.query_row(
+        "SELECT id FROM recipes WHERE name = ?1 COLLATE NOCASE ORDER BY id LIMIT 1",
+        [name],
+        |row| row.get(0),
+    ) {
+        Ok(id) => Ok(Some(id)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// Insert an inventory item, or update the existing item with the same name
+// Returns true when a new item was inserted
+pub fn upsert_inventory_item(conn: &Connection, item: &InventoryItem) -> Result<bool> {
+    match find_inventory_id_by_name(conn, &item.name)? {
+        Some(id) => {
+            conn.execute(
+                "UPDATE inventory SET unit = ?1, quantity = ?2, cost_per_unit = ?3, category = COALESCE(?4, category),
+                        role = COALESCE(?5, role)
+                 WHERE id = ?6",
+                params![item.unit, item.quantity, item.cost_per_unit, item.category, item.role, id],
+            )?;
+            Ok(false)
+        }
+        None => {
+            add_inventory_item(conn, &item.name, &item.unit, item.quantity, item.cost_per_unit,
+                item.category.as_deref(), item.role.as_deref())?;
+            Ok(true)
+        }
+    }
+}
+
+// Insert a recipe, or update the existing recipe with the same name
+// pan_id must be a pan in this database; None keeps the recipe's current pan
+// Returns true when a new recipe was inserted
+pub fn upsert_recipe(conn: &Connection, recipe: &RecipeCollection) -> Result<bool> {
+    match find_recipe_id_by_name(conn, &recipe.name)? {
+        Some(id) => {
+            let old_instructions: String = conn.query_row(
+                "SELECT instructions FROM recipes WHERE id = ?1",
+                [id],
+                |row| row.get(0),
+            )?;
+            conn.execute(
+                "UPDATE recipes SET instructions = ?1, yield_quantity = ?2, category = ?3, prep_time = ?4,
+                        bake_time = ?5, total_time = ?6, msrp_per_unit = COALESCE(?7, msrp_per_unit),
+                        target_ddt_c = COALESCE(?8, target_ddt_c), friction_factor_c = COALESCE(?9, friction_factor_c),
+                        pan_id = COALESCE(?10, pan_id)
+                 WHERE id = ?11",
+                params![
+                    recipe.instructions, recipe.yield_quantity, recipe.category, recipe.prep_time,
+                    recipe.bake_time, recipe.total_time, recipe.msrp_per_unit, recipe.target_ddt_c,
+                    recipe.friction_factor_c, recipe.pan_id, id
+                ],
+            )?;
+            // New instructions replace the old steps; unchanged ones keep any edits
+            if old_instructions != recipe.instructions {
+                conn.execute("DELETE FROM recipe_steps WHERE recipe_id = ?1", [id])?;
+                insert_recipe_steps(conn, id, &recipe_steps_from_instructions(recipe))?;
+            }
+            Ok(false)
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO recipes (name, instructions, yield_quantity, category, prep_time, bake_time, total_time, msrp_per_unit,
+                                      target_ddt_c, friction_factor_c, pan_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+                params![
+                    recipe.name, recipe.instructions, recipe.yield_quantity, recipe.category, recipe.prep_time,
+                    recipe.bake_time, recipe.total_time, recipe.msrp_per_unit, recipe.target_ddt_c, recipe.friction_factor_c,
+                    recipe.pan_id
+                ],
+            )?;
+            let steps = recipe_steps_from_instructions(recipe);
+            insert_recipe_steps(conn, conn.last_insert_rowid() as i32, &steps)?;
+            Ok(true)
+        }
+    }
+}
+
+// Insert a new recipe and its ingredient lines (ingredient id, quantity) in one transaction
+// Returns the new recipe id
+pub fn add_recipe_with_ingredients(conn: &Connection, recipe: &RecipeCollection, lines: &[(i32, f32)]) -> Result<i32> {
+    let tx = conn.unchecked_transaction()?;
+    let recipe_id = insert_recipe_with_ingredients(&tx, recipe, lines, &recipe_steps_from_instructions(recipe))?;
+    tx.commit()?;
+
+    Ok(recipe_id)
+}
+
+// Save a variant of another recipe in one transaction: the new recipe, its lines and a copy of the original's steps.
+// `new_item` is an inventory item to add first, used as one more line at the given quantity.
+// Returns the new recipe id
+pub fn add_recipe_variant(
+    conn: &Connection,
+    recipe: &RecipeCollection,
+    lines: &[(i32, f32)],
+    original_id: i32,
+    new_item: Option<(&InventoryItem, f32)>,
+    ) -> Result<i32> {
+    let tx = conn.unchecked_transaction()?;
+    let mut lines = lines.to_vec();
+    if let Some((item, quantity)) = new_item {
+        add_inventory_item(&tx, &item.name, &item.unit, item.quantity, item.cost_per_unit,
+            item.category.as_deref(), item.role.as_deref())?;
+        lines.push((tx.last_insert_rowid() as i32, quantity));
+    }
+    let steps = get_recipe_steps(&tx, original_id)?;
+    let recipe_id = insert_recipe_with_ingredients(&tx, recipe, &lines, &steps)?;
+    tx.commit()?;
+
+    Ok(recipe_id)
+}
+
+// Insert a recipe, its ingredient lines and its steps without opening a transaction
+fn insert_recipe_with_ingredients(
+    conn: &Connection,
+    recipe: &RecipeCollection,
+    lines: &[(i32, f32)],
+    steps: &[RecipeStep],
+    ) -> Result<i32> {
+    conn.execute(
+        "INSERT INTO recipes (name, instructions, yield_quantity, category, prep_time, bake_time, total_time, msrp_per_unit,
+                              target_ddt_c, friction_factor_c, pan_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            recipe.name, recipe.instructions, recipe.yield_quantity, recipe.category, recipe.prep_time,
+            recipe.bake_time, recipe.total_time, recipe.msrp_per_unit, recipe.target_ddt_c, recipe.friction_factor_c,
+            recipe.pan_id
+        ],
+    )?;
+    let recipe_id = conn.last_insert_rowid() as i32;
+    for (ingredient_id, quantity_required) in lines {
+        conn.execute(
+            "INSERT INTO recipe_ingredients (recipe_id, ingredient_id, quantity_required) VALUES (?1, ?2, ?3)",
+            params![recipe_id, ingredient_id, quantity_required],
+        )?;
+    }
+    insert_recipe_steps(conn, recipe_id, steps)?;
+
+    Ok(recipe_id)
+}
+
+// Set the quantity of an ingredient in a recipe, adding the line if it is new
+// Returns true when a new line was inserted
+pub fn upsert_recipe_ingredient(conn: &Connection, recipe_id: i32, ingredient_id: i32, quantity_required: f32) -> Result<bool> {
+    let updated = conn.execute(
+        "UPDATE recipe_ingredients SET quantity_required = ?1 WHERE recipe_id = ?2 AND ingredient_id = ?3",
+        params![quantity_required, recipe_id, ingredient_id],
+    )?;
+    if updated > 0 {
+        return Ok(false);
+    }
+
+    conn.execute(
+        "INSERT INTO recipe_ingredients (recipe_id, ingredient_id, quantity_required) VALUES (?1, ?2, ?3)",
+        params![recipe_id, ingredient_id, quantity_required],
+    )?;
+    Ok(true)
+}
+
+// Every recipe ingredient line, as stored
+pub fn get_all_recipe_ingredients(conn: &Connection) -> Result<Vec<RecipeIngredient>> {
+    let mut stmt = conn.prepare(
+        "SELECT recipe_id, ingredient_id, quantity_required FROM recipe_ingredients ORDER BY recipe_id, ingredient_id"
+    )?;
+
+    let rows = stmt.query_map([], |row: &Row| {
+        Ok(RecipeIngredient {
+            recipe_id: row.get(0)?,
+            ingredient_id: row.get(1)?,
+            quantity_required: row.get(2)?,
+        })
+    })?;
+
+    let mut lines = Vec::new();
+    for row in rows {
+        lines.push(row?);
+    }
+
+    Ok(lines)
+}
+
+// Revenue (sales, net of sales tax) and expenses per day, or per month with `by_month`, between two dates (inclusive)
+pub fn get_revenue_expense_series(conn: &Connection, start_date: &str, end_date: &str, by_month: bool) -> Result<Vec<(String, f32, f32)>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT CASE WHEN ?3 THEN substr(t.date, 1, 7) ELSE t.date END AS period,
+                COALESCE(SUM(CASE WHEN t.transaction_type = 'sale' THEN {} END), 0),
+                COALESCE(SUM(CASE WHEN t.transaction_type = 'expense' THEN t.amount END), 0)
+         FROM transactions t
+         WHERE t.date BETWEEN ?1 AND ?2
+         GROUP BY period
+         ORDER BY period", NET_OF_TAX_AMOUNT))?;
+
+    let rows = stmt.query_map(params![start_date, end_date, by_month], |row| {
+        Ok((
+            row.get(0)?, // period
+            row.get(1)?, // revenue
+            row.get(2)?, // expenses
+        ))
+    })?;
+
+    let mut series = Vec::new();
+    for row in rows {
+        series.push(row?);
+    }
+
+    Ok(series)
+}
+
+// Recipe ingredients with less than `min_batches` batches' worth on hand,
+// measured against the recipe that uses the most of each; returns (item, batches left)
+pub fn get_low_stock_items(conn: &Connection, min_batches: f32) -> Result<Vec<(InventoryItem, f32)>> {
+    let mut stmt = conn.prepare(
+        "SELECT i.id, i.name, i.unit, i.quantity, i.cost_per_unit, i.category, i.role, MAX(ri.quantity_required)
+         FROM inventory i
+         JOIN recipe_ingredients ri ON ri.ingredient_id = i.id
+         WHERE ri.quantity_required > 0
+         GROUP BY i.id
+         HAVING i.quantity < ?1 * MAX(ri.quantity_required)
+         ORDER BY i.quantity / MAX(ri.quantity_required), i.name"
+    )?;
+
+    let rows = stmt.query_map([min_batches], |row: &Row| {
+        let item = InventoryItem {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            unit: row.get(2)?,
+            quantity: row.get(3)?,
+            cost_per_unit: row.get(4)?,
+            category: row.get(5)?,
+            role: row.get(6)?,
+        };
+        let per_batch: f32 = row.get(7)?;
+        let batches_left = (item.quantity / per_batch).max(0.0);
+        Ok((item, batches_left))
+    })?;
+
+    let mut items = Vec::new();
+    for row in rows {
+        items.push(row?);
+    }
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seeded_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_db(&conn).unwrap();
+        seed_inventory(&conn).unwrap();
+        seed_recipes(&conn).unwrap();
+        conn
+    }
+
+    fn order(customer_id: Option<i32>) -> Order {
+        Order {
+            id: 0,
+            customer_name: "Corner Cafe".to_string(),
+            customer_contact: None,
+            due_date: "2026-03-02".to_string(),
+            due_time: None,
+            fulfilment: "pickup".to_string(),
+            deposit_paid: 0.0,
+            status: "confirmed".to_string(),
+            notes: None,
+            created_date: "2026-03-01".to_string(),
+            sale_transaction_id: None,
+            customer_id,
+        }
+    }
+
+    fn order_rows(conn: &Connection, order_id: i32) -> Vec<(String, f32)> {
+        let mut stmt = conn.prepare("SELECT transaction_type, amount FROM transactions WHERE order_id = ?1 ORDER BY id").unwrap();
+        stmt.query_map([order_id], |row| Ok((row.get(0)?, row.get(1)?))).unwrap().map(|r| r.unwrap()).collect()
+    }
+
+    #[test]
+    fn deposits_stop_at_the_order_total_with_tax() {
+        let conn = seeded_db();
+        add_tax_jurisdiction(&conn, "State", "state", 10.0).unwrap();
+        let recipe_id = find_recipe_id_by_name(&conn, "Babka").unwrap().unwrap();
+        let order_id = add_order(&conn, &order(None), &[(recipe_id, 10, 3.0)]).unwrap();
+
+        // $30 of product plus $3 tax
+        assert!(!add_order_deposit(&conn, order_id, 33.5, "2026-03-01", "Operating", Some("Sales")).unwrap());
+        assert!(add_order_deposit(&conn, order_id, 20.0, "2026-03-01", "Operating", Some("Sales")).unwrap());
+        assert!(!add_order_deposit(&conn, order_id, 13.5, "2026-03-01", "Operating", Some("Sales")).unwrap());
+        assert!(add_order_deposit(&conn, order_id, 13.0, "2026-03-01", "Operating", Some("Sales")).unwrap());
+        assert_eq!(get_order(&conn, order_id).unwrap().unwrap().deposit_paid, 33.0);
+
+        // Fully paid up front: fulfilment posts no zero sale and keeps the lines on the last deposit
+        let sale_id = fulfil_order(&conn, order_id, "2026-03-02", "Operating", Some("Sales")).unwrap().unwrap();
+        assert_eq!(order_rows(&conn, order_id), vec![("sale".to_string(), 20.0), ("sale".to_string(), 13.0)]);
+        assert_eq!(get_sale_items(&conn, sale_id).unwrap().len(), 1);
+        assert_eq!(get_sale_tax_total(&conn, sale_id).unwrap(), 3.0);
+    }
+
+    #[test]
+    fn fulfilment_nets_deposits_and_refunds_any_overpayment() {
+        let conn = seeded_db();
+        let recipe_id = find_recipe_id_by_name(&conn, "Babka").unwrap().unwrap();
+
+        let part_paid = add_order(&conn, &order(None), &[(recipe_id, 10, 3.0)]).unwrap();
+        add_order_deposit(&conn, part_paid, 10.0, "2026-03-01", "Operating", Some("Sales")).unwrap();
+        fulfil_order(&conn, part_paid, "2026-03-02", "Operating", Some("Sales")).unwrap().unwrap();
+        assert_eq!(order_rows(&conn, part_paid), vec![("sale".to_string(), 10.0), ("sale".to_string(), 20.0)]);
+
+        // The order shrank after a full deposit was taken
+        let shrunk = add_order(&conn, &order(None), &[(recipe_id, 10, 3.0)]).unwrap();
+        add_order_deposit(&conn, shrunk, 30.0, "2026-03-01", "Operating", Some("Sales")).unwrap();
+        conn.execute("UPDATE order_items SET quantity = 6 WHERE order_id = ?1", [shrunk]).unwrap();
+        fulfil_order(&conn, shrunk, "2026-03-02", "Operating", Some("Sales")).unwrap().unwrap();
+        assert_eq!(order_rows(&conn, shrunk), vec![("sale".to_string(), 30.0), ("expense".to_string(), 12.0)]);
+
+        assert_eq!(fulfil_order(&conn, shrunk, "2026-03-03", "Operating", Some("Sales")).unwrap(), None);
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.001
+    }
+
+    // State, county and city rates, plus a city rate that has been switched off
+    fn stacked_rates(conn: &Connection) -> Vec<i32> {
+        let state = add_tax_jurisdiction(conn, "State", "state", 6.0).unwrap();
+        let county = add_tax_jurisdiction(conn, "County", "county", 1.25).unwrap();
+        let city = add_tax_jurisdiction(conn, "City", "city", 0.5).unwrap();
+        let old_city = add_tax_jurisdiction(conn, "Old City", "city", 2.0).unwrap();
+        update_tax_jurisdiction(conn, old_city, 2.0, false).unwrap();
+        vec![state, county, city]
+    }
+
+    // $30 of Babka (Bread) and $20 of croissants (Pastry)
+    fn mixed_sale(conn: &Connection) -> Vec<(i32, i32, f32)> {
+        let babka = find_recipe_id_by_name(conn, "Babka").unwrap().unwrap();
+        let croissant = find_recipe_id_by_name(conn, "Croissant aux Amandes").unwrap().unwrap();
+        vec![(babka, 10, 3.0), (croissant, 5, 4.0)]
+    }
+
+    #[test]
+    fn stacks_every_active_jurisdiction() {
+        let conn = seeded_db();
+        let rates = stacked_rates(&conn);
+
+        let tax = compute_sale_tax(&conn, None, &mixed_sale(&conn)).unwrap();
+        assert_eq!((tax.gross, tax.taxable), (50.0, 50.0));
+        // Each jurisdiction rounds to the cent on its own: 3.00 + 0.625 -> 0.63 + 0.25
+        assert_eq!(tax.taxes, vec![(rates[0], 3.0), (rates[1], 0.63), (rates[2], 0.25)]);
+        assert!(close(tax.total(), 53.88));
+
+        // A customer id that no longer exists is taxed like a walk-in
+        assert_eq!(compute_sale_tax(&conn, Some(999), &mixed_sale(&conn)).unwrap().taxes, tax.taxes);
+    }
+
+    #[test]
+    fn exempt_categories_and_customers_pay_no_tax() {
+        let conn = seeded_db();
+        let rates = stacked_rates(&conn);
+
+        set_category_taxable(&conn, "Bread", false).unwrap();
+        let tax = compute_sale_tax(&conn, None, &mixed_sale(&conn)).unwrap();
+        assert_eq!((tax.gross, tax.taxable), (50.0, 20.0));
+        assert_eq!(tax.taxes, vec![(rates[0], 1.2), (rates[1], 0.25), (rates[2], 0.1)]);
+
+        let exempt = Customer {
+            id: 0,
+            name: "School District".to_string(),
+            email: None,
+            phone: None,
+            address: None,
+            customer_type: "wholesale".to_string(),
+            price_tier_id: None,
+            tax_exempt: true,
+            notes: None,
+        };
+        let customer_id = add_customer(&conn, &exempt).unwrap();
+        let tax = compute_sale_tax(&conn, Some(customer_id), &mixed_sale(&conn)).unwrap();
+        assert_eq!((tax.gross, tax.taxable), (50.0, 0.0));
+        assert_eq!(tax.total_tax(), 0.0);
+    }
+
+    #[test]
+    fn liability_report_totals_the_filing_period_per_jurisdiction() {
+        let conn = seeded_db();
+        stacked_rates(&conn);
+        set_category_taxable(&conn, "Bread", false).unwrap();
+        add_itemized_sale(&conn, "2026-03-05", "Counter", "Operating", Some("Sales"), None, &mixed_sale(&conn)).unwrap();
+        add_itemized_sale(&conn, "2026-03-31", "Counter", "Operating", Some("Sales"), None, &mixed_sale(&conn)).unwrap();
+        add_itemized_sale(&conn, "2026-04-01", "Counter", "Operating", Some("Sales"), None, &mixed_sale(&conn)).unwrap();
+
+        let report = get_sales_tax_liability(&conn, "2026-03-01", "2026-03-31").unwrap();
+        let names: Vec<&str> = report.iter().map(|line| line.jurisdiction.as_str()).collect();
+        assert_eq!(names, vec!["State", "County", "City", "Old City"]);
+
+        let state = &report[0];
+        assert_eq!((state.gross_sales, state.taxable_sales, state.exempt_sales), (100.0, 40.0, 60.0));
+        assert!(close(state.tax_collected, 2.4));
+        assert!(close(report[1].tax_collected, 0.5));
+        assert!(close(report[2].tax_collected, 0.2));
+        // Switched-off rates stay on the report with nothing collected
+        assert_eq!((report[3].gross_sales, report[3].tax_collected), (0.0, 0.0));
+    }
+
+    #[test]
+    fn invoice_payments_post_receipts_outside_revenue() {
+        let conn = seeded_db();
+        let customer = Customer {
+            id: 0,
+            name: "Corner Cafe".to_string(),
+            email: None,
+            phone: None,
+            address: None,
+            customer_type: "wholesale".to_string(),
+            price_tier_id: None,
+            tax_exempt: false,
+            notes: None,
+        };
+        let customer_id = add_customer(&conn, &customer).unwrap();
+        let sale_id = add_itemized_sale(&conn, "2026-03-02", "Wholesale", "Operating", Some("Sales"), Some(customer_id), &mixed_sale(&conn)).unwrap();
+        let invoice = Invoice {
+            id: 0,
+            number: 7,
+            customer_id,
+            order_id: None,
+            sale_transaction_id: Some(sale_id),
+            issue_date: "2026-03-02".to_string(),
+            due_date: "2026-04-01".to_string(),
+            terms: "Net 30".to_string(),
+            tax_rate: 0.0,
+            deposit_applied: 0.0,
+            notes: None,
+            voided: false,
+        };
+        let invoice_id = add_invoice(&conn, &invoice, &[(None, "Wholesale".to_string(), 1, 50.0)]).unwrap();
+        let receipt_id = record_invoice_payment(&conn, invoice_id, "2026-03-20", 50.0, "Operating").unwrap();
+
+        let details = get_invoice_details(&conn, invoice_id).unwrap().unwrap();
+        assert_eq!(details.status("2026-05-01"), "paid");
+
+        // The cheque shows up to be cleared, as money in
+        let reconciliation_id = start_reconciliation(&conn, "Operating", "2026-03-01", "2026-03-31", 0.0, 100.0).unwrap();
+        let reconciliation = get_open_reconciliation(&conn, "Operating").unwrap().unwrap();
+        assert_eq!(reconciliation.id, reconciliation_id);
+        let receipt = get_reconciliation_transactions(&conn, &reconciliation)
+            .unwrap()
+            .into_iter()
+            .find(|tx| tx.id == receipt_id)
+            .unwrap();
+        assert_eq!((receipt.signed_amount(), receipt.invoice_id), (50.0, Some(invoice_id)));
+
+        // Only the sale counts as revenue
+        let budget = get_budget_vs_actual(&conn, "2026-03").unwrap();
+        let revenue: f32 = budget.iter().filter(|line| line.budget_type == "revenue").map(|line| line.actual).sum();
+        assert_eq!(revenue, 50.0);
+    }
+
+    fn post(conn: &Connection, date: &str, transaction_type: &str, amount: f32, account: &str) -> i32 {
+        let transaction = Transaction {
+            id: 0,
+            date: date.to_string(),
+            transaction_type: transaction_type.to_string(),
+            amount,
+            description: format!("{} {}", transaction_type, date),
+            account: account.to_string(),
+            cleared: false,
+            locked: false,
+            customer_id: None,
+            invoice_id: None,
+            category: None,
+            reconciliation_id: None,
+            recurring_id: None,
+            order_id: None,
+        };
+        add_transaction(conn, &transaction).unwrap();
+        conn.last_insert_rowid() as i32
+    }
+
+    #[test]
+    fn reconciliation_difference_counts_only_cleared_rows_in_the_period() {
+        let conn = seeded_db();
+        let sale = post(&conn, "2026-03-05", "sale", 500.0, "Operating");
+        let rent = post(&conn, "2026-03-10", "expense", 120.0, "Operating");
+        let receipt = post(&conn, "2026-03-12", "receipt", 80.0, "Operating");
+        let outstanding = post(&conn, "2026-03-20", "expense", 40.0, "Operating");
+        post(&conn, "2026-04-02", "sale", 999.0, "Operating");
+        post(&conn, "2026-03-15", "sale", 999.0, "Savings");
+
+        let id = start_reconciliation(&conn, "Operating", "2026-03-01", "2026-03-31", 1000.0, 1450.0).unwrap();
+        let reconciliation = get_reconciliation(&conn, id).unwrap().unwrap();
+        let ids: Vec<i32> = get_reconciliation_transactions(&conn, &reconciliation).unwrap().iter().map(|tx| tx.id).collect();
+        assert_eq!(ids, vec![sale, rent, receipt, outstanding]);
+
+        for cleared in [sale, rent, receipt] {
+            assert!(set_transaction_cleared(&conn, cleared, true).unwrap());
+        }
+        let transactions = get_reconciliation_transactions(&conn, &reconciliation).unwrap();
+        assert!(close(reconciliation_cleared_balance(&reconciliation, &transactions), 1460.0));
+
+        let report = complete_reconciliation(&conn, id).unwrap();
+        assert!(report.contains("Cleared balance:     $   1460.00"));
+        assert!(report.contains("Difference:          $    -10.00"));
+        assert!(report.contains("Outstanding items:   1 (net $-40.00, carried forward)"));
+        assert_eq!(last_reconciled_balance(&conn, "Operating").unwrap(), Some(1450.0));
+    }
+
+    #[test]
+    fn reconciled_rows_are_locked_against_edits() {
+        let conn = seeded_db();
+        let sale = post(&conn, "2026-03-05", "sale", 500.0, "Operating");
+        let outstanding = post(&conn, "2026-03-20", "expense", 40.0, "Operating");
+        let id = start_reconciliation(&conn, "Operating", "2026-03-01", "2026-03-31", 0.0, 500.0).unwrap();
+        set_transaction_cleared(&conn, sale, true).unwrap();
+        complete_reconciliation(&conn, id).unwrap();
+
+        assert!(!update_transaction(&conn, sale, "2026-03-06", "expense", 1.0, "Edited", None).unwrap());
+        assert!(!set_transaction_cleared(&conn, sale, false).unwrap());
+        let locked = get_transaction(&conn, sale).unwrap().unwrap();
+        assert!(locked.locked && locked.cleared);
+        assert_eq!((locked.date.as_str(), locked.transaction_type.as_str(), locked.amount), ("2026-03-05", "sale", 500.0));
+
+        assert!(update_transaction(&conn, outstanding, "2026-03-21", "expense", 45.0, "Edited", None).unwrap());
+        assert!(unlock_transaction(&conn, sale).unwrap());
+        assert!(update_transaction(&conn, sale, "2026-03-06", "sale", 510.0, "Edited", None).unwrap());
+    }
+
+    #[test]
+    fn tier_prices_fall_back_to_discount_then_msrp_and_surface_errors() {
+        let conn = seeded_db();
+        let babka = find_recipe_id_by_name(&conn, "Babka").unwrap().unwrap();
+        let croissant = find_recipe_id_by_name(&conn, "Croissant aux Amandes").unwrap().unwrap();
+        conn.execute("UPDATE recipes SET msrp_per_unit = 10.0", []).unwrap();
+        let wholesale = add_price_tier(&conn, "Wholesale", Some(20.0)).unwrap();
+        set_tier_price(&conn, wholesale, babka, 6.5).unwrap();
+
+        assert_eq!(resolve_unit_price(&conn, Some(wholesale), babka).unwrap(), Some(6.5));
+        assert!(close(resolve_unit_price(&conn, Some(wholesale), croissant).unwrap().unwrap(), 8.0));
+        assert_eq!(resolve_unit_price(&conn, None, babka).unwrap(), Some(10.0));
+        assert_eq!(resolve_unit_price(&conn, Some(wholesale + 1), babka).unwrap(), Some(10.0));
+
+        conn.execute_batch("DROP TABLE price_tier_prices;").unwrap();
+        assert!(resolve_unit_price(&conn, Some(wholesale), babka).is_err());
+    }
+
+    #[test]
+    fn cogs_report_compares_costed_runs_with_inventory_movement() {
+        let conn = seeded_db();
+        seed_recipe_ingredients(&conn).unwrap();
+        let babka = find_recipe_id_by_name(&conn, "Babka").unwrap().unwrap();
+        let croissant = find_recipe_id_by_name(&conn, "Croissant aux Amandes").unwrap().unwrap();
+        let babka_cost = calculate_recipe_cost(&conn, babka).unwrap();
+        assert!(babka_cost > 0.0);
+
+        record_production_run(&conn, babka, 2.0, "2026-03-05").unwrap();
+        record_production_run(&conn, babka, 1.0, "2026-04-01").unwrap();
+        // A run logged before batch costs were stored is costed at today's prices
+        conn.execute(
+            "INSERT INTO production_runs (date, recipe_id, batches, units_produced) VALUES ('2026-03-10', ?1, 0.5, 6)",
+            [croissant],
+        )
+        .unwrap();
+        conn.execute("UPDATE inventory SET cost_per_unit = cost_per_unit * 2", []).unwrap();
+        let croissant_cost = calculate_recipe_cost(&conn, croissant).unwrap();
+
+        conn.execute_batch(
+            "INSERT INTO transactions (date, transaction_type, amount, description, account, category) VALUES
+                 ('2026-03-03', 'expense', 300.0, 'Flour order', 'Operating', 'ingredients'),
+                 ('2026-03-04', 'expense', 50.0, 'Rent', 'Operating', 'Rent'),
+                 ('2026-04-02', 'expense', 70.0, 'Butter order', 'Operating', 'Ingredients');",
+        )
+        .unwrap();
+
+        let report = get_cogs_report(&conn, "2026-03-01", "2026-03-31", "Ingredients", 1000.0, 900.0).unwrap();
+        let lines: Vec<(&str, f32, i32)> =
+            report.lines.iter().map(|line| (line.recipe_name.as_str(), line.batches, line.units_produced)).collect();
+        assert_eq!(lines, vec![("Babka", 2.0, 16), ("Croissant aux Amandes", 0.5, 6)]);
+        assert!(close(report.lines[0].theoretical_cost, 2.0 * babka_cost));
+        assert!(close(report.lines[1].theoretical_cost, 0.5 * croissant_cost));
+
+        let theoretical = 2.0 * babka_cost + 0.5 * croissant_cost;
+        assert!(close(report.purchases, 300.0));
+        assert!(close(report.actual(), 400.0));
+        assert!(close(report.gap(), 400.0 - theoretical));
+        assert!(close(report.gap_percent().unwrap(), (400.0 - theoretical) / theoretical * 100.0));
+    }
+
+    #[test]
+    fn recipe_variant_adds_its_new_item_only_when_the_recipe_saves() {
+        let conn = seeded_db();
+        let babka = find_recipe_id_by_name(&conn, "Babka").unwrap().unwrap();
+        let original = get_recipe_collection(&conn).unwrap().into_iter().find(|recipe| recipe.id == babka).unwrap();
+        let butter = find_inventory_id_by_name(&conn, "Butter").unwrap().unwrap();
+        let starter = InventoryItem {
+            id: 0,
+            name: "Rye Levain".to_string(),
+            unit: "grams".to_string(),
+            quantity: 0.0,
+            cost_per_unit: 0.0,
+            category: Some("Leaveners".to_string()),
+            role: Some("preferment".to_string()),
+        };
+        let variant = |name: &str| RecipeCollection { id: 0, name: name.to_string(), ..original.clone() };
+        let inventory_rows = |conn: &Connection| -> i64 {
+            conn.query_row("SELECT COUNT(*) FROM inventory", [], |row| row.get(0)).unwrap()
+        };
+        let before = inventory_rows(&conn);
+
+        conn.execute_batch(
+            "CREATE TRIGGER refuse_variant BEFORE INSERT ON recipes WHEN NEW.name = 'Refused'
+             BEGIN SELECT RAISE(ABORT, 'refused'); END;",
+        )
+        .unwrap();
+        assert!(add_recipe_variant(&conn, &variant("Refused"), &[(butter, 100.0)], babka, Some((&starter, 150.0))).is_err());
+        assert_eq!(inventory_rows(&conn), before);
+
+        let id = add_recipe_variant(&conn, &variant("Babka (starter)"), &[(butter, 100.0)], babka, Some((&starter, 150.0)))
+            .unwrap();
+        assert_eq!(inventory_rows(&conn), before + 1);
+        let starter_id = find_inventory_id_by_name(&conn, "Rye Levain").unwrap().unwrap();
+        let mut stmt = conn
+            .prepare("SELECT ingredient_id, quantity_required FROM recipe_ingredients WHERE recipe_id = ?1 ORDER BY rowid")
+            .unwrap();
+        let lines: Vec<(i32, f32)> =
+            stmt.query_map([id], |row| Ok((row.get(0)?, row.get(1)?))).unwrap().map(|line| line.unwrap()).collect();
+        assert_eq!(lines, vec![(butter, 100.0), (starter_id, 150.0)]);
+        let descriptions = |recipe_id: i32| -> Vec<String> {
+            get_recipe_steps(&conn, recipe_id).unwrap().into_iter().map(|step| step.description).collect()
+        };
+        assert!(!descriptions(babka).is_empty());
+        assert_eq!(descriptions(id), descriptions(babka));
+    }
+
+    fn roles(conn: &Connection) -> Vec<(String, Option<String>)> {
+        let mut stmt = conn.prepare("SELECT name, role FROM inventory ORDER BY name").unwrap();
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap().map(|r| r.unwrap()).collect()
+    }
+
+    #[test]
+    fn role_backfill_matches_the_seeded_roles() {
+        let fresh = Connection::open_in_memory().unwrap();
+        init_db(&fresh).unwrap();
+        seed_inventory(&fresh).unwrap();
+        let seeded = roles(&fresh);
+
+        // An inventory table from before roles existed, holding the same items
+        let upgraded = Connection::open_in_memory().unwrap();
+        upgraded
+            .execute_batch(
+                "CREATE TABLE inventory (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL,
+                    unit TEXT NOT NULL,
+                    quantity REAL NOT NULL,
+                    cost_per_unit REAL NOT NULL
+                );",
+            )
+            .unwrap();
+        for (name, _) in &seeded {
+            upgraded
+                .execute("INSERT INTO inventory (name, unit, quantity, cost_per_unit) VALUES (?1, 'g', 0, 0)", [name])
+                .unwrap();
+        }
+        init_db(&upgraded).unwrap();
+
+        assert_eq!(roles(&upgraded), seeded);
+        let powder = seeded.iter().find(|(name, _)| name == "Milk Powder, Nonfat").unwrap();
+        assert_eq!(powder.1, None);
+    }
+}
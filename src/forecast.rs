@@ -0,0 +1,205 @@
+// src/forecast.rs
+// Demand forecasting from recorded sales: the average daily units sold over the history window,
+// scaled by how the day of the week sells against that average and by any events planned for the day.
+// Pure calculations only; callers load sales history and events and print the result.
+
+use crate::models::ForecastEvent;
+use chrono::{Datelike, NaiveDate};
+
+// Units sold per day for one product over the history window; days with no sales are left out
+#[derive(Debug, Clone)]
+pub struct SalesHistory {
+    pub recipe_id: i32,
+    pub recipe_name: String,
+    pub yield_quantity: i32,
+    pub daily: Vec<(NaiveDate, i32)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ForecastLine {
+    pub recipe_id: i32,
+    pub recipe_name: String,
+    pub date: NaiveDate,
+    // Average units per day, from the product's first sale in the window to its end
+    pub daily_average: f32,
+    // Days of history behind the average; shorter than the window for newer products
+    pub history_days: usize,
+    // How this weekday sells against the average (1.0 = an average day)
+    pub weekday_index: f32,
+    // Combined multiplier from events on the date (1.0 = none)
+    pub event_factor: f32,
+    pub events: Vec<String>,
+    pub units: i32,
+    // Units in batches, rounded up to the nearest half batch
+    pub batches: f32,
+}
+
+// Forecast each product for each date from sales between history_start and history_end
+// (inclusive). Weekday indexes come from the same window, so it should cover a few weeks.
+// A product first sold partway through the window is averaged from that first sale, so the
+// days before it was on sale don't drag its average down.
+pub fn forecast(
+    history: &[SalesHistory],
+    history_start: NaiveDate,
+    history_end: NaiveDate,
+    dates: &[NaiveDate],
+    events: &[ForecastEvent],
+) -> Vec<ForecastLine> {
+    let mut lines = Vec::new();
+
+    for product in history {
+        let in_window = |date: &NaiveDate| *date >= history_start && *date <= history_end;
+        let first_sale = match product.daily.iter().map(|(d, _)| *d).filter(in_window).min() {
+            Some(date) => date,
+            None => continue,
+        };
+        let days: Vec<NaiveDate> = first_sale.iter_days().take_while(|d| *d <= history_end).collect();
+        let total: i32 = product.daily.iter().filter(|(d, _)| in_window(d)).map(|(_, units)| units).sum();
+        let daily_average = total as f32 / days.len() as f32;
+
+        for date in dates {
+            let weekday = date.weekday();
+            let weekday_days = days.iter().filter(|d| d.weekday() == weekday).count();
+            let weekday_units: i32 = product
+                .daily
+                .iter()
+                .filter(|(d, _)| in_window(d) && d.weekday() == weekday)
+                .map(|(_, units)| units)
+                .sum();
+            let weekday_index = if weekday_days == 0 || daily_average <= 0.0 {
+                1.0
+            } else {
+                weekday_units as f32 / weekday_days as f32 / daily_average
+            };
+
+            let date_text = date.format("%Y-%m-%d").to_string();
+            let todays_events: Vec<&ForecastEvent> = events
+                .iter()
+                .filter(|e| e.event_date == date_text && e.recipe_id.is_none_or(|id| id == product.recipe_id))
+                .collect();
+            let event_factor = todays_events
+                .iter()
+                .fold(1.0, |factor, e| factor * (1.0 + e.adjust_percent / 100.0))
+                .max(0.0);
+
+            let units = (daily_average * weekday_index * event_factor).round() as i32;
+            let batches = (units as f32 / product.yield_quantity.max(1) as f32 * 2.0).ceil() / 2.0;
+            lines.push(ForecastLine {
+                recipe_id: product.recipe_id,
+                recipe_name: product.recipe_name.clone(),
+                date: *date,
+                daily_average,
+                history_days: days.len(),
+                weekday_index,
+                event_factor,
+                events: todays_events.iter().map(|e| e.describe()).collect(),
+                units,
+                batches,
+            });
+        }
+    }
+
+    lines.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.recipe_name.cmp(&b.recipe_name)));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    fn product(yield_quantity: i32, daily: &[(&str, i32)]) -> SalesHistory {
+        SalesHistory {
+            recipe_id: 1,
+            recipe_name: "Baguette".to_string(),
+            yield_quantity,
+            daily: daily.iter().map(|(d, units)| (date(d), *units)).collect(),
+        }
+    }
+
+    fn event(event_date: &str, recipe_id: Option<i32>, adjust_percent: f32) -> ForecastEvent {
+        ForecastEvent {
+            id: 0,
+            event_date: event_date.to_string(),
+            name: "Market".to_string(),
+            recipe_id,
+            adjust_percent,
+        }
+    }
+
+    // Two weeks (Mon 2026-03-02 to Sun 2026-03-15) selling 10 a day, 24 on Saturdays
+    fn two_weeks() -> SalesHistory {
+        let mut history = product(12, &[]);
+        history.daily = date("2026-03-02")
+            .iter_days()
+            .take(14)
+            .map(|d| (d, if d.weekday() == chrono::Weekday::Sat { 24 } else { 10 }))
+            .collect();
+        history
+    }
+
+    #[test]
+    fn weekday_index_compares_the_weekday_with_the_average() {
+        let lines = forecast(
+            &[two_weeks()],
+            date("2026-03-02"),
+            date("2026-03-15"),
+            &[date("2026-03-16"), date("2026-03-21")],
+            &[],
+        );
+        // (12 * 10 + 2 * 24) / 14 = 12 a day
+        assert!((lines[0].daily_average - 12.0).abs() < 0.001);
+        assert!((lines[0].weekday_index - 10.0 / 12.0).abs() < 0.001);
+        assert_eq!(lines[0].units, 10);
+        assert!((lines[1].weekday_index - 2.0).abs() < 0.001);
+        assert_eq!(lines[1].units, 24);
+    }
+
+    #[test]
+    fn average_starts_at_the_first_sale_in_the_window() {
+        // A four-week window, but only on sale for the last two weeks
+        let history = two_weeks();
+        let lines = forecast(&[history], date("2026-02-16"), date("2026-03-15"), &[date("2026-03-16")], &[]);
+        assert_eq!(lines[0].history_days, 14);
+        assert!((lines[0].daily_average - 12.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn events_stack_and_never_go_below_zero() {
+        let history = [two_weeks()];
+        let (start, end) = (date("2026-03-02"), date("2026-03-15"));
+        let monday = [date("2026-03-16")];
+
+        let stacked = forecast(&history, start, end, &monday, &[event("2026-03-16", None, 50.0), event("2026-03-16", Some(1), -20.0)]);
+        assert!((stacked[0].event_factor - 1.2).abs() < 0.001);
+        assert_eq!(stacked[0].units, 12);
+        assert_eq!(stacked[0].events.len(), 2);
+
+        let closed = forecast(&history, start, end, &monday, &[event("2026-03-16", None, -100.0), event("2026-03-16", None, 50.0)]);
+        assert_eq!(closed[0].event_factor, 0.0);
+        assert_eq!(closed[0].units, 0);
+        assert_eq!(closed[0].batches, 0.0);
+
+        let beyond = forecast(&history, start, end, &monday, &[event("2026-03-16", None, -150.0)]);
+        assert_eq!(beyond[0].event_factor, 0.0);
+
+        // Events for another recipe or day don't apply
+        let other = forecast(&history, start, end, &monday, &[event("2026-03-16", Some(2), 50.0), event("2026-03-17", None, 50.0)]);
+        assert_eq!(other[0].event_factor, 1.0);
+    }
+
+    #[test]
+    fn batches_round_up_to_the_nearest_half() {
+        let (start, end) = (date("2026-03-02"), date("2026-03-02"));
+        let day = [date("2026-03-09")];
+        let batches = |units: i32| forecast(&[product(12, &[("2026-03-02", units)])], start, end, &day, &[])[0].batches;
+        assert_eq!(batches(1), 0.5);
+        assert_eq!(batches(6), 0.5);
+        assert_eq!(batches(7), 1.0);
+        assert_eq!(batches(12), 1.0);
+        assert_eq!(batches(13), 1.5);
+    }
+}
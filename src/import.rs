@@ -0,0 +1,464 @@
+// src/import.rs
+use crate::db::{find_inventory_id_by_name, find_recipe_id_by_name, upsert_inventory_item, upsert_recipe, upsert_recipe_ingredient};
+use crate::models::{InventoryItem, RecipeCollection};
+use crate::bakers_math::IngredientRole;
+use csv::{ReaderBuilder, StringRecord};
+use rusqlite::Connection;
+use std::fmt;
+use std::path::Path;
+
+// A problem with one line of an import file (line 0 means the file as a whole)
+#[derive(Debug)]
+pub struct ImportError {
+    pub line: u64,
+    pub message: String,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+
+// Rows written by a successful import
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub inserted: usize,
+    pub updated: usize,
+}
+
+type ImportResult = Result<ImportSummary, Vec<ImportError>>;
+
+fn error(line: u64, message: impl Into<String>) -> ImportError {
+    ImportError { line, message: message.into() }
+}
+
+// A CSV file read into memory, with each record's line number
+struct CsvFile {
+    headers: Vec<String>,
+    records: Vec<(u64, StringRecord)>,
+}
+
+impl CsvFile {
+    fn read(path: &Path) -> Result<CsvFile, Vec<ImportError>> {
+        let contents = std::fs::read(path)
+            .map_err(|e| vec![error(0, format!("Could not open {}: {}", path.display(), e))])?;
+        let mut reader = ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .flexible(true)
+            .from_reader(contents.as_slice());
+
+        let headers = reader
+            .headers()
+            .map_err(|e| vec![error(1, format!("Could not read header row: {}", e))])?
+            .iter()
+            .map(|h| h.to_lowercase())
+            .collect();
+
+        let mut records = Vec::new();
+        let mut errors = Vec::new();
+        for record in reader.records() {
+            match record {
+                Ok(record) => {
+                    let line = record.position().map_or(0, |p| record_line(&contents, p));
+                    // Skip blank lines
+                    if record.iter().all(|field| field.is_empty()) {
+                        continue;
+                    }
+                    records.push((line, record));
+                }
+                Err(e) => {
+                    let line = e.position().map_or(0, |p| p.line());
+                    errors.push(error(line, format!("Unreadable row: {}", e)));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(CsvFile { headers, records })
+        } else {
+            Err(errors)
+        }
+    }
+
+    // Index of the first header matching any of `names`
+    fn column(&self, names: &[&str]) -> Option<usize> {
+        self.headers.iter().position(|h| names.contains(&h.as_str()))
+    }
+
+    // Index of a required column, recording an error when it is missing
+    fn required_column(&self, names: &[&str], errors: &mut Vec<ImportError>) -> usize {
+        match self.column(names) {
+            Some(index) => index,
+            None => {
+                errors.push(error(1, format!("Missing required column '{}'", names[0])));
+                usize::MAX
+            }
+        }
+    }
+}
+
+// Line a record starts on. The reader's position points at the line endings it skipped before
+// the record (blank lines, the '\n' of a CRLF) without counting them, so count them here.
+fn record_line(contents: &[u8], position: &csv::Position) -> u64 {
+    let skipped = contents
+        .iter()
+        .skip(position.byte() as usize)
+        .take_while(|b| **b == b'\n' || **b == b'\r')
+        .filter(|b| **b == b'\n')
+        .count();
+    position.line() + skipped as u64
+}
+
+// Text of a field, empty when the column is absent or the row is short
+fn field(record: &StringRecord, index: Option<usize>) -> &str {
+    index.and_then(|i| record.get(i)).unwrap_or("")
+}
+
+fn optional_text(record: &StringRecord, index: Option<usize>) -> Option<String> {
+    let value = field(record, index);
+    if value.is_empty() { None } else { Some(value.to_string()) }
+}
+
+fn required_text(record: &StringRecord, index: usize, name: &str, line: u64, errors: &mut Vec<ImportError>) -> String {
+    let value = field(record, Some(index));
+    if value.is_empty() {
+        errors.push(error(line, format!("{} is required", name)));
+    }
+    value.to_string()
+}
+
+// Parse a non-negative number, accepting a leading '$'
+fn number(record: &StringRecord, index: usize, name: &str, line: u64, errors: &mut Vec<ImportError>) -> f32 {
+    let value = field(record, Some(index));
+    match value.trim_start_matches('$').parse::<f32>() {
+        Ok(number) if number >= 0.0 && number.is_finite() => number,
+        Ok(_) => {
+            errors.push(error(line, format!("{} must not be negative (got '{}')", name, value)));
+            0.0
+        }
+        Err(_) => {
+            errors.push(error(line, format!("{} must be a number (got '{}')", name, value)));
+            0.0
+        }
+    }
+}
+
+// Record an error when a name appears twice in the same file
+fn check_duplicate(seen: &mut Vec<(String, u64)>, name: &str, line: u64, errors: &mut Vec<ImportError>) {
+    let key = name.to_lowercase();
+    if let Some((_, first_line)) = seen.iter().find(|(seen_name, _)| *seen_name == key) {
+        errors.push(error(line, format!("'{}' already appears on line {}", name, first_line)));
+    } else {
+        seen.push((key, line));
+    }
+}
+
+// Apply validated rows in one transaction, rolling everything back on the first failure
+fn apply_all<T>(
+    conn: &Connection,
+    rows: &[(u64, T)],
+    apply: impl Fn(&Connection, &T) -> rusqlite::Result<bool>,
+    ) -> ImportResult {
+    let db_tx = conn.unchecked_transaction().map_err(|e| vec![error(0, e.to_string())])?;
+    let mut summary = ImportSummary::default();
+    for (line, row) in rows {
+        match apply(&db_tx, row) {
+            Ok(true) => summary.inserted += 1,
+            Ok(false) => summary.updated += 1,
+            Err(e) => return Err(vec![error(*line, format!("Database error: {}", e))]),
+        }
+    }
+    db_tx.commit().map_err(|e| vec![error(0, e.to_string())])?;
+
+    Ok(summary)
+}
+
+// Inventory: name, unit, quantity, cost_per_unit (or cost), optional category; upserts by name
+pub fn import_inventory_csv(conn: &Connection, path: &Path) -> ImportResult {
+    let file = CsvFile::read(path)?;
+    let mut errors = Vec::new();
+
+    let name_col = file.required_column(&["name"], &mut errors);
+    let unit_col = file.required_column(&["unit"], &mut errors);
+    let quantity_col = file.required_column(&["quantity"], &mut errors);
+    let cost_col = file.required_column(&["cost_per_unit", "cost"], &mut errors);
+    let category_col = file.column(&["category"]);
+    let role_col = file.column(&["role"]);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut items = Vec::new();
+    let mut seen = Vec::new();
+    for (line, record) in &file.records {
+        let line = *line;
+        let name = required_text(record, name_col, "name", line, &mut errors);
+        let unit = required_text(record, unit_col, "unit", line, &mut errors);
+        let quantity = number(record, quantity_col, "quantity", line, &mut errors);
+        let cost_per_unit = number(record, cost_col, "cost_per_unit", line, &mut errors);
+        if !name.is_empty() {
+            check_duplicate(&mut seen, &name, line, &mut errors);
+        }
+
+        let role = optional_text(record, role_col).map(|r| r.to_lowercase());
+        if let Some(role) = &role {
+            if IngredientRole::parse(role).is_none() {
+                errors.push(error(line, format!("unknown role '{}'", role)));
+            }
+        }
+
+        items.push((line, InventoryItem {
+            id: 0, name, unit, quantity, cost_per_unit, category: optional_text(record, category_col), role,
+        }));
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    apply_all(conn, &items, upsert_inventory_item)
+}
+
+// Recipes: name, instructions, yield_quantity, category, optional prep/bake/total time, msrp_per_unit
+// and dough temperature settings; upserts by name
+pub fn import_recipes_csv(conn: &Connection, path: &Path) -> ImportResult {
+    let file = CsvFile::read(path)?;
+    let mut errors = Vec::new();
+
+    let name_col = file.required_column(&["name"], &mut errors);
+    let instructions_col = file.required_column(&["instructions"], &mut errors);
+    let yield_col = file.required_column(&["yield_quantity", "yield"], &mut errors);
+    let category_col = file.required_column(&["category"], &mut errors);
+    let prep_col = file.column(&["prep_time"]);
+    let bake_col = file.column(&["bake_time"]);
+    let total_col = file.column(&["total_time"]);
+    let msrp_col = file.column(&["msrp_per_unit", "msrp"]);
+    let ddt_col = file.column(&["target_ddt_c"]);
+    let friction_col = file.column(&["friction_factor_c"]);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut recipes = Vec::new();
+    let mut seen = Vec::new();
+    for (line, record) in &file.records {
+        let line = *line;
+        let name = required_text(record, name_col, "name", line, &mut errors);
+        let instructions = required_text(record, instructions_col, "instructions", line, &mut errors);
+        let category = required_text(record, category_col, "category", line, &mut errors);
+        let yield_text = field(record, Some(yield_col));
+        let yield_quantity = match yield_text.parse::<i32>() {
+            Ok(quantity) if quantity > 0 => quantity,
+            _ => {
+                errors.push(error(line, format!("yield_quantity must be a whole number above zero (got '{}')", yield_text)));
+                0
+            }
+        };
+        let mut optional_number = |col: Option<usize>, name: &str| match col {
+            Some(col) if !field(record, Some(col)).is_empty() => Some(number(record, col, name, line, &mut errors)),
+            _ => None,
+        };
+        let msrp_per_unit = optional_number(msrp_col, "msrp_per_unit");
+        let target_ddt_c = optional_number(ddt_col, "target_ddt_c");
+        let friction_factor_c = optional_number(friction_col, "friction_factor_c");
+        if !name.is_empty() {
+            check_duplicate(&mut seen, &name, line, &mut errors);
+        }
+
+        recipes.push((line, RecipeCollection {
+            id: 0,
+            name,
+            // Allow "\n" in a single-line CSV cell to stand for a line break
+            instructions: instructions.replace("\\n", "\n"),
+            yield_quantity,
+            category,
+            prep_time: optional_text(record, prep_col),
+            bake_time: optional_text(record, bake_col),
+            total_time: optional_text(record, total_col),
+            msrp_per_unit,
+            target_ddt_c,
+            friction_factor_c,
+            pan_id: None,
+        }));
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    apply_all(conn, &recipes, upsert_recipe)
+}
+
+// Recipe ingredient lines: recipe_name, ingredient_name, quantity_required; both names must already exist
+pub fn import_recipe_ingredients_csv(conn: &Connection, path: &Path) -> ImportResult {
+    let file = CsvFile::read(path)?;
+    let mut errors = Vec::new();
+
+    let recipe_col = file.required_column(&["recipe_name", "recipe"], &mut errors);
+    let ingredient_col = file.required_column(&["ingredient_name", "ingredient"], &mut errors);
+    let quantity_col = file.required_column(&["quantity_required", "quantity"], &mut errors);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut lines = Vec::new();
+    let mut seen = Vec::new();
+    for (line, record) in &file.records {
+        let line = *line;
+        let recipe_name = required_text(record, recipe_col, "recipe_name", line, &mut errors);
+        let ingredient_name = required_text(record, ingredient_col, "ingredient_name", line, &mut errors);
+        let quantity = number(record, quantity_col, "quantity_required", line, &mut errors);
+
+        let recipe_id = match find_recipe_id_by_name(conn, &recipe_name) {
+            Ok(Some(id)) => id,
+            Ok(None) => {
+                if !recipe_name.is_empty() {
+                    errors.push(error(line, format!("Unknown recipe '{}'", recipe_name)));
+                }
+                0
+            }
+            Err(e) => return Err(vec![error(line, format!("Database error: {}", e))]),
+        };
+        let ingredient_id = match find_inventory_id_by_name(conn, &ingredient_name) {
+            Ok(Some(id)) => id,
+            Ok(None) => {
+                if !ingredient_name.is_empty() {
+                    errors.push(error(line, format!("Unknown ingredient '{}'", ingredient_name)));
+                }
+                0
+            }
+            Err(e) => return Err(vec![error(line, format!("Database error: {}", e))]),
+        };
+        if recipe_id > 0 && ingredient_id > 0 {
+            check_duplicate(&mut seen, &format!("{} / {}", recipe_name, ingredient_name), line, &mut errors);
+        }
+
+        lines.push((line, (recipe_id, ingredient_id, quantity)));
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    apply_all(conn, &lines, |conn, (recipe_id, ingredient_id, quantity)| {
+        upsert_recipe_ingredient(conn, *recipe_id, *ingredient_id, *quantity)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{init_db, seed_inventory, seed_recipes};
+    use std::path::PathBuf;
+
+    fn seeded_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        init_db(&conn).unwrap();
+        seed_inventory(&conn).unwrap();
+        seed_recipes(&conn).unwrap();
+        conn
+    }
+
+    fn csv_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("bakery_import_test_{}_{}.csv", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    // Every inventory row, to check an import left the table untouched
+    fn inventory(conn: &Connection) -> Vec<(String, f64, f64)> {
+        let mut stmt = conn.prepare("SELECT name, quantity, cost_per_unit FROM inventory ORDER BY id").unwrap();
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap()
+    }
+
+    fn error_lines(errors: &[ImportError]) -> Vec<(u64, String)> {
+        errors.iter().map(|e| (e.line, e.message.clone())).collect()
+    }
+
+    #[test]
+    fn bad_rows_are_reported_by_line_and_nothing_is_written() {
+        let conn = seeded_db();
+        let before = inventory(&conn);
+        let path = csv_file(
+            "bad_inventory",
+            "name,unit,quantity,cost,role\n\
+             Rye Berries,kg,2,$3.10,flour\n\
+             Butter,grams,-5,3.25,\n\
+             ,kg,1,1,\n\
+             \n\
+             rye berries,kg,1,abc,\n\
+             Cocoa,g,1,1,glaze\n",
+        );
+
+        let errors = import_inventory_csv(&conn, &path).unwrap_err();
+        assert_eq!(
+            error_lines(&errors),
+            vec![
+                (3, "quantity must not be negative (got '-5')".to_string()),
+                (4, "name is required".to_string()),
+                (6, "cost_per_unit must be a number (got 'abc')".to_string()),
+                (6, "'rye berries' already appears on line 2".to_string()),
+                (7, "unknown role 'glaze'".to_string()),
+            ]
+        );
+        assert_eq!(inventory(&conn), before);
+    }
+
+    #[test]
+    fn recipe_lines_must_name_existing_recipes_and_ingredients_once() {
+        let conn = seeded_db();
+        let path = csv_file(
+            "bad_recipe_lines",
+            "recipe,ingredient,quantity\n\
+             Babka,Butter,0.2\n\
+             Babka,Saffron,1\n\
+             Focaccia Royale,Butter,1\n\
+             BABKA,butter,0.3\n",
+        );
+
+        let errors = import_recipe_ingredients_csv(&conn, &path).unwrap_err();
+        assert_eq!(
+            error_lines(&errors),
+            vec![
+                (3, "Unknown ingredient 'Saffron'".to_string()),
+                (4, "Unknown recipe 'Focaccia Royale'".to_string()),
+                (5, "'BABKA / butter' already appears on line 2".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_failing_row_rolls_back_the_whole_import() {
+        let conn = seeded_db();
+        let before = inventory(&conn);
+        conn.execute_batch(
+            "CREATE TRIGGER refuse_poison BEFORE INSERT ON inventory WHEN NEW.name = 'Poison'
+             BEGIN SELECT RAISE(ABORT, 'refused'); END;",
+        )
+        .unwrap();
+        // Saved with Windows line endings, as spreadsheets often do
+        let path = csv_file(
+            "failing_inventory",
+            "name,unit,quantity,cost_per_unit\r\n\
+             Rye Berries,kg,2,3.10\r\n\
+             Butter,grams,99,3.25\r\n\
+             Poison,g,1,1\r\n",
+        );
+
+        let errors = import_inventory_csv(&conn, &path).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 4);
+        assert!(errors[0].message.starts_with("Database error"));
+        assert_eq!(inventory(&conn), before);
+
+        conn.execute_batch("DROP TRIGGER refuse_poison;").unwrap();
+        let summary = import_inventory_csv(&conn, &path).unwrap();
+        assert_eq!((summary.inserted, summary.updated), (2, 1));
+        assert_eq!(inventory(&conn).len(), before.len() + 2);
+    }
+}
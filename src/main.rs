@@ -1,10 +1,19 @@
 mod db;
 mod models;
 mod cli;
+mod reports;
+mod import;
+mod archive;
+mod bakers_math;
+mod schedule;
+mod steps;
+mod plan;
+mod forecast;
 
 use db::{connect, init_db, seed_inventory,
-    seed_recipe_ingredients, seed_recipes, seed_transactions, reset_database};
-use cli::show_main_menu;
+    seed_recipe_ingredients, seed_recipes, seed_transactions, seed_pans, seed_resources, reset_database};
+use cli::{show_main_menu, handle_post_due_recurring};
+use std::env;
 
 
 fn main() {
@@ -20,6 +29,21 @@ fn main() {
         seed_recipe_ingredients(&conn).expect("Failed to seed recipe ingredients");
         seed_transactions(&conn).expect("Faild to seed transactions");
     }
+    // Standard pans are reference data, so make sure the library exists on every launch
+    seed_pans(&conn).expect("❌ Failed to seed pans");
+    seed_resources(&conn).expect("❌ Failed to seed equipment");
+
+    // `bakery_manager tx post-due` posts due recurring transactions and exits
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() >= 2 && args[0] == "tx" && args[1] == "post-due" {
+        if !handle_post_due_recurring(&conn) {
+            println!("✅ No recurring transactions are due.");
+        }
+        return;
+    }
+
+    // Offer to post any recurring transactions that came due since last launch
+    handle_post_due_recurring(&conn);
 
     loop {
         show_main_menu(&conn);
@@ -1,17 +1,33 @@
 // src/models.rs
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use chrono::{Datelike, Duration, NaiveDate};
+use crate::steps::format_minutes;
 
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct InventoryItem {
     pub id: i32,
     pub name: String,
     pub unit: String,
     pub quantity: f32,
     pub cost_per_unit: f32,
+    pub category: Option<String>,
+    // Baker's math role tag (flour, liquid, salt, ...)
+    pub role: Option<String>,
 }
 
-#[derive(Debug)]
+impl InventoryItem {
+    // Stock value at cost
+    pub fn value(&self) -> f32 {
+        self.quantity * self.cost_per_unit
+    }
+
+    pub fn category_label(&self) -> &str {
+        self.category.as_deref().unwrap_or("Uncategorized")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecipeCollection {
     pub id: i32,
     pub name: String,
@@ -22,19 +38,50 @@ pub struct RecipeCollection {
     pub bake_time: Option<String>,
     pub total_time: Option<String>,
     pub msrp_per_unit: Option<f32>,
+    // Desired dough temperature and mixer friction factor, stored in °C
+    pub target_ddt_c: Option<f32>,
+    pub friction_factor_c: Option<f32>,
+    // Pan the recipe was written for
+    pub pan_id: Option<i32>,
 }
 
-#[derive(Debug)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecipeIngredient {
     pub recipe_id: i32,
     pub ingredient_id: i32,
     pub quantity_required: f32,
 }
 
+// A recipe ingredient line joined to its inventory item, for export
+#[derive(Debug, Serialize)]
+pub struct RecipeIngredientLine {
+    pub recipe_id: i32,
+    pub recipe_name: String,
+    pub ingredient_id: i32,
+    pub ingredient_name: String,
+    pub quantity_required: f32,
+    pub unit: String,
+    pub cost_per_unit: f32,
+    pub line_cost: f32,
+}
 
-
+// Batch and unit cost of a recipe against its MSRP
 #[derive(Debug, Serialize)]
+pub struct RecipeCosting {
+    pub recipe_id: i32,
+    pub recipe_name: String,
+    pub category: String,
+    pub yield_quantity: i32,
+    pub batch_cost: f32,
+    pub unit_cost: f32,
+    pub msrp_per_unit: Option<f32>,
+    pub unit_margin: Option<f32>,
+    pub margin_percent: Option<f32>,
+}
+
+
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Transaction {
     //"Add additional fields to transaction table-- payee, payment type, second field for category"
     pub id: i32,
@@ -42,4 +89,944 @@ pub struct Transaction {
     pub transaction_type: String,
     pub amount: f32,
     pub description: String,
+    pub account: String,
+    pub cleared: bool,
+    pub locked: bool,
+    pub customer_id: Option<i32>,
+    pub invoice_id: Option<i32>,
+    pub category: Option<String>,
+    pub reconciliation_id: Option<i32>,
+    pub recurring_id: Option<i32>,
+    // Set on order deposits and the sale that fulfilled the order
+    pub order_id: Option<i32>,
+}
+
+impl Transaction {
+    // Amount as it affects the account balance: sales and invoice receipts add, everything else subtracts
+    pub fn signed_amount(&self) -> f32 {
+        if self.transaction_type == "sale" || self.transaction_type == "receipt" {
+            self.amount
+        } else {
+            -self.amount
+        }
+    }
+}
+
+// Ledger transaction types entered by hand; 'receipt' rows are only posted by invoice payments
+pub const TRANSACTION_TYPES: [&str; 2] = ["sale", "expense"];
+
+#[derive(Debug)]
+pub struct Reconciliation {
+    pub id: i32,
+    pub account: String,
+    pub period_start: String,
+    pub period_end: String,
+    pub opening_balance: f32,
+    pub statement_balance: f32,
+    pub status: String,
+    pub completed_at: Option<String>,
+    pub report: Option<String>,
+}
+
+#[derive(Debug)]
+#[allow(dead_code)]
+pub struct SaleItem {
+    pub id: i32,
+    pub transaction_id: i32,
+    pub recipe_id: i32,
+    pub quantity: i32,
+    pub unit_price: f32,
+}
+
+#[derive(Debug)]
+pub struct ProductSales {
+    pub period: String,
+    pub recipe_id: i32,
+    pub recipe_name: String,
+    pub units_sold: i32,
+    pub revenue: f32,
+    pub cost: f32,
+    pub gross_margin: f32,
+}
+
+impl ProductSales {
+    // Gross margin as a percentage of revenue
+    pub fn margin_percent(&self) -> f32 {
+        if self.revenue == 0.0 {
+            0.0
+        } else {
+            self.gross_margin / self.revenue * 100.0
+        }
+    }
+}
+
+// Statuses an order moves through, in order
+pub const ORDER_STATUSES: [&str; 6] = ["quoted", "confirmed", "in_production", "ready", "fulfilled", "cancelled"];
+
+#[derive(Debug)]
+pub struct Order {
+    pub id: i32,
+    pub customer_name: String,
+    pub customer_contact: Option<String>,
+    pub due_date: String,
+    pub due_time: Option<String>,
+    pub fulfilment: String,
+    pub deposit_paid: f32,
+    pub status: String,
+    pub notes: Option<String>,
+    pub created_date: String,
+    pub sale_transaction_id: Option<i32>,
+    pub customer_id: Option<i32>,
+}
+
+#[derive(Debug)]
+#[allow(dead_code)]
+pub struct OrderItem {
+    pub id: i32,
+    pub order_id: i32,
+    pub recipe_id: i32,
+    pub recipe_name: String,
+    pub quantity: i32,
+    pub unit_price: f32,
+}
+
+// Kinds of customer we sell to
+pub const CUSTOMER_TYPES: [&str; 3] = ["retail", "wholesale", "market"];
+
+#[derive(Debug)]
+pub struct Customer {
+    pub id: i32,
+    pub name: String,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+    pub customer_type: String,
+    pub price_tier_id: Option<i32>,
+    pub tax_exempt: bool,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct PriceTier {
+    pub id: i32,
+    pub name: String,
+    pub discount_percent: Option<f32>,
+}
+
+#[derive(Debug)]
+pub struct Invoice {
+    pub id: i32,
+    pub number: i32,
+    pub customer_id: i32,
+    pub order_id: Option<i32>,
+    pub sale_transaction_id: Option<i32>,
+    pub issue_date: String,
+    pub due_date: String,
+    pub terms: String,
+    pub tax_rate: f32,
+    pub deposit_applied: f32,
+    pub notes: Option<String>,
+    pub voided: bool,
+}
+
+#[derive(Debug)]
+#[allow(dead_code)]
+pub struct InvoiceLine {
+    pub id: i32,
+    pub invoice_id: i32,
+    pub recipe_id: Option<i32>,
+    pub description: String,
+    pub quantity: i32,
+    pub unit_price: f32,
+}
+
+// Invoice numbers print as INV-00001
+pub fn format_invoice_number(number: i32) -> String {
+    format!("INV-{:05}", number)
+}
+
+// Money received against an invoice, read from its 'receipt' transactions
+#[derive(Debug)]
+pub struct InvoicePayment {
+    pub date: String,
+    pub amount: f32,
+}
+
+// An invoice together with everything needed to print it or age it
+#[derive(Debug)]
+pub struct InvoiceDetails {
+    pub invoice: Invoice,
+    pub customer: Customer,
+    pub lines: Vec<InvoiceLine>,
+    pub payments: Vec<InvoicePayment>,
+    pub subtotal: f32,
+    pub tax: f32,
+    pub total: f32,
+    pub paid: f32,
+    pub balance: f32,
+}
+
+impl InvoiceDetails {
+    pub fn new(invoice: Invoice, customer: Customer, lines: Vec<InvoiceLine>, payments: Vec<InvoicePayment>) -> Self {
+        let subtotal: f32 = lines.iter().map(|line| line.quantity as f32 * line.unit_price).sum();
+        let tax = subtotal * invoice.tax_rate / 100.0;
+        let total = subtotal + tax;
+        let paid = invoice.deposit_applied + payments.iter().map(|p| p.amount).sum::<f32>();
+        let balance = total - paid;
+
+        InvoiceDetails { invoice, customer, lines, payments, subtotal, tax, total, paid, balance }
+    }
+
+    // paid, partial, overdue or open as of the given YYYY-MM-DD date (void if voided)
+    pub fn status(&self, today: &str) -> &'static str {
+        if self.invoice.voided {
+            "void"
+        } else if self.balance <= 0.005 {
+            "paid"
+        } else if self.invoice.due_date.as_str() < today {
+            "overdue"
+        } else if self.paid > 0.0 {
+            "partial"
+        } else {
+            "open"
+        }
+    }
+}
+
+// Levels a tax jurisdiction can sit at
+pub const TAX_LEVELS: [&str; 3] = ["state", "county", "city"];
+
+#[derive(Debug)]
+pub struct TaxJurisdiction {
+    pub id: i32,
+    pub name: String,
+    pub level: String,
+    pub rate: f32,
+    pub active: bool,
+}
+
+// Tax worked out for one sale: gross and taxable line totals plus (jurisdiction_id, tax) pairs
+#[derive(Debug)]
+pub struct SaleTax {
+    pub gross: f32,
+    pub taxable: f32,
+    pub taxes: Vec<(i32, f32)>,
+}
+
+impl SaleTax {
+    pub fn total_tax(&self) -> f32 {
+        self.taxes.iter().fold(0.0, |total, (_, tax)| total + tax)
+    }
+
+    // What the customer is charged: line items plus tax
+    pub fn total(&self) -> f32 {
+        self.gross + self.total_tax()
+    }
+}
+
+#[derive(Debug)]
+pub struct TaxLiability {
+    pub jurisdiction: String,
+    pub level: String,
+    pub rate: f32,
+    pub gross_sales: f32,
+    pub exempt_sales: f32,
+    pub taxable_sales: f32,
+    pub tax_collected: f32,
+}
+
+// Ledger transaction types a recurring template can post
+pub const RECURRING_TRANSACTION_TYPES: [&str; 2] = ["sale", "expense"];
+
+// How often a recurring transaction repeats
+pub const RECURRING_FREQUENCIES: [&str; 2] = ["weekly", "monthly"];
+
+#[derive(Debug)]
+pub struct RecurringTransaction {
+    pub id: i32,
+    pub description: String,
+    pub transaction_type: String,
+    pub amount: f32,
+    pub is_estimate: bool,
+    pub account: String,
+    pub frequency: String,
+    pub interval: i32,
+    pub day_of_month: Option<i32>,
+    pub start_date: String,
+    pub end_date: Option<String>,
+    pub last_posted_date: Option<String>,
+    pub category: Option<String>,
+}
+
+// Last day of the given month
+fn last_day_of_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(28)
+}
+
+// (template, due dates with the actual amount to post, None for the template amount)
+pub type RecurringOccurrences<'a> = (&'a RecurringTransaction, Vec<(NaiveDate, Option<f32>)>);
+
+impl RecurringTransaction {
+    // Occurrences that have come due but not been posted, up to and including `through`
+    pub fn due_dates(&self, through: NaiveDate) -> Vec<NaiveDate> {
+        let start = match NaiveDate::parse_from_str(&self.start_date, "%Y-%m-%d") {
+            Ok(date) => date,
+            Err(_) => return Vec::new(),
+        };
+        let end = self
+            .end_date
+            .as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+            .map_or(through, |d| d.min(through));
+        let last_posted = self
+            .last_posted_date
+            .as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok());
+        let interval = self.interval.max(1);
+
+        let mut dates = Vec::new();
+        let mut step = 0;
+        loop {
+            let date = if self.frequency == "weekly" {
+                start + Duration::weeks((step * interval) as i64)
+            } else {
+                // Monthly on a fixed day, clamped to short months
+                let months = start.month0() as i32 + step * interval;
+                let year = start.year() + months / 12;
+                let month = (months % 12) as u32 + 1;
+                let day = self.day_of_month.unwrap_or(start.day() as i32).clamp(1, 31) as u32;
+                match NaiveDate::from_ymd_opt(year, month, day.min(last_day_of_month(year, month))) {
+                    Some(date) => date,
+                    None => break,
+                }
+            };
+
+            if date > end {
+                break;
+            }
+            if date >= start && last_posted.is_none_or(|posted| date > posted) {
+                dates.push(date);
+            }
+            step += 1;
+        }
+
+        dates
+    }
+
+    // Human-readable schedule, e.g. "every 2 weeks" or "monthly on day 1"
+    pub fn schedule_label(&self) -> String {
+        let every = if self.interval > 1 { format!("every {} ", self.interval) } else { String::new() };
+        match self.frequency.as_str() {
+            "weekly" if self.interval > 1 => format!("{}weeks", every),
+            "weekly" => "weekly".to_string(),
+            _ => {
+                let day = self
+                    .day_of_month
+                    .map(|d| d.to_string())
+                    .unwrap_or_else(|| self.start_date.chars().skip(8).collect());
+                if self.interval > 1 {
+                    format!("{}months on day {}", every, day)
+                } else {
+                    format!("monthly on day {}", day)
+                }
+            }
+        }
+    }
+}
+
+// Kinds of budget line
+pub const BUDGET_TYPES: [&str; 2] = ["revenue", "expense"];
+
+#[derive(Debug)]
+pub struct BudgetLine {
+    pub category: String,
+    pub budget_type: String,
+    pub budgeted: f32,
+    pub actual: f32,
+}
+
+impl BudgetLine {
+    // Favourable variance is positive: revenue above budget, or expense below it
+    pub fn variance(&self) -> f32 {
+        if self.budget_type == "revenue" {
+            self.actual - self.budgeted
+        } else {
+            self.budgeted - self.actual
+        }
+    }
+
+    // Variance as a percentage of budget (None when nothing was budgeted)
+    pub fn variance_percent(&self) -> Option<f32> {
+        if self.budgeted == 0.0 {
+            None
+        } else {
+            Some(self.variance() / self.budgeted * 100.0)
+        }
+    }
+}
+
+// Theoretical cost of one recipe's production over a period
+#[derive(Debug)]
+pub struct CogsLine {
+    pub recipe_id: i32,
+    pub recipe_name: String,
+    pub batches: f32,
+    pub units_produced: i32,
+    pub theoretical_cost: f32,
+}
+
+// Cost of goods sold for a period, computed two ways
+#[derive(Debug)]
+pub struct CogsReport {
+    pub start_date: String,
+    pub end_date: String,
+    pub lines: Vec<CogsLine>,
+    pub opening_value: f32,
+    pub purchases: f32,
+    pub closing_value: f32,
+}
+
+impl CogsReport {
+    // Production runs times recipe cost
+    pub fn theoretical(&self) -> f32 {
+        self.lines.iter().fold(0.0, |sum, line| sum + line.theoretical_cost)
+    }
+
+    // Opening inventory + purchases - closing inventory
+    pub fn actual(&self) -> f32 {
+        self.opening_value + self.purchases - self.closing_value
+    }
+
+    // Unexplained usage: waste, theft, over-portioning (positive means actual exceeded theoretical)
+    pub fn gap(&self) -> f32 {
+        self.actual() - self.theoretical()
+    }
+
+    pub fn gap_percent(&self) -> Option<f32> {
+        let theoretical = self.theoretical();
+        if theoretical == 0.0 {
+            None
+        } else {
+            Some(self.gap() / theoretical * 100.0)
+        }
+    }
+}
+
+// A dated copy of the inventory table
+#[derive(Debug)]
+pub struct InventorySnapshot {
+    pub id: i32,
+    pub snapshot_date: String,
+    pub label: Option<String>,
+    pub total_value: f32,
+    pub item_count: i32,
+}
+
+impl InventorySnapshot {
+    pub fn title(&self) -> String {
+        match &self.label {
+            Some(label) => format!("#{} {} ({})", self.id, self.snapshot_date, label),
+            None => format!("#{} {}", self.id, self.snapshot_date),
+        }
+    }
+}
+
+// One item's change between two inventory snapshots
+#[derive(Debug)]
+pub struct SnapshotChange {
+    pub name: String,
+    pub category: String,
+    pub unit: String,
+    pub before_quantity: f32,
+    pub after_quantity: f32,
+    pub before_value: f32,
+    pub after_value: f32,
+}
+
+impl SnapshotChange {
+    pub fn quantity_change(&self) -> f32 {
+        self.after_quantity - self.before_quantity
+    }
+
+    pub fn value_change(&self) -> f32 {
+        self.after_value - self.before_value
+    }
+}
+
+// Line up two snapshots' items by inventory id; items missing from one side count as zero
+pub fn compare_snapshots(before: &[InventoryItem], after: &[InventoryItem]) -> Vec<SnapshotChange> {
+    let mut changes: Vec<SnapshotChange> = before
+        .iter()
+        .map(|item| {
+            let later = after.iter().find(|a| a.id == item.id);
+            SnapshotChange {
+                name: item.name.clone(),
+                category: item.category_label().to_string(),
+                unit: item.unit.clone(),
+                before_quantity: item.quantity,
+                after_quantity: later.map_or(0.0, |a| a.quantity),
+                before_value: item.value(),
+                after_value: later.map_or(0.0, |a| a.value()),
+            }
+        })
+        .collect();
+
+    for item in after.iter().filter(|a| !before.iter().any(|b| b.id == a.id)) {
+        changes.push(SnapshotChange {
+            name: item.name.clone(),
+            category: item.category_label().to_string(),
+            unit: item.unit.clone(),
+            before_quantity: 0.0,
+            after_quantity: item.quantity,
+            before_value: 0.0,
+            after_value: item.value(),
+        });
+    }
+
+    changes.sort_by(|a, b| a.category.cmp(&b.category).then_with(|| a.name.cmp(&b.name)));
+    changes
+}
+
+pub const PAN_SHAPES: [&str; 6] = ["round", "square", "rectangular", "loaf", "tube", "bundt"];
+
+// Cubic inches in a US cup
+const CUBIC_INCHES_PER_CUP: f32 = 14.4375;
+
+// A baking pan or mold; dimensions in inches, volume in US cups
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pan {
+    pub id: i32,
+    pub name: String,
+    pub shape: String,
+    pub length_in: Option<f32>,
+    pub width_in: Option<f32>,
+    pub diameter_in: Option<f32>,
+    pub tube_diameter_in: Option<f32>,
+    pub depth_in: Option<f32>,
+    pub volume_cups: Option<f32>,
+}
+
+impl Pan {
+    // Base area in square inches from the pan's dimensions
+    fn dimension_area(&self) -> Option<f32> {
+        let circle = |d: f32| std::f32::consts::PI * (d / 2.0).powi(2);
+        match self.shape.as_str() {
+            "round" => self.diameter_in.map(circle),
+            "square" => self.length_in.map(|side| side * self.width_in.unwrap_or(side)),
+            "rectangular" | "loaf" => Some(self.length_in? * self.width_in?),
+            "tube" | "bundt" => Some(circle(self.diameter_in?) - self.tube_diameter_in.map_or(0.0, circle)),
+            _ => None,
+        }
+    }
+
+    // Base area in square inches, falling back to volume over depth
+    // (e.g. a bundt known only by its capacity); None unless it comes out above zero
+    pub fn area(&self) -> Option<f32> {
+        self.dimension_area()
+            .or_else(|| Some(self.volume_cups? * CUBIC_INCHES_PER_CUP / self.depth_in?))
+            .filter(|area| area.is_finite() && *area > 0.0)
+    }
+
+    // Capacity in cups: the stated volume, or area times depth; None unless above zero
+    pub fn volume_cups(&self) -> Option<f32> {
+        self.volume_cups
+            .or_else(|| self.area().zip(self.depth_in).map(|(area, depth)| area * depth / CUBIC_INCHES_PER_CUP))
+            .filter(|cups| cups.is_finite() && *cups > 0.0)
+    }
+
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        match (self.length_in, self.width_in, self.diameter_in) {
+            (Some(l), Some(w), _) => parts.push(format!("{}x{} in", l, w)),
+            (Some(l), None, _) => parts.push(format!("{} in", l)),
+            (_, _, Some(d)) => parts.push(format!("{} in diameter", d)),
+            _ => {}
+        }
+        if let Some(depth) = self.depth_in {
+            parts.push(format!("{} in deep", depth));
+        }
+        if let Some(cups) = self.volume_cups() {
+            parts.push(format!("{:.1} cups", cups));
+        }
+        format!("{} ({}: {})", self.name, self.shape, parts.join(", "))
+    }
+}
+
+// Result of scaling a recipe from one pan to another
+#[derive(Debug)]
+pub struct PanScaling {
+    pub by_volume: bool,
+    pub factor: f32,
+    // Batter depth in the new pan relative to the old, when both areas are known
+    pub depth_ratio: Option<f32>,
+    pub notes: Vec<String>,
+}
+
+// Scale factor between pans by base area (same batter depth) or by capacity;
+// None when either pan lacks the measurements for the chosen method or they aren't above zero
+pub fn pan_scaling(from: &Pan, to: &Pan, by_volume: bool) -> Option<PanScaling> {
+    let factor = if by_volume {
+        to.volume_cups()? / from.volume_cups()?
+    } else {
+        to.area()? / from.area()?
+    };
+    let depth_ratio = match (from.area(), to.area()) {
+        (Some(from_area), Some(to_area)) => Some(factor * from_area / to_area),
+        _ => None,
+    };
+
+    let mut notes = Vec::new();
+    match depth_ratio {
+        Some(ratio) if ratio > 1.1 => notes.push(format!(
+            "Batter will be about {:.0}% deeper: lower the oven 25°F, expect a longer bake and start checking at the original time",
+            (ratio - 1.0) * 100.0
+        )),
+        Some(ratio) if ratio < 0.9 => notes.push(format!(
+            "Batter will be about {:.0}% shallower: start checking for doneness about {:.0}% sooner",
+            (1.0 - ratio) * 100.0,
+            (1.0 - ratio) * 50.0
+        )),
+        Some(_) => notes.push("Batter depth is about the same, so the bake time should be similar".to_string()),
+        None => notes.push("Batter depth can't be compared; check for doneness early and often".to_string()),
+    }
+    let tube = |pan: &Pan| pan.shape == "tube" || pan.shape == "bundt";
+    if tube(to) && !tube(from) {
+        notes.push("The center tube bakes the middle faster; grease thoroughly and test with a skewer near the tube".to_string());
+    } else if tube(from) && !tube(to) {
+        notes.push("Without a center tube the middle bakes last; test the center and tent with foil if the top browns early".to_string());
+    }
+    if to.shape == "loaf" && from.shape != "loaf" {
+        notes.push("Loaf pans bake slowest in the center; tent with foil if the top browns before the center sets".to_string());
+    }
+    if !(0.5..=2.0).contains(&factor) {
+        notes.push("Large change in batch size: mix in batches if your mixer can't hold it".to_string());
+    }
+
+    Some(PanScaling { by_volume, factor, depth_ratio, notes })
+}
+
+// A day's production: recipes and batch multipliers, posted as production runs once baked.
+// Status is 'draft' until posted.
+#[derive(Debug, Clone)]
+pub struct ProductionPlan {
+    pub id: i32,
+    pub plan_date: String,
+    pub status: String,
+    pub notes: Option<String>,
+    pub posted_date: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProductionPlanItem {
+    pub recipe_id: i32,
+    pub recipe_name: String,
+    pub batches: f32,
+}
+
+// One inventory item to pull for a plan, totalled across recipes in the item's unit
+#[derive(Debug, Clone)]
+pub struct PullListLine {
+    pub inventory_id: i32,
+    pub name: String,
+    pub unit: String,
+    pub required: f32,
+    // None when the inventory item no longer exists
+    pub on_hand: Option<f32>,
+    pub recipes: Vec<String>,
+}
+
+impl PullListLine {
+    pub fn shortfall(&self) -> f32 {
+        (self.required - self.on_hand.unwrap_or(0.0)).max(0.0)
+    }
+}
+
+// A known event that moves demand on a date (holiday, market, weather), for one recipe or all
+#[derive(Debug, Clone)]
+pub struct ForecastEvent {
+    pub id: i32,
+    pub event_date: String,
+    pub name: String,
+    // None applies to every recipe
+    pub recipe_id: Option<i32>,
+    // e.g. 50 for +50%, -30 for -30%
+    pub adjust_percent: f32,
+}
+
+impl ForecastEvent {
+    pub fn describe(&self) -> String {
+        format!("{} {:+.0}%", self.name, self.adjust_percent)
+    }
+}
+
+// Shared equipment used by one step type, e.g. the oven for bake steps.
+// Capacity is how many batches it holds at once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Resource {
+    pub id: i32,
+    pub name: String,
+    pub step_type: String,
+    pub capacity: i32,
+}
+
+pub const STEP_TYPES: [&str; 7] = ["mix", "bulk ferment", "shape", "proof", "bake", "cool", "other"];
+
+// One ordered step of a recipe's method; durations in minutes, temperature in °F
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecipeStep {
+    pub id: i32,
+    pub recipe_id: i32,
+    pub position: i32,
+    pub step_type: String,
+    pub description: String,
+    pub min_minutes: Option<i32>,
+    pub max_minutes: Option<i32>,
+    pub temperature_f: Option<f32>,
+    pub notes: Option<String>,
+}
+
+impl RecipeStep {
+    pub fn duration_label(&self) -> String {
+        match (self.min_minutes, self.max_minutes) {
+            (Some(min), Some(max)) if max > min => format!("{}-{}", format_minutes(min), format_minutes(max)),
+            (Some(min), _) => format_minutes(min),
+            _ => "untimed".to_string(),
+        }
+    }
+}
+
+// Computed time for a recipe's steps
+#[derive(Debug)]
+pub struct StepTotals {
+    pub min_minutes: i32,
+    pub max_minutes: i32,
+    // Steps with no duration, which the totals leave out
+    pub untimed: usize,
+    // (step type, min, max) in STEP_TYPES order, for types that appear
+    pub by_type: Vec<(String, i32, i32)>,
+}
+
+impl StepTotals {
+    pub fn label(&self) -> String {
+        let range = if self.max_minutes > self.min_minutes {
+            format!("{}-{}", format_minutes(self.min_minutes), format_minutes(self.max_minutes))
+        } else {
+            format_minutes(self.min_minutes)
+        };
+        match self.untimed {
+            0 => range,
+            n => format!("{} (+{} untimed step{})", range, n, if n == 1 { "" } else { "s" }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invoice_details(deposit_applied: f32, payments: &[f32], voided: bool) -> InvoiceDetails {
+        let invoice = Invoice {
+            id: 1,
+            number: 1,
+            customer_id: 1,
+            order_id: None,
+            sale_transaction_id: None,
+            issue_date: "2026-03-01".to_string(),
+            due_date: "2026-03-31".to_string(),
+            terms: "Net 30".to_string(),
+            tax_rate: 10.0,
+            deposit_applied,
+            notes: None,
+            voided,
+        };
+        let customer = Customer {
+            id: 1,
+            name: "Corner Cafe".to_string(),
+            email: None,
+            phone: None,
+            address: None,
+            customer_type: "wholesale".to_string(),
+            price_tier_id: None,
+            tax_exempt: false,
+            notes: None,
+        };
+        let lines = vec![InvoiceLine {
+            id: 1,
+            invoice_id: 1,
+            recipe_id: None,
+            description: "Babka".to_string(),
+            quantity: 10,
+            unit_price: 10.0,
+        }];
+        let payments = payments
+            .iter()
+            .map(|amount| InvoicePayment { date: "2026-03-15".to_string(), amount: *amount })
+            .collect();
+        InvoiceDetails::new(invoice, customer, lines, payments)
+    }
+
+    #[test]
+    fn invoice_status_follows_payments_and_due_date() {
+        // $100 of product plus 10% tax
+        let unpaid = invoice_details(0.0, &[], false);
+        assert_eq!(unpaid.total, 110.0);
+        assert_eq!(unpaid.status("2026-03-31"), "open");
+        assert_eq!(unpaid.status("2026-04-01"), "overdue");
+
+        let partial = invoice_details(10.0, &[40.0], false);
+        assert_eq!(partial.balance, 60.0);
+        assert_eq!(partial.status("2026-03-20"), "partial");
+        assert_eq!(partial.status("2026-04-01"), "overdue");
+
+        // The deposit counts toward payment, and a paid invoice is never overdue
+        let paid = invoice_details(10.0, &[60.0, 40.0], false);
+        assert_eq!(paid.status("2026-06-01"), "paid");
+        assert_eq!(invoice_details(0.0, &[109.996], false).status("2026-06-01"), "paid");
+
+        assert_eq!(invoice_details(0.0, &[], true).status("2026-06-01"), "void");
+    }
+
+    fn pan(shape: &str, length: Option<f32>, width: Option<f32>, depth: Option<f32>, volume: Option<f32>) -> Pan {
+        Pan {
+            id: 0,
+            name: String::new(),
+            shape: shape.to_string(),
+            length_in: length,
+            width_in: width,
+            diameter_in: None,
+            tube_diameter_in: None,
+            depth_in: depth,
+            volume_cups: volume,
+        }
+    }
+
+    #[test]
+    fn pan_scaling_needs_measurements_above_zero() {
+        let quarter_sheet = pan("rectangular", Some(9.0), Some(13.0), Some(2.0), None);
+        let square = pan("square", Some(9.0), None, Some(2.0), None);
+        let scaling = pan_scaling(&square, &quarter_sheet, false).unwrap();
+        assert!((scaling.factor - 13.0 / 9.0).abs() < 0.001);
+
+        let flat = pan("rectangular", Some(9.0), Some(0.0), Some(2.0), None);
+        assert!(flat.area().is_none());
+        assert!(pan_scaling(&square, &flat, false).is_none());
+        assert!(pan_scaling(&flat, &square, false).is_none());
+
+        let bad_volume = pan("bundt", None, None, Some(0.0), Some(-10.0));
+        assert!(bad_volume.volume_cups().is_none());
+        assert!(pan_scaling(&bad_volume, &square, true).is_none());
+    }
+
+    fn item(id: i32, name: &str, category: Option<&str>, quantity: f32, cost_per_unit: f32) -> InventoryItem {
+        InventoryItem {
+            id,
+            name: name.to_string(),
+            unit: "g".to_string(),
+            quantity,
+            cost_per_unit,
+            category: category.map(str::to_string),
+            role: None,
+        }
+    }
+
+    #[test]
+    fn snapshot_comparison_counts_added_and_removed_items_as_zero() {
+        let before = [
+            item(1, "Flour", Some("Dry Goods"), 10.0, 2.0),
+            item(2, "Butter", Some("Dairy"), 500.0, 0.01),
+            item(3, "Yeast", None, 100.0, 0.05),
+        ];
+        let after = [
+            item(3, "Yeast", None, 100.0, 0.05),
+            item(1, "Flour", Some("Dry Goods"), 4.0, 2.5),
+            item(4, "Salt", Some("Dry Goods"), 1000.0, 0.002),
+        ];
+
+        let changes = compare_snapshots(&before, &after);
+        let summary: Vec<(&str, &str, f32, f32)> = changes
+            .iter()
+            .map(|c| (c.category.as_str(), c.name.as_str(), c.quantity_change(), c.value_change()))
+            .collect();
+        assert_eq!(summary.len(), 4);
+        let expected = [
+            ("Dairy", "Butter", -500.0, -5.0),
+            ("Dry Goods", "Flour", -6.0, -10.0),
+            ("Dry Goods", "Salt", 1000.0, 2.0),
+            ("Uncategorized", "Yeast", 0.0, 0.0),
+        ];
+        for (line, want) in summary.iter().zip(expected) {
+            assert_eq!((line.0, line.1), (want.0, want.1));
+            assert!((line.2 - want.2).abs() < 0.001 && (line.3 - want.3).abs() < 0.001, "{:?}", line);
+        }
+        assert!(compare_snapshots(&before, &before).iter().all(|c| c.quantity_change() == 0.0 && c.value_change() == 0.0));
+    }
+
+    fn recurring(frequency: &str, interval: i32, day_of_month: Option<i32>, start: &str, end: Option<&str>) -> RecurringTransaction {
+        RecurringTransaction {
+            id: 1,
+            description: "Rent".to_string(),
+            transaction_type: "expense".to_string(),
+            amount: 1200.0,
+            is_estimate: false,
+            account: "Operating".to_string(),
+            frequency: frequency.to_string(),
+            interval,
+            day_of_month,
+            start_date: start.to_string(),
+            end_date: end.map(str::to_string),
+            last_posted_date: None,
+            category: None,
+        }
+    }
+
+    fn day(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+    }
+
+    fn dates(dates: &[&str]) -> Vec<NaiveDate> {
+        dates.iter().map(|d| day(d)).collect()
+    }
+
+    #[test]
+    fn monthly_due_dates_clamp_day_31_to_short_months() {
+        let rent = recurring("monthly", 1, Some(31), "2026-01-31", None);
+        assert_eq!(
+            rent.due_dates(day("2026-04-30")),
+            dates(&["2026-01-31", "2026-02-28", "2026-03-31", "2026-04-30"])
+        );
+
+        let leap = recurring("monthly", 1, Some(31), "2027-12-31", None);
+        assert_eq!(leap.due_dates(day("2028-03-01")), dates(&["2027-12-31", "2028-01-31", "2028-02-29"]));
+    }
+
+    #[test]
+    fn due_dates_step_by_interval_and_stop_at_the_end_date() {
+        let fortnightly = recurring("weekly", 2, None, "2026-03-02", Some("2026-03-31"));
+        assert_eq!(
+            fortnightly.due_dates(day("2026-06-01")),
+            dates(&["2026-03-02", "2026-03-16", "2026-03-30"])
+        );
+
+        let quarterly = recurring("monthly", 3, None, "2026-01-15", None);
+        assert_eq!(
+            quarterly.due_dates(day("2026-12-31")),
+            dates(&["2026-01-15", "2026-04-15", "2026-07-15", "2026-10-15"])
+        );
+        let ended = recurring("monthly", 3, None, "2026-01-15", Some("2026-08-01"));
+        assert_eq!(ended.due_dates(day("2026-12-31")), dates(&["2026-01-15", "2026-04-15", "2026-07-15"]));
+    }
+
+    #[test]
+    fn due_dates_skip_occurrences_already_posted() {
+        let mut rent = recurring("monthly", 1, Some(1), "2026-01-01", None);
+        rent.last_posted_date = Some("2026-03-01".to_string());
+        assert_eq!(rent.due_dates(day("2026-05-10")), dates(&["2026-04-01", "2026-05-01"]));
+
+        rent.last_posted_date = Some("2026-05-01".to_string());
+        assert!(rent.due_dates(day("2026-05-10")).is_empty());
+    }
 }
@@ -0,0 +1,158 @@
+// src/plan.rs
+// Production plan helpers: converting quantities between units and consolidating a plan's
+// recipes into a pull list of inventory items.
+// Pure calculations only; callers load the plan, recipes and inventory and print the result.
+
+use crate::bakers_math::to_grams;
+use crate::models::{InventoryItem, PullListLine, RecipeIngredient};
+
+// Convert a volume into millilitres; None for weight, count or unknown units
+pub fn to_milliliters(quantity: f32, unit: &str) -> Option<f32> {
+    match unit.trim().to_lowercase().as_str() {
+        "ml" | "milliliter" | "milliliters" | "millilitre" | "millilitres" => Some(quantity),
+        "l" | "liter" | "liters" | "litre" | "litres" => Some(quantity * 1000.0),
+        "tsp" | "teaspoon" | "teaspoons" => Some(quantity * 4.928_922),
+        "tbsp" | "tablespoon" | "tablespoons" => Some(quantity * 14.786_765),
+        "fl oz" | "fluid ounce" | "fluid ounces" => Some(quantity * 29.573_53),
+        "cup" | "cups" => Some(quantity * 236.588_24),
+        _ => None,
+    }
+}
+
+// Convert a quantity between two units of the same kind (weight or volume)
+pub fn convert_quantity(quantity: f32, from: &str, to: &str) -> Option<f32> {
+    if from.trim().eq_ignore_ascii_case(to.trim()) {
+        return Some(quantity);
+    }
+    if let (Some(grams), Some(per_unit)) = (to_grams(quantity, from), to_grams(1.0, to)) {
+        return Some(grams / per_unit);
+    }
+    match (to_milliliters(quantity, from), to_milliliters(1.0, to)) {
+        (Some(ml), Some(per_unit)) => Some(ml / per_unit),
+        _ => None,
+    }
+}
+
+// A quantity in the easiest unit to weigh or measure out: kg/g for weights, l/ml for volumes
+pub fn friendly_quantity(quantity: f32, unit: &str) -> String {
+    if let Some(grams) = convert_quantity(quantity, unit, "g") {
+        return if grams.abs() >= 1000.0 { format!("{:.2} kg", grams / 1000.0) } else { format!("{:.1} g", grams) };
+    }
+    if let Some(ml) = convert_quantity(quantity, unit, "ml") {
+        return if ml.abs() >= 1000.0 { format!("{:.2} l", ml / 1000.0) } else { format!("{:.0} ml", ml) };
+    }
+    format!("{:.2} {}", quantity, unit)
+}
+
+// (recipe name, batches, ingredient lines per batch)
+pub type PlannedRecipe = (String, f32, Vec<RecipeIngredient>);
+
+// Consolidate each recipe's ingredients times its batches into one line per inventory item.
+// Recipe quantities are already in the item's stocked unit, so lines add up directly.
+pub fn build_pull_list(
+    recipes: &[PlannedRecipe],
+    inventory: &[InventoryItem],
+) -> (Vec<PullListLine>, Vec<String>) {
+    let mut lines: Vec<PullListLine> = Vec::new();
+    let mut warnings = Vec::new();
+
+    for (recipe_name, batches, ingredients) in recipes {
+        for ingredient in ingredients {
+            let required = ingredient.quantity_required * batches;
+            match lines.iter_mut().find(|line| line.inventory_id == ingredient.ingredient_id) {
+                Some(line) => {
+                    line.required += required;
+                    if !line.recipes.contains(recipe_name) {
+                        line.recipes.push(recipe_name.clone());
+                    }
+                }
+                None => {
+                    let stock = inventory.iter().find(|item| item.id == ingredient.ingredient_id);
+                    if stock.is_none() {
+                        warnings.push(format!(
+                            "{} uses inventory item #{}, which no longer exists",
+                            recipe_name, ingredient.ingredient_id
+                        ));
+                    }
+                    lines.push(PullListLine {
+                        inventory_id: ingredient.ingredient_id,
+                        name: stock.map_or(format!("Item #{}", ingredient.ingredient_id), |item| item.name.clone()),
+                        unit: stock.map_or(String::new(), |item| item.unit.clone()),
+                        required,
+                        on_hand: stock.map(|item| item.quantity),
+                        recipes: vec![recipe_name.clone()],
+                    });
+                }
+            }
+        }
+    }
+
+    lines.sort_by(|a, b| a.name.cmp(&b.name).then(a.inventory_id.cmp(&b.inventory_id)));
+    (lines, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stock(id: i32, name: &str, unit: &str, quantity: f32) -> InventoryItem {
+        InventoryItem {
+            id,
+            name: name.to_string(),
+            unit: unit.to_string(),
+            quantity,
+            cost_per_unit: 1.0,
+            category: None,
+            role: None,
+        }
+    }
+
+    fn uses(ingredient_id: i32, quantity_required: f32) -> RecipeIngredient {
+        RecipeIngredient { recipe_id: 0, ingredient_id, quantity_required }
+    }
+
+    #[test]
+    fn convert_quantity_between_units_of_a_kind() {
+        assert_eq!(convert_quantity(2.5, "KG", "kg"), Some(2.5));
+        assert!((convert_quantity(1.5, "kg", "g").unwrap() - 1500.0).abs() < 0.01);
+        assert!((convert_quantity(1.0, "lb", "oz").unwrap() - 16.0).abs() < 0.01);
+        assert!((convert_quantity(2.0, "cups", "ml").unwrap() - 473.18).abs() < 0.01);
+        assert!((convert_quantity(3.0, "tsp", "tbsp").unwrap() - 1.0).abs() < 0.01);
+        assert_eq!(convert_quantity(1.0, "kg", "ml"), None);
+        assert_eq!(convert_quantity(12.0, "each", "g"), None);
+        assert_eq!(friendly_quantity(1.25, "kg"), "1.25 kg");
+        assert_eq!(friendly_quantity(12.0, "each"), "12.00 each");
+    }
+
+    #[test]
+    fn pull_list_totals_by_inventory_item() {
+        // Two stocked items share a name; they stay separate lines
+        let inventory = [stock(1, "Flour", "kg", 5.0), stock(2, "Flour", "kg", 1.0), stock(3, "Butter", "grams", 100.0)];
+        let recipes: Vec<PlannedRecipe> = vec![
+            ("Baguette".to_string(), 2.0, vec![uses(1, 1.0), uses(3, 10.0)]),
+            ("Brioche".to_string(), 0.5, vec![uses(1, 2.0), uses(2, 3.0), uses(3, 250.0)]),
+        ];
+
+        let (lines, warnings) = build_pull_list(&recipes, &inventory);
+        assert!(warnings.is_empty());
+        assert_eq!(lines.len(), 3);
+
+        assert_eq!((lines[0].name.as_str(), lines[0].required), ("Butter", 145.0));
+        assert_eq!(lines[0].shortfall(), 45.0);
+        assert_eq!(lines[0].recipes, vec!["Baguette".to_string(), "Brioche".to_string()]);
+
+        assert_eq!((lines[1].inventory_id, lines[1].required, lines[1].unit.as_str()), (1, 3.0, "kg"));
+        assert_eq!(lines[1].shortfall(), 0.0);
+        assert_eq!((lines[2].inventory_id, lines[2].required), (2, 1.5));
+        assert_eq!(lines[2].shortfall(), 0.5);
+    }
+
+    #[test]
+    fn pull_list_reports_missing_inventory_items() {
+        let recipes: Vec<PlannedRecipe> = vec![("Baguette".to_string(), 1.0, vec![uses(9, 0.5)])];
+        let (lines, warnings) = build_pull_list(&recipes, &[]);
+        assert_eq!(lines[0].on_hand, None);
+        assert_eq!(lines[0].shortfall(), 0.5);
+        assert_eq!(warnings.len(), 1);
+    }
+}
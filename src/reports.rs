@@ -0,0 +1,959 @@
+// src/reports.rs
+use crate::models::{format_invoice_number, BudgetLine, CogsReport, InventoryItem, InvoiceDetails, ProductSales,
+    PullListLine, RecipeCosting, SnapshotChange, Transaction};
+use chrono::{Local, NaiveDate};
+use csv::Writer;
+use rust_xlsxwriter::{Color, ExcelDateTime, Format, FormatBorder, Workbook, Worksheet, XlsxError};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Escape text for safe inclusion in HTML
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Resolve where an export should be written
+// Blank input uses reports/<default_name>; input ending in the file's extension is a file, anything else a directory
+// With `timestamped`, the current date and time are added to the file name so earlier exports are kept
+pub fn export_path(input: &str, default_name: &str, timestamped: bool) -> PathBuf {
+    let default_path = Path::new(default_name);
+    let extension = default_path.extension().and_then(|e| e.to_str()).unwrap_or("");
+
+    let path = if input.is_empty() {
+        PathBuf::from("reports").join(default_name)
+    } else if !extension.is_empty() && input.to_lowercase().ends_with(&format!(".{}", extension)) {
+        PathBuf::from(input)
+    } else {
+        PathBuf::from(input).join(default_name)
+    };
+
+    if !timestamped {
+        return path;
+    }
+
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("report").to_string();
+    let stamp = Local::now().format("%Y%m%d-%H%M%S");
+    let file_name = match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{}-{}.{}", stem, stamp, ext),
+        None => format!("{}-{}", stem, stamp),
+    };
+    path.with_file_name(file_name)
+}
+
+// Plain-text invoice
+pub fn render_invoice_text(details: &InvoiceDetails, today: &str) -> String {
+    let invoice = &details.invoice;
+    let customer = &details.customer;
+    let mut out = String::new();
+
+    out.push_str(&format!("INVOICE {}\n", format_invoice_number(invoice.number)));
+    out.push_str(&format!("{}\n", "=".repeat(64)));
+    out.push_str(&format!("Bill to: {}\n", customer.name));
+    if let Some(address) = &customer.address {
+        out.push_str(&format!("         {}\n", address));
+    }
+    if let Some(email) = &customer.email {
+        out.push_str(&format!("         {}\n", email));
+    }
+    out.push_str(&format!("\nIssued: {}\n", invoice.issue_date));
+    out.push_str(&format!("Due:    {} ({})\n", invoice.due_date, invoice.terms));
+    out.push_str(&format!("Status: {}\n", details.status(today)));
+    if let Some(order_id) = invoice.order_id {
+        out.push_str(&format!("Order:  #{}\n", order_id));
+    }
+
+    out.push_str(&format!("\n{:<32} {:>6} {:>10} {:>11}\n", "Item", "Qty", "Price", "Amount"));
+    out.push_str(&format!("{}\n", "-".repeat(64)));
+    for line in &details.lines {
+        out.push_str(&format!(
+            "{:<32} {:>6} {:>10.2} {:>11.2}\n",
+            line.description, line.quantity, line.unit_price, line.quantity as f32 * line.unit_price
+        ));
+    }
+    out.push_str(&format!("{}\n", "-".repeat(64)));
+    out.push_str(&format!("{:>50} {:>13.2}\n", "Subtotal:", details.subtotal));
+    if details.tax > 0.0 {
+        out.push_str(&format!("{:>50} {:>13.2}\n", format!("Tax ({:.2}%):", invoice.tax_rate), details.tax));
+    }
+    out.push_str(&format!("{:>50} {:>13.2}\n", "Total:", details.total));
+    if invoice.deposit_applied > 0.0 {
+        out.push_str(&format!("{:>50} {:>13.2}\n", "Deposit received:", -invoice.deposit_applied));
+    }
+    for payment in &details.payments {
+        out.push_str(&format!("{:>50} {:>13.2}\n", format!("Payment {}:", payment.date), -payment.amount));
+    }
+    out.push_str(&format!("{:>50} {:>13.2}\n", "Balance due:", details.balance));
+
+    if let Some(notes) = &invoice.notes {
+        out.push_str(&format!("\nNotes: {}\n", notes));
+    }
+    out.push_str("\nThank you for your business!\n");
+
+    out
+}
+
+// Printable standalone HTML invoice
+pub fn render_invoice_html(details: &InvoiceDetails, today: &str) -> String {
+    let invoice = &details.invoice;
+    let customer = &details.customer;
+    let number = format_invoice_number(invoice.number);
+
+    let mut rows = String::new();
+    for line in &details.lines {
+        rows.push_str(&format!(
+            "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">${:.2}</td><td class=\"num\">${:.2}</td></tr>\n",
+            escape_html(&line.description), line.quantity, line.unit_price, line.quantity as f32 * line.unit_price
+        ));
+    }
+
+    let mut totals = format!("<tr><th colspan=\"3\">Subtotal</th><td class=\"num\">${:.2}</td></tr>\n", details.subtotal);
+    if details.tax > 0.0 {
+        totals.push_str(&format!(
+            "<tr><th colspan=\"3\">Tax ({:.2}%)</th><td class=\"num\">${:.2}</td></tr>\n",
+            invoice.tax_rate, details.tax
+        ));
+    }
+    totals.push_str(&format!("<tr><th colspan=\"3\">Total</th><td class=\"num\">${:.2}</td></tr>\n", details.total));
+    if invoice.deposit_applied > 0.0 {
+        totals.push_str(&format!(
+            "<tr><th colspan=\"3\">Deposit received</th><td class=\"num\">-${:.2}</td></tr>\n",
+            invoice.deposit_applied
+        ));
+    }
+    for payment in &details.payments {
+        totals.push_str(&format!(
+            "<tr><th colspan=\"3\">Payment {}</th><td class=\"num\">-${:.2}</td></tr>\n",
+            escape_html(&payment.date), payment.amount
+        ));
+    }
+    totals.push_str(&format!(
+        "<tr class=\"due\"><th colspan=\"3\">Balance due</th><td class=\"num\">${:.2}</td></tr>\n",
+        details.balance
+    ));
+
+    let mut bill_to = escape_html(&customer.name);
+    for extra in [&customer.address, &customer.email, &customer.phone].into_iter().flatten() {
+        bill_to.push_str(&format!("<br>{}", escape_html(extra)));
+    }
+
+    let notes = match &invoice.notes {
+        Some(notes) => format!("<p class=\"notes\">{}</p>", escape_html(notes)),
+        None => String::new(),
+    };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Invoice {number}</title>
+<style>
+body {{ font-family: Helvetica, Arial, sans-serif; color: #333; max-width: 720px; margin: 2em auto; }}
+h1 {{ margin-bottom: 0; }}
+.meta {{ display: flex; justify-content: space-between; margin: 1.5em 0; }}
+table {{ width: 100%; border-collapse: collapse; }}
+th, td {{ padding: 6px 8px; border-bottom: 1px solid #ddd; text-align: left; }}
+.num {{ text-align: right; }}
+tfoot th {{ text-align: right; font-weight: normal; }}
+tr.due th, tr.due td {{ font-weight: bold; border-top: 2px solid #333; }}
+.status {{ text-transform: uppercase; font-weight: bold; }}
+.notes {{ margin-top: 1.5em; font-style: italic; }}
+@media print {{ body {{ margin: 0; }} }}
+</style>
+</head>
+<body>
+<h1>Invoice {number}</h1>
+<div class="meta">
+<div><strong>Bill to</strong><br>{bill_to}</div>
+<div>Issued: {issued}<br>Due: {due} ({terms})<br>Status: <span class="status">{status}</span></div>
+</div>
+<table>
+<thead><tr><th>Item</th><th class="num">Qty</th><th class="num">Price</th><th class="num">Amount</th></tr></thead>
+<tbody>
+{rows}</tbody>
+<tfoot>
+{totals}</tfoot>
+</table>
+{notes}
+<p>Thank you for your business!</p>
+</body>
+</html>
+"#,
+        number = number,
+        bill_to = bill_to,
+        issued = escape_html(&invoice.issue_date),
+        due = escape_html(&invoice.due_date),
+        terms = escape_html(&invoice.terms),
+        status = details.status(today),
+        rows = rows,
+        totals = totals,
+        notes = notes,
+    )
+}
+
+// Write the HTML and plain-text invoice to reports/invoices/
+pub fn write_invoice_documents(details: &InvoiceDetails, today: &str) -> io::Result<(PathBuf, PathBuf)> {
+    let dir = PathBuf::from("reports").join("invoices");
+    fs::create_dir_all(&dir)?;
+
+    let number = format_invoice_number(details.invoice.number);
+    let html_path = dir.join(format!("{}.html", number));
+    let text_path = dir.join(format!("{}.txt", number));
+
+    fs::write(&html_path, render_invoice_html(details, today))?;
+    fs::write(&text_path, render_invoice_text(details, today))?;
+
+    Ok((html_path, text_path))
+}
+
+// Accounts-receivable aging buckets
+pub const AGING_BUCKETS: [&str; 5] = ["Current", "1-30", "31-60", "61-90", "90+"];
+
+// Which aging bucket an invoice falls in, by days past its due date
+pub fn aging_bucket(due_date: &str, today: &str) -> usize {
+    let days_past_due = match (
+        NaiveDate::parse_from_str(due_date, "%Y-%m-%d"),
+        NaiveDate::parse_from_str(today, "%Y-%m-%d"),
+    ) {
+        (Ok(due), Ok(now)) => (now - due).num_days(),
+        _ => 0,
+    };
+
+    match days_past_due {
+        d if d <= 0 => 0,
+        1..=30 => 1,
+        31..=60 => 2,
+        61..=90 => 3,
+        _ => 4,
+    }
+}
+
+// Aging report across all open invoices, one row per customer plus totals
+pub fn render_ar_aging(invoices: &[InvoiceDetails], today: &str) -> String {
+    let mut customers: Vec<(String, [f32; 5])> = Vec::new();
+    let mut totals = [0.0_f32; 5];
+
+    for details in invoices.iter().filter(|d| !d.invoice.voided && d.balance > 0.005) {
+        let bucket = aging_bucket(&details.invoice.due_date, today);
+        totals[bucket] += details.balance;
+
+        match customers.iter_mut().find(|(name, _)| *name == details.customer.name) {
+            Some((_, buckets)) => buckets[bucket] += details.balance,
+            None => {
+                let mut buckets = [0.0_f32; 5];
+                buckets[bucket] = details.balance;
+                customers.push((details.customer.name.clone(), buckets));
+            }
+        }
+    }
+    customers.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut out = format!("Accounts Receivable Aging as of {}\n", today);
+    out.push_str(&format!("{:<24}", "Customer"));
+    for bucket in AGING_BUCKETS {
+        out.push_str(&format!(" {:>10}", bucket));
+    }
+    out.push_str(&format!(" {:>11}\n", "Total"));
+    out.push_str(&format!("{}\n", "-".repeat(91)));
+
+    for (name, buckets) in &customers {
+        out.push_str(&format!("{:<24}", name));
+        for amount in buckets {
+            out.push_str(&format!(" {:>10.2}", amount));
+        }
+        out.push_str(&format!(" {:>11.2}\n", buckets.iter().sum::<f32>()));
+    }
+
+    out.push_str(&format!("{}\n", "-".repeat(91)));
+    out.push_str(&format!("{:<24}", "Total"));
+    for amount in totals {
+        out.push_str(&format!(" {:>10.2}", amount));
+    }
+    out.push_str(&format!(" {:>11.2}\n", totals.iter().sum::<f32>()));
+
+    out
+}
+
+// Side-by-side theoretical and actual cost of goods sold
+pub fn render_cogs_report(report: &CogsReport) -> String {
+    let mut out = format!("Cost of Goods Sold: {} to {}\n", report.start_date, report.end_date);
+    out.push_str(&format!("{}\n", "=".repeat(72)));
+
+    out.push_str("\nTheoretical (production runs x recipe cost)\n");
+    out.push_str(&format!("{:<4} {:<25} {:>10} {:>10} {:>14}\n", "ID", "Recipe", "Batches", "Units", "Cost"));
+    out.push_str(&format!("{}\n", "-".repeat(72)));
+    for line in &report.lines {
+        out.push_str(&format!(
+            "{:<4} {:<25} {:>10.2} {:>10} {:>14.2}\n",
+            line.recipe_id, line.recipe_name, line.batches, line.units_produced, line.theoretical_cost
+        ));
+    }
+    if report.lines.is_empty() {
+        out.push_str("(no production runs in this period)\n");
+    }
+    out.push_str(&format!("{:<52} {:>14.2}\n", "Theoretical COGS", report.theoretical()));
+
+    out.push_str("\nActual (opening inventory + purchases - closing inventory)\n");
+    out.push_str(&format!("{}\n", "-".repeat(72)));
+    out.push_str(&format!("{:<52} {:>14.2}\n", "Opening inventory value", report.opening_value));
+    out.push_str(&format!("{:<52} {:>14.2}\n", "+ Purchases", report.purchases));
+    out.push_str(&format!("{:<52} {:>14.2}\n", "- Closing inventory value", report.closing_value));
+    out.push_str(&format!("{:<52} {:>14.2}\n", "Actual COGS", report.actual()));
+
+    out.push_str(&format!("\n{:<26} {:>14} {:>14} {:>14}\n", "", "Theoretical", "Actual", "Gap"));
+    out.push_str(&format!("{}\n", "-".repeat(72)));
+    let gap_percent = match report.gap_percent() {
+        Some(percent) => format!(" ({:+.1}%)", percent),
+        None => String::new(),
+    };
+    out.push_str(&format!(
+        "{:<26} {:>14.2} {:>14.2} {:>+14.2}{}\n",
+        "COGS", report.theoretical(), report.actual(), report.gap(), gap_percent
+    ));
+    if report.gap() > 0.005 {
+        out.push_str("Actual usage exceeded theoretical: check waste, theft and over-portioning.\n");
+    }
+
+    out
+}
+
+// Stock value per item, grouped by category with subtotals
+pub fn render_inventory_valuation(title: &str, items: &[InventoryItem]) -> String {
+    let mut out = format!("Inventory Valuation — {}\n", title);
+    out.push_str(&format!("{:<34} {:>12} {:<8} {:>10} {:>12}\n", "Item", "Quantity", "Unit", "Cost/Unit", "Value"));
+    out.push_str(&format!("{}\n", "=".repeat(80)));
+
+    let mut total = 0.0;
+    let mut category: Option<&str> = None;
+    let mut subtotal = 0.0;
+    for item in items {
+        if category != Some(item.category_label()) {
+            if let Some(previous) = category {
+                out.push_str(&format!("{:<68} {:>11.2}\n\n", format!("Subtotal {}", previous), subtotal));
+            }
+            category = Some(item.category_label());
+            subtotal = 0.0;
+            out.push_str(&format!("{}\n", item.category_label()));
+            out.push_str(&format!("{}\n", "-".repeat(80)));
+        }
+        out.push_str(&format!(
+            "{:<34} {:>12.2} {:<8} {:>10.4} {:>12.2}\n",
+            item.name, item.quantity, item.unit, item.cost_per_unit, item.value()
+        ));
+        subtotal += item.value();
+        total += item.value();
+    }
+    if let Some(previous) = category {
+        out.push_str(&format!("{:<68} {:>11.2}\n", format!("Subtotal {}", previous), subtotal));
+    }
+
+    out.push_str(&format!("{}\n", "=".repeat(80)));
+    out.push_str(&format!("{:<68} {:>11.2}\n", "Total inventory value", total));
+
+    out
+}
+
+// Quantity and value changes between two snapshots; unchanged items are left out
+pub fn render_snapshot_comparison(before: &str, after: &str, changes: &[SnapshotChange]) -> String {
+    let mut out = format!("Inventory changes from {} to {}\n", before, after);
+    out.push_str(&format!(
+        "{:<18} {:<28} {:>10} {:>10} {:>10} {:>11}\n",
+        "Category", "Item", "Before", "After", "Qty Chg", "Value Chg"
+    ));
+    out.push_str(&format!("{}\n", "-".repeat(92)));
+
+    let mut unchanged = 0;
+    for change in changes {
+        if change.quantity_change().abs() < 0.0005 && change.value_change().abs() < 0.005 {
+            unchanged += 1;
+            continue;
+        }
+        out.push_str(&format!(
+            "{:<18} {:<28} {:>10.2} {:>10.2} {:>+10.2} {:>+11.2}\n",
+            change.category, change.name, change.before_quantity, change.after_quantity,
+            change.quantity_change(), change.value_change()
+        ));
+    }
+
+    let before_total = changes.iter().fold(0.0, |sum, c| sum + c.before_value);
+    let after_total = changes.iter().fold(0.0, |sum, c| sum + c.after_value);
+    out.push_str(&format!("{}\n", "-".repeat(92)));
+    out.push_str(&format!("Value before: ${:.2}\n", before_total));
+    out.push_str(&format!("Value after:  ${:.2}\n", after_total));
+    out.push_str(&format!("Change:       ${:+.2}\n", after_total - before_total));
+    out.push_str(&format!("({} items unchanged)\n", unchanged));
+
+    out
+}
+
+// Inventory valuation as CSV, one row per item
+pub fn write_inventory_valuation_csv(path: &Path, items: &[InventoryItem]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut writer = Writer::from_path(path)?;
+
+    writer.write_record(["id", "name", "category", "unit", "quantity", "cost_per_unit", "value"])?;
+    for item in items {
+        writer.write_record([
+            item.id.to_string(),
+            item.name.clone(),
+            item.category_label().to_string(),
+            item.unit.clone(),
+            format!("{:.4}", item.quantity),
+            format!("{:.5}", item.cost_per_unit),
+            format!("{:.2}", item.value()),
+        ])?;
+    }
+
+    writer.flush()
+}
+
+// Snapshot comparison as CSV, including unchanged items
+pub fn write_snapshot_comparison_csv(path: &Path, changes: &[SnapshotChange]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut writer = Writer::from_path(path)?;
+
+    writer.write_record([
+        "name", "category", "unit", "quantity_before", "quantity_after", "quantity_change",
+        "value_before", "value_after", "value_change",
+    ])?;
+    for change in changes {
+        writer.write_record([
+            change.name.clone(),
+            change.category.clone(),
+            change.unit.clone(),
+            format!("{:.4}", change.before_quantity),
+            format!("{:.4}", change.after_quantity),
+            format!("{:.4}", change.quantity_change()),
+            format!("{:.2}", change.before_value),
+            format!("{:.2}", change.after_value),
+            format!("{:.2}", change.value_change()),
+        ])?;
+    }
+
+    writer.flush()
+}
+
+const CURRENCY_FORMAT: &str = "$#,##0.00;[Red]-$#,##0.00";
+
+// Shared cell formats for the bookkeeping workbook
+struct WorkbookFormats {
+    header: Format,
+    title: Format,
+    date: Format,
+    currency: Format,
+    unit_cost: Format,
+    quantity: Format,
+    percent: Format,
+    total_label: Format,
+    total_currency: Format,
+}
+
+impl WorkbookFormats {
+    fn new() -> WorkbookFormats {
+        WorkbookFormats {
+            header: Format::new().set_bold().set_background_color(Color::RGB(0xF3E5D0)).set_border_bottom(FormatBorder::Thin),
+            title: Format::new().set_bold().set_font_size(14),
+            date: Format::new().set_num_format("yyyy-mm-dd"),
+            currency: Format::new().set_num_format(CURRENCY_FORMAT),
+            unit_cost: Format::new().set_num_format("$#,##0.0000"),
+            quantity: Format::new().set_num_format("#,##0.00"),
+            percent: Format::new().set_num_format("0.0%"),
+            total_label: Format::new().set_bold().set_border_top(FormatBorder::Thin),
+            total_currency: Format::new().set_bold().set_num_format(CURRENCY_FORMAT).set_border_top(FormatBorder::Thin),
+        }
+    }
+}
+
+// Bold header row with column widths, frozen so it stays visible while scrolling
+fn write_header_row(sheet: &mut Worksheet, row: u32, columns: &[(&str, f64)], formats: &WorkbookFormats) -> Result<(), XlsxError> {
+    for (col, (title, width)) in columns.iter().enumerate() {
+        sheet.write_string_with_format(row, col as u16, *title, &formats.header)?;
+        sheet.set_column_width(col as u16, *width)?;
+    }
+    sheet.set_freeze_panes(row + 1, 0)?;
+
+    Ok(())
+}
+
+// Column letter for simple A1 formulas (first 26 columns)
+fn column_letter(col: u16) -> char {
+    (b'A' + col as u8) as char
+}
+
+fn write_transactions_sheet(sheet: &mut Worksheet, transactions: &[Transaction], formats: &WorkbookFormats) -> Result<(), XlsxError> {
+    sheet.set_name("Transactions")?;
+    write_header_row(sheet, 0, &[
+        ("ID", 6.0), ("Date", 12.0), ("Type", 10.0), ("Account", 14.0), ("Category", 18.0),
+        ("Description", 42.0), ("Amount", 13.0), ("Cleared", 9.0), ("Locked", 9.0),
+    ], formats)?;
+
+    for (i, tx) in transactions.iter().enumerate() {
+        let row = i as u32 + 1;
+        sheet.write_number(row, 0, tx.id)?;
+        match ExcelDateTime::parse_from_str(&tx.date) {
+            Ok(date) => sheet.write_datetime_with_format(row, 1, &date, &formats.date)?,
+            Err(_) => sheet.write_string(row, 1, &tx.date)?,
+        };
+        sheet.write_string(row, 2, &tx.transaction_type)?;
+        sheet.write_string(row, 3, &tx.account)?;
+        sheet.write_string(row, 4, tx.category.as_deref().unwrap_or(""))?;
+        sheet.write_string(row, 5, &tx.description)?;
+        sheet.write_number_with_format(row, 6, tx.amount, &formats.currency)?;
+        sheet.write_boolean(row, 7, tx.cleared)?;
+        sheet.write_boolean(row, 8, tx.locked)?;
+    }
+    if !transactions.is_empty() {
+        sheet.autofilter(0, 0, transactions.len() as u32, 8)?;
+    }
+
+    Ok(())
+}
+
+// Revenue and expenses by category against budget, with net income
+fn write_profit_and_loss_sheet(sheet: &mut Worksheet, month: &str, lines: &[BudgetLine], formats: &WorkbookFormats) -> Result<(), XlsxError> {
+    sheet.set_name("P&L Summary")?;
+    sheet.write_string_with_format(0, 0, format!("Profit & Loss — {}", month), &formats.title)?;
+    write_header_row(sheet, 2, &[("Category", 28.0), ("Actual", 14.0), ("Budget", 14.0), ("Variance", 14.0)], formats)?;
+
+    let mut row = 3;
+    let mut total_rows = Vec::new();
+    for (budget_type, heading) in [("revenue", "Revenue"), ("expense", "Expenses")] {
+        sheet.write_string_with_format(row, 0, heading, &formats.total_label)?;
+        row += 1;
+        let first = row;
+        for line in lines.iter().filter(|l| l.budget_type == budget_type) {
+            sheet.write_string(row, 0, format!("  {}", line.category))?;
+            sheet.write_number_with_format(row, 1, line.actual, &formats.currency)?;
+            sheet.write_number_with_format(row, 2, line.budgeted, &formats.currency)?;
+            sheet.write_number_with_format(row, 3, line.variance(), &formats.currency)?;
+            row += 1;
+        }
+        sheet.write_string_with_format(row, 0, format!("Total {}", heading.to_lowercase()), &formats.total_label)?;
+        for col in 1..=3u16 {
+            let letter = column_letter(col);
+            let formula = if row > first {
+                format!("=SUM({}{}:{}{})", letter, first + 1, letter, row)
+            } else {
+                "=0".to_string()
+            };
+            sheet.write_formula_with_format(row, col, formula.as_str(), &formats.total_currency)?;
+        }
+        total_rows.push(row);
+        row += 2;
+    }
+
+    // Net income = revenue - expenses; net variance is already signed favourable-positive
+    sheet.write_string_with_format(row, 0, "Net income", &formats.total_label)?;
+    for col in 1..=3u16 {
+        let letter = column_letter(col);
+        let operator = if col == 3 { "+" } else { "-" };
+        let formula = format!("={}{}{}{}{}", letter, total_rows[0] + 1, operator, letter, total_rows[1] + 1);
+        sheet.write_formula_with_format(row, col, formula.as_str(), &formats.total_currency)?;
+    }
+
+    Ok(())
+}
+
+fn write_inventory_sheet(sheet: &mut Worksheet, items: &[InventoryItem], formats: &WorkbookFormats) -> Result<(), XlsxError> {
+    sheet.set_name("Inventory Valuation")?;
+    write_header_row(sheet, 0, &[
+        ("Category", 18.0), ("Item", 34.0), ("Quantity", 12.0), ("Unit", 9.0), ("Cost/Unit", 12.0), ("Value", 13.0),
+    ], formats)?;
+
+    for (i, item) in items.iter().enumerate() {
+        let row = i as u32 + 1;
+        sheet.write_string(row, 0, item.category_label())?;
+        sheet.write_string(row, 1, &item.name)?;
+        sheet.write_number_with_format(row, 2, item.quantity, &formats.quantity)?;
+        sheet.write_string(row, 3, &item.unit)?;
+        sheet.write_number_with_format(row, 4, item.cost_per_unit, &formats.unit_cost)?;
+        sheet.write_formula_with_format(row, 5, format!("=C{}*E{}", row + 1, row + 1).as_str(), &formats.currency)?;
+    }
+
+    let total_row = items.len() as u32 + 1;
+    sheet.write_string_with_format(total_row, 0, "Total inventory value", &formats.total_label)?;
+    sheet.write_formula_with_format(total_row, 5, format!("=SUM(F2:F{})", total_row.max(2)).as_str(), &formats.total_currency)?;
+    if !items.is_empty() {
+        sheet.autofilter(0, 0, items.len() as u32, 5)?;
+    }
+
+    Ok(())
+}
+
+fn write_recipe_costing_sheet(sheet: &mut Worksheet, costing: &[RecipeCosting], formats: &WorkbookFormats) -> Result<(), XlsxError> {
+    sheet.set_name("Recipe Costing")?;
+    write_header_row(sheet, 0, &[
+        ("Recipe", 28.0), ("Category", 14.0), ("Yield", 8.0), ("Batch Cost", 13.0), ("Unit Cost", 12.0),
+        ("MSRP/Unit", 12.0), ("Unit Margin", 13.0), ("Margin %", 10.0),
+    ], formats)?;
+
+    for (i, recipe) in costing.iter().enumerate() {
+        let row = i as u32 + 1;
+        sheet.write_string(row, 0, &recipe.recipe_name)?;
+        sheet.write_string(row, 1, &recipe.category)?;
+        sheet.write_number(row, 2, recipe.yield_quantity)?;
+        sheet.write_number_with_format(row, 3, recipe.batch_cost, &formats.currency)?;
+        sheet.write_number_with_format(row, 4, recipe.unit_cost, &formats.unit_cost)?;
+        if let (Some(msrp), Some(margin)) = (recipe.msrp_per_unit, recipe.unit_margin) {
+            sheet.write_number_with_format(row, 5, msrp, &formats.currency)?;
+            sheet.write_number_with_format(row, 6, margin, &formats.currency)?;
+        }
+        if let Some(percent) = recipe.margin_percent {
+            sheet.write_number_with_format(row, 7, percent / 100.0, &formats.percent)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Monthly bookkeeping pack: transactions, P&L, inventory valuation and recipe costing in one workbook
+pub fn write_bookkeeping_workbook(
+    path: &Path,
+    month: &str,
+    transactions: &[Transaction],
+    profit_and_loss: &[BudgetLine],
+    inventory: &[InventoryItem],
+    costing: &[RecipeCosting],
+    ) -> Result<(), XlsxError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let formats = WorkbookFormats::new();
+    let mut workbook = Workbook::new();
+    write_transactions_sheet(workbook.add_worksheet(), transactions, &formats)?;
+    write_profit_and_loss_sheet(workbook.add_worksheet(), month, profit_and_loss, &formats)?;
+    write_inventory_sheet(workbook.add_worksheet(), inventory, &formats)?;
+    write_recipe_costing_sheet(workbook.add_worksheet(), costing, &formats)?;
+    workbook.save(path)?;
+
+    Ok(())
+}
+
+// Everything the HTML dashboard shows for a reporting period
+pub struct Dashboard {
+    pub start_date: String,
+    pub end_date: String,
+    pub generated_at: String,
+    // (period, revenue, expenses)
+    pub revenue_expenses: Vec<(String, f32, f32)>,
+    pub product_sales: Vec<ProductSales>,
+    pub inventory: Vec<InventoryItem>,
+    // (item, batches left)
+    pub low_stock: Vec<(InventoryItem, f32)>,
+    pub min_batches: f32,
+    pub costing: Vec<RecipeCosting>,
+}
+
+const REVENUE_COLOR: &str = "#4f8a5b";
+const EXPENSE_COLOR: &str = "#c0574b";
+const ACCENT_COLOR: &str = "#b7793e";
+
+fn money(value: f32) -> String {
+    if value < 0.0 {
+        format!("-${:.2}", -value)
+    } else {
+        format!("${:.2}", value)
+    }
+}
+
+// A "nice" axis maximum at or above `value`
+fn axis_max(value: f32) -> f32 {
+    if value <= 0.0 {
+        return 1.0;
+    }
+    let magnitude = 10f32.powf(value.log10().floor());
+    for step in [1.0, 2.0, 2.5, 5.0, 10.0] {
+        if step * magnitude >= value {
+            return step * magnitude;
+        }
+    }
+    10.0 * magnitude
+}
+
+// Grouped vertical bars: revenue vs expenses per period
+fn svg_revenue_expense_chart(series: &[(String, f32, f32)]) -> String {
+    if series.is_empty() {
+        return "<p class=\"empty\">No sales or expenses in this period.</p>".to_string();
+    }
+
+    let (width, height) = (760.0, 280.0);
+    let (left, right, top, bottom) = (64.0, 12.0, 16.0, 56.0);
+    let plot_width = width - left - right;
+    let plot_height = height - top - bottom;
+    let max = axis_max(series.iter().fold(0.0_f32, |m, (_, r, e)| m.max(*r).max(*e)));
+    let slot = plot_width / series.len() as f32;
+    let bar = (slot * 0.38).min(28.0);
+    // Thin out x labels so they don't overlap
+    let label_every = (series.len() as f32 / 16.0).ceil().max(1.0) as usize;
+
+    let mut svg = format!(
+        "<svg viewBox=\"0 0 {w} {h}\" width=\"100%\" role=\"img\" aria-label=\"Revenue vs expenses\">\n",
+        w = width, h = height
+    );
+    for tick in 0..=4 {
+        let value = max * tick as f32 / 4.0;
+        let y = top + plot_height - plot_height * tick as f32 / 4.0;
+        svg.push_str(&format!(
+            "<line x1=\"{l}\" y1=\"{y:.1}\" x2=\"{r}\" y2=\"{y:.1}\" class=\"grid\"/><text x=\"{tx}\" y=\"{ty:.1}\" class=\"axis\" text-anchor=\"end\">{v}</text>\n",
+            l = left, r = width - right, y = y, tx = left - 6.0, ty = y + 4.0, v = money(value)
+        ));
+    }
+    for (i, (period, revenue, expenses)) in series.iter().enumerate() {
+        let x = left + slot * i as f32 + slot / 2.0;
+        for (offset, value, color, label) in [(-bar, *revenue, REVENUE_COLOR, "Revenue"), (0.0, *expenses, EXPENSE_COLOR, "Expenses")] {
+            let bar_height = plot_height * value / max;
+            svg.push_str(&format!(
+                "<rect x=\"{x:.1}\" y=\"{y:.1}\" width=\"{w:.1}\" height=\"{h:.1}\" fill=\"{c}\"><title>{p} {l}: {v}</title></rect>\n",
+                x = x + offset, y = top + plot_height - bar_height, w = bar, h = bar_height, c = color,
+                p = escape_html(period), l = label, v = money(value)
+            ));
+        }
+        if i % label_every == 0 {
+            svg.push_str(&format!(
+                "<text x=\"{x:.1}\" y=\"{y:.1}\" class=\"axis\" text-anchor=\"end\" transform=\"rotate(-40 {x:.1} {y:.1})\">{p}</text>\n",
+                x = x, y = top + plot_height + 14.0, p = escape_html(period)
+            ));
+        }
+    }
+    svg.push_str(&format!(
+        "<rect x=\"{x}\" y=\"2\" width=\"10\" height=\"10\" fill=\"{rc}\"/><text x=\"{tx}\" y=\"11\" class=\"axis\">Revenue</text>\
+         <rect x=\"{x2}\" y=\"2\" width=\"10\" height=\"10\" fill=\"{ec}\"/><text x=\"{tx2}\" y=\"11\" class=\"axis\">Expenses</text>\n",
+        x = width - 170.0, tx = width - 156.0, x2 = width - 90.0, tx2 = width - 76.0, rc = REVENUE_COLOR, ec = EXPENSE_COLOR
+    ));
+    svg.push_str("</svg>");
+
+    svg
+}
+
+// Horizontal bars with a label and formatted value per row; negative values draw in the expense colour
+fn svg_horizontal_bars(rows: &[(String, f32, String)], color: &str) -> String {
+    if rows.is_empty() {
+        return "<p class=\"empty\">Nothing to show.</p>".to_string();
+    }
+
+    let (width, label_width, value_width, row_height) = (760.0, 200.0, 90.0, 24.0);
+    let plot_width = width - label_width - value_width;
+    let max = rows.iter().fold(0.0_f32, |m, (_, v, _)| m.max(v.abs()));
+    let max = if max > 0.0 { max } else { 1.0 };
+    let height = row_height * rows.len() as f32 + 4.0;
+
+    let mut svg = format!("<svg viewBox=\"0 0 {} {}\" width=\"100%\" role=\"img\">\n", width, height);
+    for (i, (label, value, display)) in rows.iter().enumerate() {
+        let y = 2.0 + row_height * i as f32;
+        let bar_width = plot_width * value.abs() / max;
+        let fill = if *value < 0.0 { EXPENSE_COLOR } else { color };
+        svg.push_str(&format!(
+            "<text x=\"{lx}\" y=\"{ty:.1}\" class=\"label\" text-anchor=\"end\">{label}</text>\
+             <rect x=\"{bx}\" y=\"{y:.1}\" width=\"{bw:.1}\" height=\"{bh}\" fill=\"{fill}\" rx=\"2\"/>\
+             <text x=\"{vx:.1}\" y=\"{ty:.1}\" class=\"value\">{display}</text>\n",
+            lx = label_width - 8.0, ty = y + 15.0, label = escape_html(label), bx = label_width, y = y,
+            bw = bar_width, bh = row_height - 6.0, fill = fill, vx = label_width + bar_width + 6.0, display = escape_html(display)
+        ));
+    }
+    svg.push_str("</svg>");
+
+    svg
+}
+
+// Self-contained dashboard page: embedded CSS, inline SVG, no external resources
+pub fn render_dashboard_html(dashboard: &Dashboard) -> String {
+    let revenue = dashboard.revenue_expenses.iter().fold(0.0, |sum, (_, r, _)| sum + r);
+    let expenses = dashboard.revenue_expenses.iter().fold(0.0, |sum, (_, _, e)| sum + e);
+    let inventory_value = dashboard.inventory.iter().fold(0.0, |sum, item| sum + item.value());
+
+    // Top sellers by revenue
+    let mut products: Vec<&ProductSales> = dashboard.product_sales.iter().collect();
+    products.sort_by(|a, b| b.revenue.total_cmp(&a.revenue));
+    let top_products: Vec<(String, f32, String)> = products
+        .iter()
+        .take(10)
+        .map(|p| (p.recipe_name.clone(), p.revenue, format!("{} ({} sold)", money(p.revenue), p.units_sold)))
+        .collect();
+
+    // Stock value by category
+    let mut categories: Vec<(String, f32)> = Vec::new();
+    for item in &dashboard.inventory {
+        match categories.iter_mut().find(|(name, _)| name == item.category_label()) {
+            Some((_, value)) => *value += item.value(),
+            None => categories.push((item.category_label().to_string(), item.value())),
+        }
+    }
+    categories.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let category_rows: Vec<(String, f32, String)> =
+        categories.into_iter().map(|(name, value)| (name, value, money(value))).collect();
+
+    let margin_rows: Vec<(String, f32, String)> = dashboard
+        .costing
+        .iter()
+        .map(|r| match (r.margin_percent, r.unit_margin) {
+            (Some(percent), Some(margin)) => (r.recipe_name.clone(), percent, format!("{:.1}% ({}/unit)", percent, money(margin))),
+            _ => (r.recipe_name.clone(), 0.0, format!("no MSRP, cost {}/unit", money(r.unit_cost))),
+        })
+        .collect();
+
+    let mut low_stock = String::new();
+    for (item, batches_left) in &dashboard.low_stock {
+        low_stock.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td class=\"num\">{:.2} {}</td><td class=\"num\">{:.1}</td></tr>\n",
+            escape_html(&item.name), escape_html(item.category_label()), item.quantity, escape_html(&item.unit), batches_left
+        ));
+    }
+    let low_stock = if low_stock.is_empty() {
+        "<p class=\"empty\">Every recipe ingredient is above the threshold.</p>".to_string()
+    } else {
+        format!(
+            "<table><thead><tr><th>Item</th><th>Category</th><th class=\"num\">On hand</th><th class=\"num\">Batches left</th></tr></thead>\n<tbody>\n{}</tbody></table>",
+            low_stock
+        )
+    };
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Bakery Dashboard {start} to {end}</title>
+<style>
+body {{ font-family: Helvetica, Arial, sans-serif; color: #333; background: #faf7f2; max-width: 860px; margin: 2em auto; padding: 0 1em; }}
+h1 {{ margin-bottom: 0.2em; }}
+.sub {{ color: #777; margin-top: 0; }}
+.cards {{ display: grid; grid-template-columns: repeat(5, 1fr); gap: 10px; margin: 1.5em 0; }}
+.card {{ background: #fff; border-radius: 6px; padding: 10px 12px; box-shadow: 0 1px 2px rgba(0,0,0,0.08); }}
+.card .k {{ font-size: 0.8em; color: #777; text-transform: uppercase; }}
+.card .v {{ font-size: 1.3em; font-weight: bold; margin-top: 4px; }}
+section {{ background: #fff; border-radius: 6px; padding: 12px 16px; margin-bottom: 16px; box-shadow: 0 1px 2px rgba(0,0,0,0.08); }}
+h2 {{ font-size: 1.1em; margin: 0.2em 0 0.8em; }}
+svg text {{ font-size: 11px; fill: #555; }}
+svg .label {{ font-size: 12px; fill: #333; }}
+svg .value {{ font-size: 12px; fill: #333; font-weight: bold; }}
+svg .grid {{ stroke: #eee; }}
+table {{ width: 100%; border-collapse: collapse; }}
+th, td {{ padding: 5px 8px; border-bottom: 1px solid #eee; text-align: left; }}
+.num {{ text-align: right; }}
+.neg {{ color: {expense_color}; }}
+.empty {{ color: #999; font-style: italic; }}
+@media print {{ body {{ background: #fff; }} section, .card {{ box-shadow: none; border: 1px solid #ddd; }} }}
+</style>
+</head>
+<body>
+<h1>🍞 Bakery Dashboard</h1>
+<p class="sub">{start} to {end} · generated {generated}</p>
+<div class="cards">
+<div class="card"><div class="k">Revenue</div><div class="v">{revenue}</div></div>
+<div class="card"><div class="k">Expenses</div><div class="v">{expenses}</div></div>
+<div class="card"><div class="k">Net</div><div class="v{net_class}">{net}</div></div>
+<div class="card"><div class="k">Inventory value</div><div class="v">{inventory_value}</div></div>
+<div class="card"><div class="k">Low-stock items</div><div class="v">{low_count}</div></div>
+</div>
+<section><h2>Revenue vs expenses</h2>
+{revenue_chart}
+</section>
+<section><h2>Top-selling products</h2>
+{products_chart}
+</section>
+<section><h2>Inventory value by category</h2>
+{inventory_chart}
+</section>
+<section><h2>Low stock (under {min_batches} batches on hand)</h2>
+{low_stock}
+</section>
+<section><h2>Recipe margins at MSRP</h2>
+{margin_chart}
+</section>
+</body>
+</html>
+"#,
+        start = escape_html(&dashboard.start_date),
+        end = escape_html(&dashboard.end_date),
+        generated = escape_html(&dashboard.generated_at),
+        expense_color = EXPENSE_COLOR,
+        revenue = money(revenue),
+        expenses = money(expenses),
+        net = money(revenue - expenses),
+        net_class = if revenue - expenses < 0.0 { " neg" } else { "" },
+        inventory_value = money(inventory_value),
+        low_count = dashboard.low_stock.len(),
+        revenue_chart = svg_revenue_expense_chart(&dashboard.revenue_expenses),
+        products_chart = if top_products.is_empty() {
+            "<p class=\"empty\">No itemized sales in this period.</p>".to_string()
+        } else {
+            svg_horizontal_bars(&top_products, REVENUE_COLOR)
+        },
+        inventory_chart = svg_horizontal_bars(&category_rows, ACCENT_COLOR),
+        min_batches = dashboard.min_batches,
+        low_stock = low_stock,
+        margin_chart = svg_horizontal_bars(&margin_rows, ACCENT_COLOR),
+    )
+}
+
+// Pull list CSV: one row per ingredient with what's required, on hand and short
+pub fn write_pull_list_csv(path: &Path, lines: &[PullListLine]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut writer = Writer::from_path(path)?;
+
+    writer.write_record(["name", "unit", "required", "on_hand", "shortfall", "recipes"])?;
+    for line in lines {
+        writer.write_record([
+            line.name.clone(),
+            line.unit.clone(),
+            format!("{:.4}", line.required),
+            line.on_hand.map_or(String::new(), |q| format!("{:.4}", q)),
+            format!("{:.4}", line.shortfall()),
+            line.recipes.join("; "),
+        ])?;
+    }
+
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ages_invoices_by_days_past_due() {
+        let due = "2026-01-31";
+        let bucket = |today: &str| AGING_BUCKETS[aging_bucket(due, today)];
+        assert_eq!(bucket("2026-01-15"), "Current");
+        assert_eq!(bucket("2026-01-31"), "Current");
+        assert_eq!(bucket("2026-02-01"), "1-30");
+        assert_eq!(bucket("2026-03-02"), "1-30");
+        assert_eq!(bucket("2026-03-03"), "31-60");
+        assert_eq!(bucket("2026-04-01"), "31-60");
+        assert_eq!(bucket("2026-04-02"), "61-90");
+        assert_eq!(bucket("2026-05-01"), "61-90");
+        assert_eq!(bucket("2026-05-02"), "90+");
+        assert_eq!(aging_bucket("not a date", "2026-05-02"), 0);
+    }
+
+    #[test]
+    fn export_path_resolves_blank_folder_and_file_input() {
+        assert_eq!(export_path("", "inventory.csv", false), Path::new("reports").join("inventory.csv"));
+        assert_eq!(export_path("exports", "inventory.csv", false), Path::new("exports").join("inventory.csv"));
+        assert_eq!(export_path("exports/v1.2", "inventory.csv", false), Path::new("exports/v1.2").join("inventory.csv"));
+        assert_eq!(export_path("exports/stock.csv", "inventory.csv", false), PathBuf::from("exports/stock.csv"));
+        assert_eq!(export_path("STOCK.CSV", "inventory.csv", false), PathBuf::from("STOCK.CSV"));
+    }
+
+    #[test]
+    fn timestamped_export_path_keeps_folder_and_extension() {
+        let path = export_path("exports", "inventory.csv", true);
+        assert_eq!(path.parent(), Some(Path::new("exports")));
+        assert_eq!(path.extension().and_then(|e| e.to_str()), Some("csv"));
+
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap();
+        let stamp = stem.strip_prefix("inventory-").unwrap();
+        assert!(chrono::NaiveDateTime::parse_from_str(stamp, "%Y%m%d-%H%M%S").is_ok(), "{}", stamp);
+    }
+}
@@ -0,0 +1,273 @@
+// src/schedule.rs
+// Backward production scheduling: start from when each product must be ready and walk its
+// recipe steps back in time, then check the shared equipment for overbooking.
+// Pure calculations only; callers load recipes, steps and resources and print the result.
+
+use crate::models::{RecipeStep, Resource};
+use chrono::{Duration, NaiveDateTime};
+
+// One product to have ready: a recipe, how many batches and when
+#[derive(Debug, Clone)]
+pub struct ScheduleItem {
+    pub recipe_name: String,
+    pub batches: f32,
+    pub ready_by: NaiveDateTime,
+    pub steps: Vec<RecipeStep>,
+}
+
+impl ScheduleItem {
+    pub fn label(&self) -> String {
+        format!("{} x{}", self.recipe_name, self.batches)
+    }
+}
+
+// A step placed on the timeline
+#[derive(Debug, Clone)]
+pub struct ScheduledStep {
+    // Index into the items passed to build_schedule
+    pub item: usize,
+    pub label: String,
+    pub position: i32,
+    pub step_type: String,
+    pub description: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub resource: Option<String>,
+    // Batch slots held on the resource
+    pub slots: i32,
+    // Back-to-back loads when the batches don't fit on the equipment at once
+    pub loads: i32,
+}
+
+// A stretch of time where a resource is asked to hold more batches than it can
+#[derive(Debug, Clone)]
+pub struct ResourceConflict {
+    pub resource: String,
+    pub capacity: i32,
+    pub peak: i32,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub labels: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Schedule {
+    // Every step, earliest first
+    pub steps: Vec<ScheduledStep>,
+    // When each item has to start, in item order
+    pub starts: Vec<NaiveDateTime>,
+    pub conflicts: Vec<ResourceConflict>,
+    pub warnings: Vec<String>,
+}
+
+// Work each item back from its ready-by time. Steps with a duration range are planned at the
+// long end so the product is never late; untimed steps take no time and are reported.
+// A step with more batches than its equipment holds runs as back-to-back loads.
+pub fn build_schedule(items: &[ScheduleItem], resources: &[Resource]) -> Schedule {
+    let mut steps = Vec::new();
+    let mut starts = Vec::new();
+    let mut warnings = Vec::new();
+
+    for (index, item) in items.iter().enumerate() {
+        let label = item.label();
+        if item.steps.is_empty() {
+            warnings.push(format!("{} has no steps; add them under Recipe Steps to schedule it", label));
+        }
+
+        let batch_slots = (item.batches.ceil() as i32).max(1);
+        let mut untimed = Vec::new();
+        let mut end = item.ready_by;
+        for step in item.steps.iter().rev() {
+            let minutes = match step.max_minutes.or(step.min_minutes) {
+                Some(minutes) => minutes,
+                None => {
+                    untimed.push(format!(
+                        "{}: step {} ({}) has no duration and was scheduled as instant",
+                        label, step.position, step.description
+                    ));
+                    0
+                }
+            };
+
+            let resource = resources.iter().find(|r| r.step_type == step.step_type);
+            let (slots, loads) = match resource {
+                Some(resource) if batch_slots > resource.capacity => {
+                    let capacity = resource.capacity.max(1);
+                    (capacity, (batch_slots + capacity - 1) / capacity)
+                }
+                _ => (batch_slots, 1),
+            };
+
+            let start = end - Duration::minutes((minutes * loads) as i64);
+            steps.push(ScheduledStep {
+                item: index,
+                label: label.clone(),
+                position: step.position,
+                step_type: step.step_type.clone(),
+                description: step.description.clone(),
+                start,
+                end,
+                resource: resource.map(|r| r.name.clone()),
+                slots,
+                loads,
+            });
+            end = start;
+        }
+        starts.push(end);
+        warnings.extend(untimed.into_iter().rev());
+    }
+
+    steps.sort_by_key(|s| (s.start, s.item, s.position));
+    let conflicts = find_conflicts(&steps, resources);
+
+    Schedule { steps, starts, conflicts, warnings }
+}
+
+// Sweep each resource's bookings and collect the stretches where they exceed its capacity
+fn find_conflicts(steps: &[ScheduledStep], resources: &[Resource]) -> Vec<ResourceConflict> {
+    let mut conflicts: Vec<ResourceConflict> = Vec::new();
+
+    for resource in resources {
+        let bookings: Vec<&ScheduledStep> = steps
+            .iter()
+            .filter(|s| s.resource.as_deref() == Some(resource.name.as_str()) && s.end > s.start)
+            .collect();
+
+        let mut times: Vec<NaiveDateTime> = bookings.iter().flat_map(|s| [s.start, s.end]).collect();
+        times.sort();
+        times.dedup();
+
+        for window in times.windows(2) {
+            let (from, to) = (window[0], window[1]);
+            let active: Vec<&&ScheduledStep> = bookings.iter().filter(|s| s.start <= from && s.end > from).collect();
+            let load: i32 = active.iter().map(|s| s.slots).sum();
+            if load <= resource.capacity {
+                continue;
+            }
+
+            let labels = active.iter().map(|s| s.label.clone());
+            match conflicts.last_mut() {
+                Some(last) if last.resource == resource.name && last.end == from => {
+                    last.end = to;
+                    last.peak = last.peak.max(load);
+                    for label in labels {
+                        if !last.labels.contains(&label) {
+                            last.labels.push(label);
+                        }
+                    }
+                }
+                _ => {
+                    let mut unique: Vec<String> = Vec::new();
+                    for label in labels {
+                        if !unique.contains(&label) {
+                            unique.push(label);
+                        }
+                    }
+                    conflicts.push(ResourceConflict {
+                        resource: resource.name.clone(),
+                        capacity: resource.capacity,
+                        peak: load,
+                        start: from,
+                        end: to,
+                        labels: unique,
+                    });
+                }
+            }
+        }
+    }
+
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn step(position: i32, step_type: &str, min_minutes: Option<i32>, max_minutes: Option<i32>) -> RecipeStep {
+        RecipeStep {
+            id: position,
+            recipe_id: 1,
+            position,
+            step_type: step_type.to_string(),
+            description: step_type.to_string(),
+            min_minutes,
+            max_minutes,
+            temperature_f: None,
+            notes: None,
+        }
+    }
+
+    fn item(recipe_name: &str, batches: f32, ready_by: &str, steps: Vec<RecipeStep>) -> ScheduleItem {
+        ScheduleItem { recipe_name: recipe_name.to_string(), batches, ready_by: at(ready_by), steps }
+    }
+
+    fn oven(capacity: i32) -> Resource {
+        Resource { id: 1, name: "Deck Oven".to_string(), step_type: "bake".to_string(), capacity }
+    }
+
+    #[test]
+    fn works_back_from_the_ready_time() {
+        let steps = vec![
+            step(1, "mix", Some(15), None),
+            step(2, "bulk ferment", Some(120), Some(180)),
+            step(3, "shape", None, None),
+            step(4, "bake", Some(40), Some(40)),
+        ];
+        let schedule = build_schedule(&[item("Babka", 1.0, "2026-10-18 08:00", steps)], &[oven(2)]);
+
+        let times: Vec<(i32, NaiveDateTime, NaiveDateTime)> =
+            schedule.steps.iter().map(|s| (s.position, s.start, s.end)).collect();
+        // Bulk ferment is planned at its long end; the untimed shape takes no time
+        assert_eq!(
+            times,
+            vec![
+                (1, at("2026-10-18 04:05"), at("2026-10-18 04:20")),
+                (2, at("2026-10-18 04:20"), at("2026-10-18 07:20")),
+                (3, at("2026-10-18 07:20"), at("2026-10-18 07:20")),
+                (4, at("2026-10-18 07:20"), at("2026-10-18 08:00")),
+            ]
+        );
+        assert_eq!(schedule.starts, vec![at("2026-10-18 04:05")]);
+        assert_eq!(schedule.steps[3].resource.as_deref(), Some("Deck Oven"));
+        assert_eq!(schedule.warnings.len(), 1);
+        assert!(schedule.warnings[0].contains("step 3"));
+        assert!(schedule.conflicts.is_empty());
+    }
+
+    #[test]
+    fn runs_extra_batches_as_back_to_back_loads() {
+        let steps = vec![step(1, "mix", Some(20), None), step(2, "bake", Some(40), None)];
+        let schedule = build_schedule(&[item("Babka", 4.5, "2026-10-18 08:00", steps)], &[oven(2)]);
+
+        // 4.5 batches need 5 slots; a two-deck oven bakes them in three loads
+        let bake = schedule.steps.iter().find(|s| s.step_type == "bake").unwrap();
+        assert_eq!((bake.slots, bake.loads), (2, 3));
+        assert_eq!((bake.start, bake.end), (at("2026-10-18 06:00"), at("2026-10-18 08:00")));
+        let mix = schedule.steps.iter().find(|s| s.step_type == "mix").unwrap();
+        assert_eq!((mix.slots, mix.loads), (5, 1));
+        assert_eq!(schedule.starts, vec![at("2026-10-18 05:40")]);
+        assert!(schedule.conflicts.is_empty());
+    }
+
+    #[test]
+    fn flags_overlapping_bookings_past_capacity() {
+        let items = vec![
+            item("Babka", 1.0, "2026-10-18 08:00", vec![step(1, "bake", Some(60), None)]),
+            item("Challah", 2.0, "2026-10-18 08:30", vec![step(1, "bake", Some(45), None)]),
+            // Starts as soon as the others finish, so it doesn't overlap them
+            item("Rye", 2.0, "2026-10-18 09:30", vec![step(1, "bake", Some(60), None)]),
+        ];
+        let schedule = build_schedule(&items, &[oven(2)]);
+
+        assert_eq!(schedule.conflicts.len(), 1);
+        let conflict = &schedule.conflicts[0];
+        assert_eq!(conflict.resource, "Deck Oven");
+        assert_eq!((conflict.capacity, conflict.peak), (2, 3));
+        assert_eq!((conflict.start, conflict.end), (at("2026-10-18 07:45"), at("2026-10-18 08:00")));
+        assert_eq!(conflict.labels, vec!["Babka x1".to_string(), "Challah x2".to_string()]);
+    }
+}
@@ -0,0 +1,360 @@
+// src/steps.rs
+// Recipe method steps: reading durations and temperatures out of free text, guessing each step's
+// type, splitting a recipe's instructions into steps and totalling their time.
+// Pure calculations only; callers load and save the steps.
+
+use crate::models::{RecipeStep, StepTotals, STEP_TYPES};
+
+// Minutes as "4h 30m", "45m" or "2h"
+pub fn format_minutes(minutes: i32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
+// Add up a recipe's step durations, per step type and overall
+pub fn step_totals(steps: &[RecipeStep]) -> StepTotals {
+    let mut totals = StepTotals { min_minutes: 0, max_minutes: 0, untimed: 0, by_type: Vec::new() };
+    for step_type in STEP_TYPES {
+        let of_type: Vec<&RecipeStep> = steps.iter().filter(|s| s.step_type == step_type).collect();
+        if of_type.is_empty() {
+            continue;
+        }
+        let min = of_type.iter().map(|s| s.min_minutes.unwrap_or(0)).sum();
+        let max = of_type.iter().map(|s| s.max_minutes.or(s.min_minutes).unwrap_or(0)).sum();
+        totals.by_type.push((step_type.to_string(), min, max));
+    }
+    totals.min_minutes = totals.by_type.iter().map(|(_, min, _)| min).sum();
+    totals.max_minutes = totals.by_type.iter().map(|(_, _, max)| max).sum();
+    totals.untimed = steps.iter().filter(|s| s.min_minutes.is_none()).count();
+    totals
+}
+
+// Read a duration such as "4 hours 30 minutes", "30-45 min", "1 to 2 hrs", "1h30m" or
+// "overnight" as a (min, max) range of minutes; None when no duration is found
+pub fn parse_duration_range(text: &str) -> Option<(i32, i32)> {
+    let text = text.to_lowercase().replace('–', "-").replace(" to ", "-");
+    if text.contains("overnight") {
+        return Some((8 * 60, 12 * 60));
+    }
+
+    // Split into number runs (keeping '.' and '-') and letter runs
+    let mut tokens: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut numeric = false;
+    for c in text.chars() {
+        let is_num = c.is_ascii_digit() || c == '.' || (c == '-' && numeric);
+        let is_word = c.is_alphabetic();
+        if (is_num && numeric) || (is_word && !numeric && !current.is_empty()) {
+            current.push(c);
+            continue;
+        }
+        if !current.is_empty() {
+            tokens.push(std::mem::take(&mut current));
+        }
+        if is_num || is_word {
+            current.push(c);
+            numeric = is_num;
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    let (mut min, mut max, mut found) = (0.0, 0.0, false);
+    for pair in tokens.windows(2) {
+        let (amount, unit) = (&pair[0], &pair[1]);
+        let per_unit = match unit.as_str() {
+            "h" | "hr" | "hrs" | "hour" | "hours" => 60.0,
+            "m" | "min" | "mins" | "minute" | "minutes" => 1.0,
+            _ => continue,
+        };
+        let mut bounds = amount.trim_end_matches('-').splitn(2, '-').map(|n| n.parse::<f32>());
+        let low = match bounds.next() {
+            Some(Ok(low)) => low,
+            _ => continue,
+        };
+        let high = match bounds.next() {
+            Some(Ok(high)) => high,
+            _ => low,
+        };
+        min += low * per_unit;
+        max += high * per_unit;
+        found = true;
+    }
+
+    if found { Some((min.round() as i32, max.round() as i32)) } else { None }
+}
+
+// Read an oven or dough temperature such as "350F", "350 F", "350°F" or "180 °C", returned in °F.
+// A bare "c" after a space ("2 c flour") is a cup, not Celsius.
+pub fn parse_temperature_f(text: &str) -> Option<f32> {
+    let chars: Vec<char> = text.to_lowercase().chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+            i += 1;
+        }
+        let number: f32 = chars[start..i].iter().collect::<String>().parse().ok()?;
+        let mut j = i;
+        while j < chars.len() && (chars[j] == ' ' || chars[j] == '°') {
+            j += 1;
+        }
+        let attached = j == i || chars[i..j].contains(&'°');
+        let unit_ends_word = chars.get(j + 1).is_none_or(|c| !c.is_alphabetic());
+        match chars.get(j) {
+            Some('f') if unit_ends_word => return Some(number),
+            Some('c') if unit_ends_word && attached => return Some(number * 9.0 / 5.0 + 32.0),
+            _ => {}
+        }
+    }
+    None
+}
+
+// Guess a step's type from the whole words in its text; bake and proof words win over mixing
+// and shaping. Preheating is prep, so it never books the oven as a bake step.
+pub fn step_type_for(text: &str) -> &'static str {
+    let words: Vec<String> = text
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(String::from)
+        .collect();
+    let padded = format!(" {} ", words.join(" "));
+    let has = |term: &&str| padded.contains(&format!(" {} ", term));
+
+    if ["preheat", "preheating"].iter().any(has) {
+        return "other";
+    }
+    let rules: [(&str, &[&str]); 6] = [
+        ("bake", &["bake", "bakes", "baking", "oven"]),
+        ("cool", &["cool", "cools", "cooling"]),
+        ("bulk ferment", &["bulk", "ferment", "fermentation", "first rise", "autolyse"]),
+        ("proof", &["proof", "proofing", "second rise", "final rise", "rise", "rises"]),
+        ("mix", &["mix", "mixing", "knead", "combine", "prepare", "make", "whisk", "stir", "cream"]),
+        ("shape", &["shape", "shaping", "roll", "braid", "divide", "fill", "form", "slice", "score", "spread"]),
+    ];
+    rules
+        .iter()
+        .find(|(_, terms)| terms.iter().any(has))
+        .map_or("other", |(step_type, _)| *step_type)
+}
+
+// Break free-text instructions ("1. Mix\n2. Bake at 350F") into ordered steps.
+// Recipe-level prep/bake/total times fill in steps that don't state their own duration:
+// bake time goes to the bake step, prep time to the first mix (or shaping) step, and whatever is left
+// of the total to the first ferment or proof step (or a rest step before baking).
+pub fn parse_instructions(
+    instructions: &str,
+    prep_time: Option<&str>,
+    bake_time: Option<&str>,
+    total_time: Option<&str>,
+) -> Vec<RecipeStep> {
+    let mut steps: Vec<RecipeStep> = instructions
+        .lines()
+        .map(|line| line.trim().trim_start_matches(|c: char| c.is_ascii_digit()).trim_start_matches(['.', ')', '-']).trim())
+        .filter(|line| !line.is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            let duration = parse_duration_range(line);
+            RecipeStep {
+                id: 0,
+                recipe_id: 0,
+                position: i as i32 + 1,
+                step_type: step_type_for(line).to_string(),
+                description: line.to_string(),
+                min_minutes: duration.map(|(min, _)| min),
+                max_minutes: duration.map(|(_, max)| max),
+                temperature_f: parse_temperature_f(line),
+                notes: None,
+            }
+        })
+        .collect();
+
+    let prep = prep_time.and_then(parse_duration_range);
+    let bake = bake_time.and_then(parse_duration_range);
+    fill_untimed_step(&mut steps, &["bake"], bake);
+    fill_untimed_step(&mut steps, &["mix", "shape"], prep);
+
+    // Whatever the steps don't already account for goes to the first untimed ferment or proof step,
+    // or to a rest step before baking so the total isn't lost
+    let remainder = total_time.and_then(parse_duration_range).map(|(min, max)| {
+        let totals = step_totals(&steps);
+        ((min - totals.min_minutes).max(0), (max - totals.max_minutes).max(0))
+    });
+    if let Some((min, max)) = remainder.filter(|(_, max)| *max > 0) {
+        if !fill_untimed_step(&mut steps, &["bulk ferment", "proof"], Some((min, max))) {
+            let at = steps.iter().position(|s| s.step_type == "bake").unwrap_or(steps.len());
+            steps.insert(at, RecipeStep {
+                id: 0,
+                recipe_id: 0,
+                position: 0,
+                step_type: "other".to_string(),
+                description: "Rest (time left over from the recipe's total time)".to_string(),
+                min_minutes: Some(min),
+                max_minutes: Some(max),
+                temperature_f: None,
+                notes: None,
+            });
+            for (i, step) in steps.iter_mut().enumerate() {
+                step.position = i as i32 + 1;
+            }
+        }
+    }
+
+    steps
+}
+
+// Give the first untimed step of one of the types a duration; false when there is no such step
+fn fill_untimed_step(steps: &mut [RecipeStep], step_types: &[&str], duration: Option<(i32, i32)>) -> bool {
+    let Some((min, max)) = duration else { return false };
+    match steps.iter_mut().find(|s| step_types.contains(&s.step_type.as_str()) && s.min_minutes.is_none()) {
+        Some(step) => {
+            step.min_minutes = Some(min);
+            step.max_minutes = Some(max);
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(step_type: &str, min: Option<i32>, max: Option<i32>) -> RecipeStep {
+        RecipeStep {
+            id: 0,
+            recipe_id: 0,
+            position: 1,
+            step_type: step_type.to_string(),
+            description: String::new(),
+            min_minutes: min,
+            max_minutes: max,
+            temperature_f: None,
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn parses_durations_and_ranges() {
+        assert_eq!(parse_duration_range("4 hours 30 minutes"), Some((270, 270)));
+        assert_eq!(parse_duration_range("Bake 30-45 min"), Some((30, 45)));
+        assert_eq!(parse_duration_range("Rise 1 to 2 hours"), Some((60, 120)));
+        assert_eq!(parse_duration_range("1h30m"), Some((90, 90)));
+        assert_eq!(parse_duration_range("1.5 hrs"), Some((90, 90)));
+        assert_eq!(parse_duration_range("Retard overnight"), Some((480, 720)));
+        assert_eq!(parse_duration_range("Bake at 350F"), None);
+        assert_eq!(parse_duration_range("Mix well"), None);
+    }
+
+    #[test]
+    fn parses_temperatures_into_fahrenheit() {
+        assert_eq!(parse_temperature_f("Bake at 350F"), Some(350.0));
+        assert_eq!(parse_temperature_f("Bake at 350°F for 30 min"), Some(350.0));
+        assert_eq!(parse_temperature_f("Oven to 180 °C"), Some(356.0));
+        assert_eq!(parse_temperature_f("Proof at 26c"), Some(78.8));
+        // A bare letter after a space is a unit of measure, not a temperature
+        assert_eq!(parse_temperature_f("Add 2 c flour"), None);
+        assert_eq!(parse_temperature_f("Bake at 350 F"), Some(350.0));
+        assert_eq!(parse_temperature_f("Bake 350 for 30 minutes"), None);
+        assert_eq!(parse_temperature_f("Fold 4 times"), None);
+    }
+
+    #[test]
+    fn guesses_step_types_by_priority() {
+        assert_eq!(step_type_for("Shape and proof"), "proof");
+        assert_eq!(step_type_for("Fill, bake, dust with sugar"), "bake");
+        assert_eq!(step_type_for("Prepare chocolate filling"), "mix");
+        assert_eq!(step_type_for("Roll out dough"), "shape");
+        assert_eq!(step_type_for("Bulk ferment with folds"), "bulk ferment");
+        assert_eq!(step_type_for("Cool on a rack"), "cool");
+        assert_eq!(step_type_for("Ice while warm"), "other");
+        // Whole words only: none of these are ferments, bakes or shaping
+        assert_eq!(step_type_for("Mix the preferment"), "mix");
+        assert_eq!(step_type_for("Check the baker's percentages"), "other");
+        assert_eq!(step_type_for("Weigh out the formula"), "other");
+        assert_eq!(step_type_for("Cut into uniform pieces"), "other");
+        assert_eq!(step_type_for("Preheat oven to 450F"), "other");
+        assert_eq!(step_type_for("Let the first rise go 2 hours"), "bulk ferment");
+    }
+
+    #[test]
+    fn recipe_bake_time_skips_the_preheat_line() {
+        let steps = parse_instructions("1. Preheat oven to 350 F\n2. Mix the batter\n3. Bake until golden", None, Some("35 minutes"), None);
+        let types: Vec<&str> = steps.iter().map(|s| s.step_type.as_str()).collect();
+        assert_eq!(types, vec!["other", "mix", "bake"]);
+        assert_eq!(steps[0].temperature_f, Some(350.0));
+        assert_eq!(steps[0].min_minutes, None);
+        assert_eq!((steps[2].min_minutes, steps[2].max_minutes), (Some(35), Some(35)));
+    }
+
+    #[test]
+    fn parses_numbered_instructions_and_fills_recipe_times() {
+        let steps = parse_instructions(
+            "1. Make brioche dough\n2. Prepare chocolate filling\n3. Shape and proof\n4. Bake at 350F",
+            Some("20 minutes"),
+            Some("25 minutes"),
+            Some("4 hours 30 minutes"),
+        );
+        let summary: Vec<(&str, Option<i32>)> = steps.iter().map(|s| (s.step_type.as_str(), s.min_minutes)).collect();
+        assert_eq!(
+            summary,
+            vec![("mix", Some(20)), ("mix", None), ("proof", Some(225)), ("bake", Some(25))]
+        );
+        assert_eq!(steps[0].description, "Make brioche dough");
+        assert_eq!(steps[3].temperature_f, Some(350.0));
+        assert_eq!(step_totals(&steps).min_minutes, 270);
+    }
+
+    #[test]
+    fn leftover_total_time_becomes_a_rest_before_baking() {
+        let steps = parse_instructions(
+            "1. Prepare croissant dough\n2. Make almond cream\n3. Fill, bake, dust with powdered sugar",
+            Some("20 minutes"),
+            Some("25 minutes"),
+            Some("4 hours 30 minutes"),
+        );
+        let types: Vec<&str> = steps.iter().map(|s| s.step_type.as_str()).collect();
+        assert_eq!(types, vec!["mix", "mix", "other", "bake"]);
+        assert_eq!(steps[2].min_minutes, Some(225));
+        assert_eq!(steps.iter().map(|s| s.position).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(step_totals(&steps).min_minutes, 270);
+    }
+
+    #[test]
+    fn leftover_time_ignores_durations_the_steps_already_state() {
+        let steps = parse_instructions(
+            "Mix 15 min\nBulk ferment\nBake 40 min",
+            Some("15 minutes"),
+            Some("40 minutes"),
+            Some("3 hours"),
+        );
+        assert_eq!(steps[1].min_minutes, Some(125));
+        assert_eq!(step_totals(&steps).min_minutes, 180);
+    }
+
+    #[test]
+    fn totals_ranges_by_type_and_counts_untimed_steps() {
+        let steps = vec![
+            step("mix", Some(20), None),
+            step("bulk ferment", Some(120), Some(180)),
+            step("shape", None, None),
+            step("proof", Some(60), Some(90)),
+            step("bake", Some(35), Some(40)),
+        ];
+        let totals = step_totals(&steps);
+        assert_eq!((totals.min_minutes, totals.max_minutes), (235, 330));
+        assert_eq!(totals.untimed, 1);
+        assert_eq!(totals.by_type[0], ("mix".to_string(), 20, 20));
+        assert_eq!(totals.by_type[2], ("shape".to_string(), 0, 0));
+        assert_eq!(totals.label(), "3h 55m-5h 30m (+1 untimed step)");
+    }
+}